@@ -0,0 +1,47 @@
+use by_loco_openapi::utils::CachedSpec;
+use criterion::{criterion_group, criterion_main, Criterion};
+use utoipa::openapi::{
+    path::{HttpMethod, OperationBuilder, PathItem},
+    Info, OpenApi, OpenApiBuilder, PathsBuilder, ResponseBuilder,
+};
+
+// Build a spec with 500 paths, roughly the size of a large API
+fn large_spec() -> OpenApi {
+    let paths = (0..500).fold(PathsBuilder::new(), |paths, i| {
+        paths.path(
+            format!("/api/resource_{i}/{{id}}"),
+            PathItem::new(
+                HttpMethod::Get,
+                OperationBuilder::new()
+                    .operation_id(Some(format!("get_resource_{i}")))
+                    .summary(Some(format!("Get resource {i}")))
+                    .response(
+                        "200",
+                        ResponseBuilder::new().description("Resource found").build(),
+                    ),
+            ),
+        )
+    });
+
+    OpenApiBuilder::new()
+        .info(Info::new("Large API", "1.0.0"))
+        .paths(paths.build())
+        .build()
+}
+
+fn spec_serialization(c: &mut Criterion) {
+    let spec = large_spec();
+    let cached = CachedSpec::new(spec.clone()).unwrap();
+
+    c.bench_function("json serialized per request", |b| {
+        b.iter(|| serde_json::to_vec(&spec).unwrap());
+    });
+    c.bench_function("yaml serialized per request", |b| {
+        b.iter(|| spec.to_yaml().unwrap());
+    });
+    c.bench_function("json cached", |b| b.iter(|| cached.json().clone()));
+    c.bench_function("yaml cached", |b| b.iter(|| cached.yaml().clone()));
+}
+
+criterion_group!(benches, spec_serialization);
+criterion_main!(benches);
@@ -1,10 +1,19 @@
-use std::sync::OnceLock;
+use std::collections::BTreeMap;
+use std::sync::{Mutex, OnceLock, RwLock};
 
+use serde_json::Value;
 use utoipa::{
-    openapi::security::{ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityScheme},
+    openapi::{
+        extensions::Extensions,
+        security::{
+            ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityRequirement, SecurityScheme,
+        },
+    },
     Modify,
 };
 
+use crate::openapi::for_each_operation;
+
 // Import Loco types for conversion
 use loco_rs::{app::AppContext, config::JWTLocation as LocoJWTLocation};
 
@@ -40,6 +49,21 @@ impl From<&AppContext> for JWTLocation {
     }
 }
 
+impl JWTLocation {
+    // JWT location of a named auth config, for apps with multiple JWT configs.
+    // Read from `settings.auth.<name>.jwt.location`, which has the same format
+    // as `auth.jwt.location`, falls back to the `auth.jwt` location
+    #[must_use]
+    pub fn from_named(ctx: &AppContext, name: &str) -> Self {
+        ctx.config
+            .settings
+            .as_ref()
+            .and_then(|settings| settings.get("auth")?.get(name)?.get("jwt")?.get("location"))
+            .and_then(|location| serde_json::from_value::<LocoJWTLocation>(location.clone()).ok())
+            .map_or_else(|| ctx.into(), |location| (&location).into())
+    }
+}
+
 static JWT_LOCATION: OnceLock<Option<JWTLocation>> = OnceLock::new();
 
 // Main API for working with JWT location - independent from Loco
@@ -51,41 +75,250 @@ pub fn get_jwt_location() -> Option<&'static JWTLocation> {
     JWT_LOCATION.get().unwrap_or(&None).as_ref()
 }
 
-// Security implementation using our JWTLocation
+static JWT_LOCATIONS: OnceLock<Mutex<BTreeMap<String, JWTLocation>>> = OnceLock::new();
+
+fn get_jwt_locations() -> &'static Mutex<BTreeMap<String, JWTLocation>> {
+    JWT_LOCATIONS.get_or_init(|| Mutex::new(BTreeMap::new()))
+}
+
+// Register another location the JWT is accepted from, e.g. a session cookie
+// next to the bearer token, documented as its own `{name}` scheme next to
+// `jwt_token` by the security addons. Routes requiring `jwt_token`, e.g.
+// collected with `openapi_secured`, accept either scheme
+pub fn add_jwt_location(name: &str, jwt_location: JWTLocation) {
+    if let Ok(mut locations) = get_jwt_locations().lock() {
+        locations.insert(name.to_string(), jwt_location);
+    }
+}
+
+// Clears the locations registered with `add_jwt_location`, mostly used for
+// testing
+pub fn clear_jwt_locations() {
+    if let Ok(mut locations) = get_jwt_locations().lock() {
+        locations.clear();
+    }
+}
+
+// Names of the JWT schemes, `jwt_token` and the ones of `add_jwt_location`
+#[must_use]
+pub fn get_jwt_scheme_names() -> Vec<String> {
+    let mut names = vec!["jwt_token".to_string()];
+    if let Ok(locations) = get_jwt_locations().lock() {
+        names.extend(locations.keys().cloned());
+    }
+    names
+}
+
+const DEFAULT_BEARER_FORMAT: &str = "JWT";
+
+static BEARER_FORMAT: RwLock<Option<String>> = RwLock::new(None);
+
+// Set the `bearerFormat` of the `jwt_token` scheme for bearer tokens, e.g. for
+// opaque tokens, replacing the one set before. Defaults to "JWT"
+pub fn set_bearer_format(bearer_format: &str) {
+    if let Ok(mut current) = BEARER_FORMAT.write() {
+        *current = Some(bearer_format.to_string());
+    }
+}
+
+#[must_use]
+pub fn get_bearer_format() -> String {
+    BEARER_FORMAT
+        .read()
+        .ok()
+        .and_then(|bearer_format| bearer_format.clone())
+        .unwrap_or_else(|| DEFAULT_BEARER_FORMAT.to_string())
+}
+
+static JWT_SCOPES: OnceLock<Mutex<BTreeMap<String, String>>> = OnceLock::new();
+
+fn get_jwt_scopes() -> &'static Mutex<BTreeMap<String, String>> {
+    JWT_SCOPES.get_or_init(|| Mutex::new(BTreeMap::new()))
+}
+
+// Declare a scope carried by the JWT, listed with its description in the
+// `x-scopes` extension of the bearer `jwt_token` scheme. Require it on a route
+// with `openapi_scoped` or `security(("jwt_token" = ["{scope}"]))`
+pub fn add_jwt_scope(scope: &str, description: &str) {
+    if let Ok(mut scopes) = get_jwt_scopes().lock() {
+        scopes.insert(scope.to_string(), description.to_string());
+    }
+}
+
+// Clears the scopes declared with `add_jwt_scope`, mostly used for testing
+pub fn clear_jwt_scopes() {
+    if let Ok(mut scopes) = get_jwt_scopes().lock() {
+        scopes.clear();
+    }
+}
+
+// `x-scopes` extension of the declared scopes, bearer schemes have no
+// standard field for them outside of OAuth2 flows
+fn jwt_scopes_extensions() -> Option<Extensions> {
+    let scopes = get_jwt_scopes().lock().ok()?;
+    if scopes.is_empty() {
+        return None;
+    }
+    let mut extensions = Extensions::default();
+    extensions.insert(
+        "x-scopes".to_string(),
+        Value::Object(
+            scopes
+                .iter()
+                .map(|(scope, description)| (scope.clone(), Value::String(description.clone())))
+                .collect(),
+        ),
+    );
+    Some(extensions)
+}
+
+// Security scheme of the JWT token for the given location
+fn jwt_security_scheme(jwt_location: &JWTLocation) -> SecurityScheme {
+    match jwt_location {
+        JWTLocation::Bearer => {
+            let mut http = HttpBuilder::new()
+                .scheme(HttpAuthScheme::Bearer)
+                .bearer_format(get_bearer_format())
+                .build();
+            http.extensions = jwt_scopes_extensions();
+            SecurityScheme::Http(http)
+        }
+        JWTLocation::Query(name) => SecurityScheme::ApiKey(ApiKey::Query(ApiKeyValue::new(name))),
+        JWTLocation::Cookie(name) => SecurityScheme::ApiKey(ApiKey::Cookie(ApiKeyValue::new(name))),
+    }
+}
+
+// Security implementation using our JWTLocation, adds the `jwt_token` and
+// `api_key` schemes
 pub struct SecurityAddon;
 
+impl SecurityAddon {
+    // Only add the `jwt_token` scheme, for apps that don't use API keys
+    #[must_use]
+    pub const fn without_api_key() -> JwtSecurityAddon {
+        JwtSecurityAddon
+    }
+}
+
 impl Modify for SecurityAddon {
     fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
-        if let Some(jwt_location) = get_jwt_location() {
+        JwtSecurityAddon.modify(openapi);
+        if get_jwt_location().is_some() {
             if let Some(components) = openapi.components.as_mut() {
-                components.add_security_schemes_from_iter([
-                    (
-                        "jwt_token",
-                        match jwt_location {
-                            JWTLocation::Bearer => SecurityScheme::Http(
-                                HttpBuilder::new()
-                                    .scheme(HttpAuthScheme::Bearer)
-                                    .bearer_format("JWT")
-                                    .build(),
-                            ),
-                            JWTLocation::Query(name) => {
-                                SecurityScheme::ApiKey(ApiKey::Query(ApiKeyValue::new(name)))
-                            }
-                            JWTLocation::Cookie(name) => {
-                                SecurityScheme::ApiKey(ApiKey::Cookie(ApiKeyValue::new(name)))
-                            }
-                        },
-                    ),
-                    (
-                        "api_key",
-                        SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::new("apikey"))),
-                    ),
-                ]);
+                components.add_security_scheme(
+                    "api_key",
+                    SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::new("apikey"))),
+                );
             }
         }
     }
 }
 
+// Security implementation using our JWTLocation, only adds the `jwt_token`
+// scheme. Use it as `modifiers(&JwtSecurityAddon)` in the `OpenApi` derive
+pub struct JwtSecurityAddon;
+
+impl Modify for JwtSecurityAddon {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let Some(components) = openapi.components.as_mut() else {
+            return;
+        };
+        if let Some(jwt_location) = get_jwt_location() {
+            components.add_security_scheme("jwt_token", jwt_security_scheme(jwt_location));
+        }
+        if let Ok(jwt_locations) = get_jwt_locations().lock() {
+            for (name, jwt_location) in jwt_locations.iter() {
+                components.add_security_scheme(name, jwt_security_scheme(jwt_location));
+            }
+        }
+        if let Ok(api_key_schemes) = get_api_key_schemes().lock() {
+            components.add_security_schemes_from_iter(api_key_schemes.clone());
+        }
+    }
+}
+
+// Adds a requirement of each scheme of `add_jwt_location` next to every
+// `jwt_token` requirement, with the same scopes, so any of the JWT schemes
+// satisfies the operation. Applied when the spec is collected, so the
+// locations registered after the routes are included too
+pub struct JwtSchemeRequirements;
+
+impl Modify for JwtSchemeRequirements {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let names = get_jwt_scheme_names()
+            .into_iter()
+            .filter(|name| name != "jwt_token")
+            .collect::<Vec<_>>();
+        if names.is_empty() {
+            return;
+        }
+
+        for_each_operation(&mut openapi.paths, |_, operation| {
+            let Some(security) = operation.security.as_mut() else {
+                return;
+            };
+            let jwt_token_scopes = security
+                .iter()
+                .filter_map(jwt_token_scopes)
+                .collect::<Vec<_>>();
+            for scopes in jwt_token_scopes {
+                for name in &names {
+                    let requirement = SecurityRequirement::new(name, scopes.clone());
+                    if !security.contains(&requirement) {
+                        security.push(requirement);
+                    }
+                }
+            }
+        });
+    }
+}
+
+// The scopes of a requirement of the `jwt_token` scheme alone
+fn jwt_token_scopes(requirement: &SecurityRequirement) -> Option<Vec<String>> {
+    let value = serde_json::to_value(requirement).ok()?;
+    let schemes = value.as_object()?;
+    if schemes.len() != 1 {
+        return None;
+    }
+    serde_json::from_value(schemes.get("jwt_token")?.clone()).ok()
+}
+
+// Where the API key of a scheme is sent
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApiKeyLocation {
+    Header,
+    Query,
+    Cookie,
+}
+
+static API_KEY_SCHEMES: OnceLock<Mutex<BTreeMap<String, SecurityScheme>>> = OnceLock::new();
+
+fn get_api_key_schemes() -> &'static Mutex<BTreeMap<String, SecurityScheme>> {
+    API_KEY_SCHEMES.get_or_init(|| Mutex::new(BTreeMap::new()))
+}
+
+// Register an API key security scheme, added next to `jwt_token` by the
+// security addons. Document a route with it as `security(("{name}" = []))`
+pub fn add_api_key_scheme(name: &str, location: ApiKeyLocation, param_name: &str) {
+    let value = ApiKeyValue::new(param_name);
+    let api_key = match location {
+        ApiKeyLocation::Header => ApiKey::Header(value),
+        ApiKeyLocation::Query => ApiKey::Query(value),
+        ApiKeyLocation::Cookie => ApiKey::Cookie(value),
+    };
+    if let Ok(mut schemes) = get_api_key_schemes().lock() {
+        schemes.insert(name.to_string(), SecurityScheme::ApiKey(api_key));
+    }
+}
+
+// Clears the schemes registered with `add_api_key_scheme`, mostly used for
+// testing
+pub fn clear_api_key_schemes() {
+    if let Ok(mut schemes) = get_api_key_schemes().lock() {
+        schemes.clear();
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -101,6 +334,112 @@ mod tests {
         assert_eq!(get_jwt_location(), Some(&JWTLocation::Bearer));
     }
 
+    #[tokio::test]
+    async fn test_from_named() {
+        let mut ctx = loco_rs::tests_cfg::app::get_app_context().await;
+        ctx.config.settings = Some(serde_json::json!({
+            "auth": {
+                "service": {
+                    "jwt": { "location": { "from": "Query", "name": "token" } }
+                }
+            }
+        }));
+
+        assert_eq!(
+            JWTLocation::from_named(&ctx, "service"),
+            JWTLocation::Query("token".to_string())
+        );
+        assert_eq!(
+            JWTLocation::from_named(&ctx, "user"),
+            JWTLocation::from(&ctx)
+        );
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_bearer_format() {
+        set_bearer_format("JWE");
+        set_bearer_format("Opaque");
+
+        let json = serde_json::to_value(jwt_security_scheme(&JWTLocation::Bearer)).unwrap();
+        assert_eq!(json["bearerFormat"], "Opaque");
+
+        set_bearer_format(DEFAULT_BEARER_FORMAT);
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_jwt_scopes() {
+        add_jwt_scope("albums:read", "Read albums");
+        add_jwt_scope("albums:write", "Create and update albums");
+
+        let json = serde_json::to_value(jwt_security_scheme(&JWTLocation::Bearer)).unwrap();
+        assert_eq!(
+            json["x-scopes"],
+            serde_json::json!({
+                "albums:read": "Read albums",
+                "albums:write": "Create and update albums",
+            })
+        );
+
+        clear_jwt_scopes();
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_multiple_api_key_schemes() {
+        add_api_key_scheme("api_key_header", ApiKeyLocation::Header, "X-API-Key");
+        add_api_key_scheme("client_token", ApiKeyLocation::Header, "X-Client-Token");
+        let mut openapi = utoipa::openapi::OpenApiBuilder::new()
+            .components(Some(utoipa::openapi::Components::new()))
+            .build();
+
+        JwtSecurityAddon.modify(&mut openapi);
+
+        let json = serde_json::to_value(&openapi).unwrap();
+        let schemes = &json["components"]["securitySchemes"];
+        assert_eq!(schemes["api_key_header"]["name"], "X-API-Key");
+        assert_eq!(schemes["api_key_header"]["in"], "header");
+        assert_eq!(schemes["client_token"]["name"], "X-Client-Token");
+
+        clear_api_key_schemes();
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_multiple_jwt_locations() {
+        set_jwt_location(JWTLocation::Bearer);
+        add_jwt_location("session_cookie", JWTLocation::Cookie("session".to_string()));
+        let mut openapi = utoipa::openapi::OpenApiBuilder::new()
+            .components(Some(utoipa::openapi::Components::new()))
+            .build();
+
+        SecurityAddon.modify(&mut openapi);
+
+        let json = serde_json::to_value(&openapi).unwrap();
+        let schemes = &json["components"]["securitySchemes"];
+        assert_eq!(schemes["jwt_token"]["scheme"], "bearer");
+        assert_eq!(schemes["session_cookie"]["in"], "cookie");
+        assert_eq!(schemes["session_cookie"]["name"], "session");
+        assert_eq!(get_jwt_scheme_names(), vec!["jwt_token", "session_cookie"]);
+
+        clear_jwt_locations();
+    }
+
+    #[test]
+    fn test_without_api_key() {
+        set_jwt_location(JWTLocation::Bearer);
+        let mut openapi = utoipa::openapi::OpenApiBuilder::new()
+            .components(Some(utoipa::openapi::Components::new()))
+            .build();
+
+        SecurityAddon::without_api_key().modify(&mut openapi);
+
+        let schemes = &openapi.components.unwrap().security_schemes;
+        assert!(schemes.contains_key("jwt_token"));
+        assert!(!schemes.contains_key("api_key"));
+    }
+
     #[test]
     fn test_from_loco_jwt_location() {
         let loco_bearer = LocoJWTLocation::Bearer;
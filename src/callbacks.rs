@@ -0,0 +1,154 @@
+use std::collections::BTreeMap;
+use std::sync::{Mutex, OnceLock};
+
+use utoipa::{
+    openapi::{path::Paths, OpenApi},
+    Modify,
+};
+
+use crate::members;
+use crate::openapi::for_each_operation;
+
+/// Callbacks by `operationId` and callback name
+type CallbackRegistry = BTreeMap<String, BTreeMap<String, Paths>>;
+
+static OPERATION_CALLBACKS: OnceLock<Mutex<CallbackRegistry>> = OnceLock::new();
+
+fn get_operation_callbacks() -> &'static Mutex<CallbackRegistry> {
+    OPERATION_CALLBACKS.get_or_init(|| Mutex::new(BTreeMap::new()))
+}
+
+/// Document a callback of an operation, a request the server sends back to
+/// the client asynchronously, e.g. to a URL given in the request body
+///
+/// The callback maps runtime expressions of the callback URL to the path item
+/// of the request, and is added under `callbacks` of the operation with the
+/// given `operationId`. Schemas only used by callbacks must be registered with
+/// [`crate::schemas::register_schema`]
+/// ```rust
+/// use by_loco_openapi::prelude::*;
+/// use utoipa::openapi::{
+///     path::{HttpMethod, OperationBuilder, PathItem, PathsBuilder},
+///     ResponseBuilder,
+/// };
+///
+/// add_callback(
+///     "create_export",
+///     "exportReady",
+///     PathsBuilder::new()
+///         .path(
+///             "{$request.body#/callback_url}",
+///             PathItem::new(
+///                 HttpMethod::Post,
+///                 OperationBuilder::new()
+///                     .response("200", ResponseBuilder::new().description("Received")),
+///             ),
+///         )
+///         .build(),
+/// );
+/// ```
+pub fn add_callback(operation_id: &str, name: &str, callback: Paths) {
+    if let Ok(mut callbacks) = get_operation_callbacks().lock() {
+        callbacks
+            .entry(operation_id.to_string())
+            .or_default()
+            .insert(name.to_string(), callback);
+    }
+}
+
+/// Clears the callbacks added with [`add_callback`], mostly used for testing
+pub fn clear_callbacks() {
+    if let Ok(mut callbacks) = get_operation_callbacks().lock() {
+        callbacks.clear();
+    }
+}
+
+/// Adds the registered callbacks to their operations
+pub struct OperationCallbacks;
+
+impl Modify for OperationCallbacks {
+    fn modify(&self, openapi: &mut OpenApi) {
+        let Ok(callbacks) = get_operation_callbacks().lock() else {
+            return;
+        };
+        if callbacks.is_empty() {
+            return;
+        }
+
+        for_each_operation(&mut openapi.paths, |_, operation| {
+            let Some(operation_callbacks) = operation
+                .operation_id
+                .as_ref()
+                .and_then(|operation_id| callbacks.get(operation_id))
+            else {
+                return;
+            };
+            let Some(items) = members::object_mut(&mut operation.extensions, "callbacks") else {
+                return;
+            };
+            for (name, callback) in operation_callbacks {
+                if let Ok(value) = serde_json::to_value(callback) {
+                    items.insert(name.clone(), value);
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use utoipa::openapi::{
+        path::{HttpMethod, OperationBuilder, PathItem, PathsBuilder},
+        ResponseBuilder,
+    };
+    use utoipa_axum::{router::OpenApiRouter, routes};
+
+    use super::*;
+
+    #[utoipa::path(
+        post,
+        path = "/api/exports",
+        responses((status = 202, description = "Export started")),
+    )]
+    async fn create_export() {}
+
+    #[test]
+    #[serial_test::serial]
+    fn test_operation_callback() {
+        add_callback(
+            "create_export",
+            "exportReady",
+            PathsBuilder::new()
+                .path(
+                    "{$request.body#/callback_url}",
+                    PathItem::new(
+                        HttpMethod::Post,
+                        OperationBuilder::new()
+                            .response("200", ResponseBuilder::new().description("Received")),
+                    ),
+                )
+                .build(),
+        );
+
+        let (_, mut spec) = OpenApiRouter::<()>::new()
+            .routes(routes!(create_export))
+            .split_for_parts();
+        OperationCallbacks.modify(&mut spec);
+
+        let json = serde_json::to_value(&spec).unwrap();
+        assert_eq!(
+            json["paths"]["/api/exports"]["post"]["callbacks"],
+            serde_json::json!({
+                "exportReady": {
+                    "{$request.body#/callback_url}": {
+                        "post": {
+                            "responses": { "200": { "description": "Received" } }
+                        }
+                    }
+                }
+            })
+        );
+
+        clear_callbacks();
+    }
+}
@@ -1,14 +1,25 @@
 use std::collections::BTreeMap;
-use std::sync::OnceLock;
+use std::sync::RwLock;
 
 use loco_rs::Error;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use utoipa::{
+    openapi::{
+        security::SecurityScheme, tag::Tag, ContactBuilder, LicenseBuilder, OpenApi, Server,
+    },
+    Modify,
+};
+
+use crate::members;
+use crate::utils::LEAN_SPEC_URL;
 
-static OPENAPI_CONFIG: OnceLock<Option<OpenAPIConfig>> = OnceLock::new();
+// `None` until the config is set. The set config is leaked to hand it out as
+// `&'static`, it's only replaced by `replace_openapi_config`
+static OPENAPI_CONFIG: RwLock<Option<Option<&'static OpenAPIConfig>>> = RwLock::new(None);
 
 // Newtype wrapper for initialization config
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub struct InitializerConfig<'a>(&'a Option<BTreeMap<String, Value>>);
 
 impl<'a> From<&'a Option<BTreeMap<String, Value>>> for InitializerConfig<'a> {
@@ -17,30 +28,200 @@ impl<'a> From<&'a Option<BTreeMap<String, Value>>> for InitializerConfig<'a> {
     }
 }
 
+/// An invalid `openapi` config is treated like a missing one, see
+/// [`InitializerConfig::try_into_config`] to tell them apart
 impl<'a> From<InitializerConfig<'a>> for Option<OpenAPIConfig> {
     fn from(config: InitializerConfig<'a>) -> Self {
-        config
+        config.try_into_config().ok().flatten()
+    }
+}
+
+/// `OpenAPI` visualizers and the features that compile them in
+const VIEW_FEATURES: [(&str, bool); 4] = [
+    ("redoc", cfg!(feature = "redoc")),
+    ("scalar", cfg!(feature = "scalar")),
+    ("swagger", cfg!(feature = "swagger-cdn")),
+    ("elements", cfg!(feature = "elements")),
+];
+
+impl InitializerConfig<'_> {
+    /// The `openapi` config, `None` when there is no `openapi` key or it's
+    /// empty
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the `openapi` config is present but doesn't
+    /// deserialize
+    pub fn try_into_config(&self) -> Result<Option<OpenAPIConfig>, Error> {
+        let Some(json) = self
             .0
             .as_ref()
             .and_then(|m| m.get("openapi"))
-            .cloned()
-            .and_then(|json| serde_json::from_value(json).ok())
+            .filter(|json| !json.is_null())
+        else {
+            return Ok(None);
+        };
+        serde_json::from_value(json.clone())
+            .map(|config| Some(OpenAPIConfig::with_base_path(config)))
+            .map_err(|err| Error::string(&format!("invalid `initializers.openapi` config: {err}")))
+    }
+
+    /// Features of the visualizers in the `openapi` config that aren't enabled
+    ///
+    /// The fields of disabled visualizers are compiled out, so their config
+    /// is silently dropped when deserializing
+    #[must_use]
+    pub fn missing_features(&self) -> Vec<&'static str> {
+        let Some(Value::Object(openapi)) = self.0.as_ref().and_then(|m| m.get("openapi")) else {
+            return Vec::new();
+        };
+        let groups = openapi
+            .get("groups")
+            .and_then(Value::as_object)
+            .into_iter()
+            .flat_map(|groups| groups.values().filter_map(Value::as_object));
+        let listed = openapi
+            .get("views")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(|view| view.get("type")?.as_str());
+
+        VIEW_FEATURES
+            .into_iter()
+            .filter(|(feature, enabled)| {
+                !enabled
+                    && (std::iter::once(openapi)
+                        .chain(groups.clone())
+                        .any(|view| view.contains_key(*feature))
+                        || listed.clone().any(|view_type| view_type == *feature))
+            })
+            .map(|(feature, _)| feature)
+            .collect()
+    }
+
+    /// Reject keys in the `openapi` config that aren't fields of the
+    /// deserialized `config`, when its `strict_config` flag is set
+    ///
+    /// Unknown keys are otherwise silently ignored, since the flattened
+    /// visualizer fields rule out `#[serde(deny_unknown_fields)]`
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` listing the unknown keys
+    pub fn check_unknown_keys(&self, config: &OpenAPIConfig) -> Result<(), Error> {
+        let Some(raw) = self.0.as_ref().and_then(|m| m.get("openapi")) else {
+            return Ok(());
+        };
+        if !config.strict_config {
+            return Ok(());
+        }
+
+        let mut known = serde_json::to_value(config)?;
+        if let Value::Object(known) = &mut known {
+            // Visualizers of disabled features are reported by `missing_features`
+            for (feature, _) in VIEW_FEATURES {
+                known.entry(feature).or_insert(Value::Null);
+            }
+        }
+        let mut keys = Vec::new();
+        collect_unknown_keys(raw, &known, "openapi", &mut keys);
+
+        if keys.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::string(&format!(
+                "unknown keys in `initializers.openapi`: {}",
+                keys.join(", ")
+            )))
+        }
+    }
+}
+
+/// Paths of the keys of `raw` that aren't in `known`
+fn collect_unknown_keys(raw: &Value, known: &Value, path: &str, keys: &mut Vec<String>) {
+    match (raw, known) {
+        (Value::Object(raw), Value::Object(known)) => {
+            for (key, value) in raw {
+                let path = format!("{path}.{key}");
+                match known.get(key) {
+                    Some(known) => collect_unknown_keys(value, known, &path, keys),
+                    None => keys.push(path),
+                }
+            }
+        }
+        (Value::Array(raw), Value::Array(known)) => {
+            // Views of the list form are matched by `type`, the views of
+            // disabled features are dropped when deserializing and skipped
+            let mut views = known
+                .iter()
+                .filter(|known| known.get("type").is_some())
+                .collect::<Vec<_>>();
+            for (index, value) in raw.iter().enumerate() {
+                let known = match value.get("type") {
+                    Some(view_type) => views
+                        .iter()
+                        .position(|view| view.get("type") == Some(view_type))
+                        .map(|position| views.remove(position)),
+                    None => known.get(index),
+                };
+                if let Some(known) = known {
+                    collect_unknown_keys(value, known, &format!("{path}[{index}]"), keys);
+                }
+            }
+        }
+        _ => {}
     }
 }
 
 /// Set the `OpenAPI` configuration directly
 ///
+/// The configuration is set once per process: the first call stores it and
+/// later calls return the stored configuration, ignoring their own. See
+/// [`replace_openapi_config`] to replace it in tests
+///
 /// # Errors
 ///
 /// Will return `Err` if the configuration can't be set
 pub fn set_openapi_config(
     config: Option<OpenAPIConfig>,
 ) -> Result<Option<&'static OpenAPIConfig>, Error> {
-    Ok(OPENAPI_CONFIG.get_or_init(|| config).as_ref())
+    let mut stored = OPENAPI_CONFIG
+        .write()
+        .map_err(|_| Error::string("OpenAPI config lock is poisoned"))?;
+    Ok(*stored.get_or_insert_with(|| config.map(leak_config)))
 }
 
+/// Replace the `OpenAPI` configuration, e.g. between tests booting apps with
+/// different configurations
+///
+/// Every replaced configuration stays allocated, it's meant for tests rather
+/// than for replacing the configuration of a running app
+///
+/// # Errors
+///
+/// Will return `Err` if the configuration can't be set
+#[cfg(any(test, feature = "test-support"))]
+pub fn replace_openapi_config(
+    config: Option<OpenAPIConfig>,
+) -> Result<Option<&'static OpenAPIConfig>, Error> {
+    let config = config.map(leak_config);
+    *OPENAPI_CONFIG
+        .write()
+        .map_err(|_| Error::string("OpenAPI config lock is poisoned"))? = Some(config);
+    Ok(config)
+}
+
+fn leak_config(config: OpenAPIConfig) -> &'static OpenAPIConfig {
+    Box::leak(Box::new(config))
+}
+
+#[must_use]
 pub fn get_openapi_config() -> Option<&'static OpenAPIConfig> {
-    OPENAPI_CONFIG.get().unwrap_or(&None).as_ref()
+    OPENAPI_CONFIG
+        .read()
+        .ok()
+        .and_then(|config| config.flatten())
 }
 
 /// `OpenAPI` configuration
@@ -58,12 +239,30 @@ pub fn get_openapi_config() -> Option<&'static OpenAPIConfig> {
 ///       # spec_yaml_url: /scalar/openapi.yaml
 ///     swagger:
 ///       url: /swagger
-///       spec_json_url: /api-docs/openapi.json
-///       # spec_yaml_url: /api-docs/openapi.yaml
+///       # spec_json_url: /swagger/openapi.json
+///       # spec_yaml_url: /swagger/openapi.yaml
+///     elements:
+///       url: /elements
+///       # spec_json_url: /elements/openapi.json
+///       # spec_yaml_url: /elements/openapi.yaml
 /// ```
 #[derive(Debug, Clone, Deserialize, Serialize)]
 #[cfg_attr(test, derive(PartialEq, Eq))]
 pub struct OpenAPIConfig {
+    // The flattened visualizer fields each take the first visualizer key left
+    // in the settings, which are sorted by key, so the fields are declared in
+    // the order of their keys
+    /// Stoplight Elements configuration
+    /// Example:
+    /// ```yaml
+    /// initializers:
+    ///   openapi:
+    ///     elements:
+    ///       url: /elements
+    /// ```
+    #[cfg(feature = "elements")]
+    #[serde(flatten)]
+    pub elements: Option<OpenAPIType>,
     /// Redoc configuration
     /// Example:
     /// ```yaml
@@ -95,9 +294,790 @@ pub struct OpenAPIConfig {
     ///       url: /swagger
     ///       spec_json_url: /openapi.json
     /// ```
-    #[cfg(feature = "swagger")]
+    #[cfg(feature = "swagger-cdn")]
     #[serde(flatten)]
     pub swagger: Option<OpenAPIType>,
+    /// Visualizers for named route groups, each group is documented in its
+    /// own spec, see [`crate::openapi::openapi_grouped`]
+    /// Example:
+    /// ```yaml
+    /// initializers:
+    ///   openapi:
+    ///     groups:
+    ///       admin:
+    ///         redoc:
+    ///           url: /docs/admin
+    ///           spec_json_url: /docs/admin/openapi.json
+    /// ```
+    #[serde(default)]
+    pub groups: BTreeMap<String, OpenAPIType>,
+    /// Visualizers as a list, an alternative to the `redoc`, `scalar`,
+    /// `swagger` and `elements` keys. Each entry names its visualizer with
+    /// `type`. Entries of disabled features are skipped, see
+    /// [`InitializerConfig::missing_features`]
+    /// Example:
+    /// ```yaml
+    /// initializers:
+    ///   openapi:
+    ///     views:
+    ///       - type: redoc
+    ///         url: /redoc
+    ///       - type: swagger
+    ///         url: /swagger
+    ///         spec_json_url: /api-docs/openapi.json
+    /// ```
+    #[serde(default, with = "view_list")]
+    pub views: Vec<OpenAPIType>,
+    /// Tag metadata added to the spec, merged into tags of the same name
+    /// Example:
+    /// ```yaml
+    /// initializers:
+    ///   openapi:
+    ///     tags:
+    ///       - name: album
+    ///         description: Album management
+    ///         external_docs_url: https://example.com/docs/albums
+    /// ```
+    #[serde(default)]
+    pub tags: Vec<TagConfig>,
+    /// Order of the tags in the visualizers, which follow the order of the
+    /// spec `tags`. The listed tags come first, the others keep their order
+    /// after them
+    /// Example:
+    /// ```yaml
+    /// initializers:
+    ///   openapi:
+    ///     tag_order:
+    ///       - auth
+    ///       - album
+    /// ```
+    #[serde(default)]
+    pub tag_order: Vec<String>,
+    /// Path the app is mounted at, prepended to all visualizer and spec URLs
+    /// and used as the server URL of the spec. Absolute URLs are left as is
+    /// Example:
+    /// ```yaml
+    /// initializers:
+    ///   openapi:
+    ///     base_path: /api/v2
+    ///     redoc:
+    ///       url: /redoc # served at /api/v2/redoc
+    /// ```
+    #[serde(default)]
+    pub base_path: Option<String>,
+    /// Fail the boot when a `$ref` of the spec doesn't resolve. With the
+    /// `validate` feature, fail instead of logging errors on any problem
+    /// found by its checks
+    #[serde(default)]
+    pub strict: bool,
+    /// Per-IP rate limit of the visualizer and spec routes, off by default
+    /// Example:
+    /// ```yaml
+    /// initializers:
+    ///   openapi:
+    ///     rate_limit:
+    ///       requests_per_minute: 60
+    /// ```
+    #[serde(default)]
+    pub rate_limit: Option<RateLimitConfig>,
+    /// Hosts the visualizer and spec routes are served on, other hosts get a
+    /// `404 Not Found`, e.g. to keep the docs off the public domain of an app
+    /// also serving an internal one. A host listed with a port only matches
+    /// that port. Served on all hosts by default
+    /// Example:
+    /// ```yaml
+    /// initializers:
+    ///   openapi:
+    ///     allowed_hosts:
+    ///       - internal.example.com
+    /// ```
+    #[serde(default)]
+    pub allowed_hosts: Vec<String>,
+    /// Documentation standards checked on boot, problems are logged as
+    /// warnings or fail the boot when `strict` is set
+    /// Example:
+    /// ```yaml
+    /// initializers:
+    ///   openapi:
+    ///     lint:
+    ///       require_examples: true
+    ///       require_descriptions: true
+    /// ```
+    #[serde(default)]
+    pub lint: Option<LintConfig>,
+    /// URL serving a Markdown rendering of the spec
+    /// Example:
+    /// ```yaml
+    /// initializers:
+    ///   openapi:
+    ///     markdown_url: /api.md
+    /// ```
+    #[cfg(feature = "markdown")]
+    #[serde(default)]
+    pub markdown_url: Option<String>,
+    /// URL under which every component schema is served as a standalone
+    /// JSON Schema document, at `{json_schema_url}/{name}.json`
+    /// Example:
+    /// ```yaml
+    /// initializers:
+    ///   openapi:
+    ///     json_schema_url: /schemas
+    /// ```
+    #[cfg(feature = "json-schema")]
+    #[serde(default)]
+    pub json_schema_url: Option<String>,
+    /// URL serving the spec as a Postman v2.1 collection
+    /// Example:
+    /// ```yaml
+    /// initializers:
+    ///   openapi:
+    ///     postman_url: /postman.json
+    /// ```
+    #[cfg(feature = "postman")]
+    #[serde(default)]
+    pub postman_url: Option<String>,
+    /// Fail the boot on unknown keys in the config, so typos like `swaggr`
+    /// don't go unnoticed
+    #[serde(default)]
+    pub strict_config: bool,
+    /// Also serve the JSON spec at `/.well-known/openapi.json`, for crawlers
+    /// and API catalogs
+    #[serde(default)]
+    pub well_known: bool,
+    /// Also serve the JSON spec without the `description` and `summary`
+    /// texts at `/openapi.min.json`, a smaller spec for code generators. The
+    /// visualizers keep the full spec
+    #[serde(default)]
+    pub strip_descriptions: bool,
+    /// Title of the docs pages, replacing the title of the visualizer so
+    /// the browser tabs of the docs of several services can be told apart
+    /// Example:
+    /// ```yaml
+    /// initializers:
+    ///   openapi:
+    ///     page_title: Albums API
+    ///     favicon_url: /static/favicon.png
+    /// ```
+    #[serde(default)]
+    pub page_title: Option<String>,
+    /// URL of the favicon of the docs pages, see `page_title`
+    #[serde(default)]
+    pub favicon_url: Option<String>,
+    /// Clear the collected routes, groups and webhooks once the spec is
+    /// built, so the next boot in the same process, e.g. of another test,
+    /// serves a spec of only its own routes. The schemas, responses and
+    /// other registries are kept
+    ///
+    /// The routes are registered while the app routes are built, before
+    /// `after_routes` runs, so they are cleared at the end of it
+    #[serde(default)]
+    pub reset_on_boot: bool,
+    /// Pretty-print the JSON spec, compact by default to save bandwidth
+    #[serde(default)]
+    pub json_pretty: bool,
+    /// Public URL of the app behind a reverse proxy, the server URL of the
+    /// spec when no servers are set, instead of the host and port of Loco's
+    /// `server` config
+    /// Example:
+    /// ```yaml
+    /// initializers:
+    ///   openapi:
+    ///     public_url: https://api.example.com
+    /// ```
+    #[serde(default)]
+    pub public_url: Option<String>,
+    /// Point the first server of the JSON spec at the origin of the request,
+    /// taken from the `X-Forwarded-Host` and `X-Forwarded-Proto` headers, so
+    /// "Try it out" works behind a proxy. Any client can send the headers, so
+    /// only the hosts listed in `allowed_hosts` are trusted
+    #[serde(default)]
+    pub dynamic_servers: bool,
+    /// Media type of the JSON spec endpoints, `application/json` by default.
+    /// Some gateways require `application/openapi+json` for spec discovery
+    #[serde(default)]
+    pub json_content_type: Option<String>,
+    /// Serve the JSON spec endpoints, even when a visualizer needs them
+    #[serde(default = "default_true")]
+    pub serve_json: bool,
+    /// Serve the YAML spec endpoints
+    #[serde(default = "default_true")]
+    pub serve_yaml: bool,
+    /// URL serving the spec as JSON or YAML, depending on the `Accept` header
+    /// Example:
+    /// ```yaml
+    /// initializers:
+    ///   openapi:
+    ///     spec_url: /openapi
+    /// ```
+    #[serde(default)]
+    pub spec_url: Option<String>,
+    /// URL returning the build of the live spec as JSON, its `info.version`
+    /// and the build timestamp and git commit from the `BUILD_TIMESTAMP` and
+    /// `GIT_SHA` environment variables. The build is also documented as the
+    /// `x-build` extension of the spec
+    /// Example:
+    /// ```yaml
+    /// initializers:
+    ///   openapi:
+    ///     version_url: /openapi/version
+    /// ```
+    #[serde(default)]
+    pub version_url: Option<String>,
+    /// Contact information of the API, replaces `info.contact`
+    /// Example:
+    /// ```yaml
+    /// initializers:
+    ///   openapi:
+    ///     contact:
+    ///       name: API Support
+    ///       url: https://example.com/support
+    ///       email: support@example.com
+    /// ```
+    #[serde(default)]
+    pub contact: Option<ContactConfig>,
+    /// License of the API, replaces `info.license`
+    /// Example:
+    /// ```yaml
+    /// initializers:
+    ///   openapi:
+    ///     license:
+    ///       name: MIT
+    ///       url: https://opensource.org/licenses/MIT
+    /// ```
+    #[serde(default)]
+    pub license: Option<LicenseConfig>,
+    /// URL of the terms of service of the API, replaces
+    /// `info.termsOfService`
+    /// Example:
+    /// ```yaml
+    /// initializers:
+    ///   openapi:
+    ///     terms_of_service: https://example.com/terms
+    /// ```
+    #[serde(default)]
+    pub terms_of_service: Option<String>,
+    /// Short summary of the API, the `info.summary` of `OpenAPI` 3.1, next to
+    /// the longer `info.description`
+    /// Example:
+    /// ```yaml
+    /// initializers:
+    ///   openapi:
+    ///     summary: Albums and artists of the catalog
+    /// ```
+    #[serde(default)]
+    pub summary: Option<String>,
+    /// Path prefixes left out of the spec, the Loco internal routes by default
+    /// Example:
+    /// ```yaml
+    /// initializers:
+    ///   openapi:
+    ///     exclude_paths:
+    ///       - /_ping
+    ///       - /_health
+    ///       - /api/internal
+    /// ```
+    #[serde(default = "default_exclude_paths")]
+    pub exclude_paths: Vec<String>,
+    /// External documentation of the whole API, replaces `externalDocs`
+    /// Example:
+    /// ```yaml
+    /// initializers:
+    ///   openapi:
+    ///     external_docs:
+    ///       description: Developer guide
+    ///       url: https://example.com/guide
+    /// ```
+    #[serde(default)]
+    pub external_docs: Option<ExternalDocsConfig>,
+    /// Default `$schema` of the schemas in the spec, the `jsonSchemaDialect`
+    /// of `OpenAPI` 3.1. Unset by default
+    /// Example:
+    /// ```yaml
+    /// initializers:
+    ///   openapi:
+    ///     json_schema_dialect: https://spec.openapis.org/oas/3.1/dialect/base
+    /// ```
+    #[serde(default)]
+    pub json_schema_dialect: Option<String>,
+    /// Fail the boot when a `${VAR}` placeholder of a visualizer or spec URL
+    /// names an unset environment variable without a `${VAR:-fallback}`,
+    /// instead of expanding it to an empty string
+    #[serde(default)]
+    pub strict_env: bool,
+    /// What to do when two collected routes use different component schemas
+    /// with the same name, e.g. two `Album` structs in different modules
+    /// Example:
+    /// ```yaml
+    /// initializers:
+    ///   openapi:
+    ///     duplicate_schemas: rename
+    /// ```
+    #[serde(default)]
+    pub duplicate_schemas: DuplicateSchemaStrategy,
+    /// Security schemes added to `components.securitySchemes`, in the
+    /// `OpenAPI` format, replacing schemes of the same name. Document auth
+    /// without writing a `SecurityAddon`
+    /// Example:
+    /// ```yaml
+    /// initializers:
+    ///   openapi:
+    ///     security_schemes:
+    ///       jwt_token:
+    ///         type: http
+    ///         scheme: bearer
+    ///         bearerFormat: JWT
+    ///       api_key:
+    ///         type: apiKey
+    ///         in: header
+    ///         name: X-API-Key
+    /// ```
+    #[serde(default)]
+    pub security_schemes: BTreeMap<String, Value>,
+}
+
+/// How component schemas with the same name but different definitions are
+/// merged, see [`crate::openapi::merge_routes`]
+#[derive(Default, Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DuplicateSchemaStrategy {
+    /// Log a warning and keep one of the schemas
+    #[default]
+    Warn,
+    /// Fail the boot
+    Error,
+    /// Rename the later schema with a numeric suffix, `Album2`, and update
+    /// the `$ref`s of its routes
+    Rename,
+}
+
+const fn default_true() -> bool {
+    true
+}
+
+/// The routes added by `AppRoutes::with_default_routes()`
+fn default_exclude_paths() -> Vec<String> {
+    ["/_ping", "/_health", "/_readiness"]
+        .map(ToString::to_string)
+        .to_vec()
+}
+
+/// Rate limit configuration
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[cfg_attr(test, derive(PartialEq, Eq))]
+pub struct RateLimitConfig {
+    /// Requests allowed per client IP per minute
+    pub requests_per_minute: u32,
+}
+
+/// Lint configuration, see [`crate::lint::lint_spec`]
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[cfg_attr(test, derive(PartialEq, Eq))]
+pub struct LintConfig {
+    /// Every JSON-like content of a 2xx response needs an example
+    #[serde(default)]
+    pub require_examples: bool,
+    /// Every operation needs a description
+    #[serde(default)]
+    pub require_descriptions: bool,
+}
+
+/// Tag metadata
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[cfg_attr(test, derive(PartialEq, Eq))]
+pub struct TagConfig {
+    /// Name of the tag, as used in `#[utoipa::path(tags = [...])]`
+    pub name: String,
+    /// Description of the tag
+    pub description: Option<String>,
+    /// URL of the external documentation of the tag
+    pub external_docs_url: Option<String>,
+}
+
+/// Contact information
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[cfg_attr(test, derive(PartialEq, Eq))]
+pub struct ContactConfig {
+    /// Name of the contact person or organization
+    pub name: Option<String>,
+    /// URL of the contact information
+    pub url: Option<String>,
+    /// Email address of the contact
+    pub email: Option<String>,
+}
+
+/// External documentation
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[cfg_attr(test, derive(PartialEq, Eq))]
+pub struct ExternalDocsConfig {
+    /// Description of the external documentation
+    pub description: Option<String>,
+    /// URL of the external documentation
+    pub url: String,
+}
+
+/// License information
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[cfg_attr(test, derive(PartialEq, Eq))]
+pub struct LicenseConfig {
+    /// Name of the license, for example `MIT`
+    pub name: String,
+    /// URL of the license text
+    pub url: Option<String>,
+}
+
+impl OpenAPIConfig {
+    /// All configured visualizers
+    #[must_use]
+    pub fn views(&self) -> Vec<&OpenAPIType> {
+        #[allow(unused_mut)]
+        let mut views = Vec::new();
+        #[cfg(feature = "redoc")]
+        views.extend(self.redoc.as_ref());
+        #[cfg(feature = "scalar")]
+        views.extend(self.scalar.as_ref());
+        #[cfg(feature = "swagger-cdn")]
+        views.extend(self.swagger.as_ref());
+        #[cfg(feature = "elements")]
+        views.extend(self.elements.as_ref());
+        views.extend(&self.views);
+        views
+    }
+}
+
+impl OpenAPIConfig {
+    /// All configured visualizers, including the ones of the route groups
+    fn views_mut(&mut self) -> Vec<&mut OpenAPIType> {
+        #[allow(unused_mut)]
+        let mut views = Vec::new();
+        #[cfg(feature = "redoc")]
+        views.extend(self.redoc.as_mut());
+        #[cfg(feature = "scalar")]
+        views.extend(self.scalar.as_mut());
+        #[cfg(feature = "swagger-cdn")]
+        views.extend(self.swagger.as_mut());
+        #[cfg(feature = "elements")]
+        views.extend(self.elements.as_mut());
+        views.extend(&mut self.views);
+        views.extend(self.groups.values_mut());
+        views
+    }
+
+    /// Expand the `${VAR}` and `${VAR:-fallback}` placeholders of the
+    /// visualizer and spec URLs from the environment
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` when `strict_env` is set and a variable without a
+    /// fallback is unset
+    pub fn with_env_vars(mut self) -> Result<Self, Error> {
+        let strict_env = self.strict_env;
+        for view in self.views_mut() {
+            let (url, spec_json_url, spec_yaml_url, _) = view.fields_mut();
+            for url in
+                std::iter::once(url).chain([spec_json_url, spec_yaml_url].into_iter().flatten())
+            {
+                let (expanded, unset) = expand_env_vars(url);
+                if !unset.is_empty() {
+                    let message = format!(
+                        "unset environment variables in OpenAPI URL `{url}`: {}",
+                        unset.join(", ")
+                    );
+                    if strict_env {
+                        return Err(Error::string(&message));
+                    }
+                    tracing::warn!("{message}");
+                }
+                *url = expanded;
+            }
+        }
+        Ok(self)
+    }
+
+    /// Prepend the `base_path` to the visualizer and spec URLs, and to the
+    /// URLs of the other endpoints
+    #[must_use]
+    pub fn with_base_path(mut self) -> Self {
+        let Some(base_path) = self.base_path.clone() else {
+            return self;
+        };
+        let base_path = base_path.trim_end_matches('/');
+        for view in self.views_mut() {
+            let (url, spec_json_url, spec_yaml_url, _) = view.fields_mut();
+            prefix_url(base_path, url);
+            for spec_url in [spec_json_url, spec_yaml_url].into_iter().flatten() {
+                prefix_url(base_path, spec_url);
+            }
+        }
+        for url in [&mut self.spec_url, &mut self.version_url]
+            .into_iter()
+            .flatten()
+        {
+            prefix_url(base_path, url);
+        }
+        #[cfg(feature = "markdown")]
+        if let Some(url) = &mut self.markdown_url {
+            prefix_url(base_path, url);
+        }
+        #[cfg(feature = "json-schema")]
+        if let Some(url) = &mut self.json_schema_url {
+            prefix_url(base_path, url);
+        }
+        #[cfg(feature = "postman")]
+        if let Some(url) = &mut self.postman_url {
+            prefix_url(base_path, url);
+        }
+        self
+    }
+
+    /// URL of the spec without descriptions, [`LEAN_SPEC_URL`] under the
+    /// `base_path`
+    #[must_use]
+    pub fn lean_spec_url(&self) -> String {
+        let mut url = LEAN_SPEC_URL.to_string();
+        if let Some(base_path) = &self.base_path {
+            prefix_url(base_path.trim_end_matches('/'), &mut url);
+        }
+        url
+    }
+}
+
+/// (De)serialize visualizers in the list form, `{ type: redoc, url: /redoc }`
+/// instead of `OpenAPIType`'s `{ redoc: { url: /redoc } }`
+mod view_list {
+    use serde::{de::Error as _, ser::Error as _, Deserialize, Deserializer, Serializer};
+    use serde_json::{Map, Value};
+
+    use super::{OpenAPIType, VIEW_FEATURES};
+
+    pub fn serialize<S: Serializer>(
+        views: &[OpenAPIType],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let views = views
+            .iter()
+            .map(
+                |view| match serde_json::to_value(view).map_err(S::Error::custom)? {
+                    Value::Object(tagged) => Ok(tagged
+                        .into_iter()
+                        .map(|(view_type, fields)| {
+                            let mut view = fields.as_object().cloned().unwrap_or_default();
+                            view.insert("type".to_string(), Value::String(view_type));
+                            Value::Object(view)
+                        })
+                        .next()
+                        .unwrap_or_default()),
+                    _ => Err(S::Error::custom("visualizer isn't serialized as a map")),
+                },
+            )
+            .collect::<Result<Vec<_>, _>>()?;
+        serializer.collect_seq(views)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<OpenAPIType>, D::Error> {
+        Vec::<Map<String, Value>>::deserialize(deserializer)?
+            .into_iter()
+            .filter_map(|mut view| {
+                let Some(Value::String(view_type)) = view.remove("type") else {
+                    return Some(Err(D::Error::missing_field("type")));
+                };
+                let disabled = VIEW_FEATURES
+                    .iter()
+                    .any(|(feature, enabled)| *feature == view_type && !enabled);
+                if disabled {
+                    return None;
+                }
+                let tagged = Value::Object(Map::from_iter([(view_type, Value::Object(view))]));
+                Some(serde_json::from_value(tagged).map_err(D::Error::custom))
+            })
+            .collect()
+    }
+}
+
+/// Expand the `${VAR}` and `${VAR:-fallback}` placeholders of `value`,
+/// returning the names of the unset variables without a fallback, which
+/// expand to an empty string
+fn expand_env_vars(value: &str) -> (String, Vec<String>) {
+    let mut expanded = String::with_capacity(value.len());
+    let mut unset = Vec::new();
+    let mut rest = value;
+    while let Some(start) = rest.find("${") {
+        let Some(len) = rest[start..].find('}') else {
+            break;
+        };
+        expanded.push_str(&rest[..start]);
+        let placeholder = &rest[start + 2..start + len];
+        let (name, fallback) = match placeholder.split_once(":-") {
+            Some((name, fallback)) => (name, Some(fallback)),
+            None => (placeholder, None),
+        };
+        match (std::env::var(name), fallback) {
+            (Ok(var), _) => expanded.push_str(&var),
+            (Err(_), Some(fallback)) => expanded.push_str(fallback),
+            (Err(_), None) => unset.push(name.to_string()),
+        }
+        rest = &rest[start + len + 1..];
+    }
+    expanded.push_str(rest);
+    (expanded, unset)
+}
+
+/// Prepend `base_path` to `url`, unless it's absolute or already prefixed
+fn prefix_url(base_path: &str, url: &mut String) {
+    let is_prefixed = url
+        .strip_prefix(base_path)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'));
+    if base_path.is_empty() || is_prefixed || url.contains("://") {
+        return;
+    }
+    *url = format!("{base_path}{url}");
+}
+
+/// Apply the spec metadata from the config
+impl Modify for OpenAPIConfig {
+    fn modify(&self, openapi: &mut OpenApi) {
+        if let Some(base_path) = &self.base_path {
+            let base_path = match base_path.trim_end_matches('/') {
+                "" => "/",
+                base_path => base_path,
+            };
+            openapi
+                .servers
+                .get_or_insert_with(|| vec![Server::new(base_path)]);
+        }
+        if let Some(contact) = &self.contact {
+            openapi.info.contact = Some(
+                ContactBuilder::new()
+                    .name(contact.name.clone())
+                    .url(contact.url.clone())
+                    .email(contact.email.clone())
+                    .build(),
+            );
+        }
+        if let Some(external_docs) = &self.external_docs {
+            openapi.external_docs = None;
+            members::set(
+                &mut openapi.extensions,
+                "externalDocs",
+                external_docs_value(&external_docs.url, external_docs.description.as_deref()),
+            );
+        }
+        if let Some(dialect) = &self.json_schema_dialect {
+            members::set(
+                &mut openapi.extensions,
+                "jsonSchemaDialect",
+                Value::String(dialect.clone()),
+            );
+        }
+        if let Some(license) = &self.license {
+            openapi.info.license = Some(
+                LicenseBuilder::new()
+                    .name(&license.name)
+                    .url(license.url.clone())
+                    .build(),
+            );
+        }
+        if let Some(terms_of_service) = &self.terms_of_service {
+            openapi.info.terms_of_service = Some(terms_of_service.clone());
+        }
+        if let Some(summary) = &self.summary {
+            members::set(
+                &mut openapi.info.extensions,
+                "summary",
+                Value::String(summary.clone()),
+            );
+        }
+        for (name, scheme) in &self.security_schemes {
+            match serde_json::from_value::<SecurityScheme>(scheme.clone()) {
+                Ok(scheme) => openapi
+                    .components
+                    .get_or_insert_with(Default::default)
+                    .add_security_scheme(name, scheme),
+                Err(err) => tracing::error!(
+                    scheme = name,
+                    error = err.to_string(),
+                    "invalid security scheme in `initializers.openapi.security_schemes`, skipping it"
+                ),
+            }
+        }
+        if !self.tags.is_empty() {
+            let tags = openapi.tags.get_or_insert_with(Vec::new);
+            for tag_config in &self.tags {
+                let index = tags
+                    .iter()
+                    .position(|tag| tag.name == tag_config.name)
+                    .unwrap_or_else(|| {
+                        tags.push(Tag::new(&tag_config.name));
+                        tags.len() - 1
+                    });
+                let tag = &mut tags[index];
+                if tag_config.description.is_some() {
+                    tag.description.clone_from(&tag_config.description);
+                }
+                if let Some(url) = &tag_config.external_docs_url {
+                    tag.external_docs = None;
+                    members::set(
+                        &mut tag.extensions,
+                        "externalDocs",
+                        external_docs_value(url, None),
+                    );
+                }
+            }
+        }
+        if !self.tag_order.is_empty() {
+            let mut tags = openapi.tags.take().unwrap_or_default();
+            let mut ordered = Vec::with_capacity(tags.len());
+            for name in &self.tag_order {
+                match tags.iter().position(|tag| &tag.name == name) {
+                    Some(index) => ordered.push(tags.remove(index)),
+                    None => ordered.push(Tag::new(name)),
+                }
+            }
+            ordered.append(&mut tags);
+            openapi.tags = Some(ordered);
+        }
+    }
+}
+
+/// An `externalDocs` object, utoipa serializes a missing description as
+/// `null`
+fn external_docs_value(url: &str, description: Option<&str>) -> Value {
+    let mut docs = serde_json::Map::new();
+    docs.insert("url".to_string(), Value::String(url.to_string()));
+    if let Some(description) = description {
+        docs.insert(
+            "description".to_string(),
+            Value::String(description.to_string()),
+        );
+    }
+    Value::Object(docs)
+}
+
+/// Where the Swagger UI assets are loaded from
+#[cfg(feature = "swagger-cdn")]
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SwaggerAssets {
+    /// Served by the app from the assets embedded by `utoipa-swagger-ui`,
+    /// needs the `swagger` feature
+    Embedded,
+    /// Loaded by the browser from the minified `swagger-ui-dist` bundle of a
+    /// CDN, see `cdn_url`
+    Cdn,
+}
+
+/// `embedded` with the `swagger` feature, `cdn` when only `swagger-cdn` is
+/// enabled
+#[cfg(feature = "swagger-cdn")]
+impl Default for SwaggerAssets {
+    fn default() -> Self {
+        if cfg!(feature = "swagger") {
+            Self::Embedded
+        } else {
+            Self::Cdn
+        }
+    }
 }
 
 /// `OpenAPI` configuration types
@@ -116,11 +1096,17 @@ pub enum OpenAPIType {
     #[serde(rename = "redoc")]
     Redoc {
         /// URL for where to host the redoc `OpenAPI` spec, example: /redoc
+        ///
+        /// `/redoc/` is redirected (307) to `/redoc`, or the other way around
+        /// when `url` ends with a slash
         url: String,
         /// URL for openapi.json, for example: /openapi.json
         spec_json_url: Option<String>,
         /// URL for openapi.yaml, for example: /openapi.yaml
         spec_yaml_url: Option<String>,
+        /// Path of an HTML template served instead of the built-in page, see
+        /// [`OpenAPIType::fields`]
+        custom_html_path: Option<String>,
     },
     /// Scalar configuration
     /// Example:
@@ -134,11 +1120,17 @@ pub enum OpenAPIType {
     #[serde(rename = "scalar")]
     Scalar {
         /// URL for where to host the scalar `OpenAPI` spec, example: /scalar
+        ///
+        /// `/scalar/` is redirected (307) to `/scalar`, or the other way around
+        /// when `url` ends with a slash
         url: String,
         /// URL for openapi.json, for example: /openapi.json
         spec_json_url: Option<String>,
         /// URL for openapi.yaml, for example: /openapi.yaml
         spec_yaml_url: Option<String>,
+        /// Path of an HTML template served instead of the built-in page, see
+        /// [`OpenAPIType::fields`]
+        custom_html_path: Option<String>,
     },
     /// Swagger configuration
     /// Example:
@@ -149,27 +1141,204 @@ pub enum OpenAPIType {
     ///       url: /swagger
     ///       spec_json_url: /openapi.json
     /// ```
-    #[cfg(feature = "swagger")]
+    #[cfg(feature = "swagger-cdn")]
     #[serde(rename = "swagger")]
     Swagger {
         /// URL for where to host the swagger `OpenAPI` spec, example:
         /// /swagger-ui
+        ///
+        /// Swagger UI is always served at `/swagger-ui/`, `/swagger-ui` is
+        /// redirected to it
         url: String,
-        /// URL for openapi.json, for example: /api-docs/openapi.json
-        spec_json_url: String,
+        /// URL for openapi.json, defaults to `{url}/openapi.json`, for
+        /// example: /api-docs/openapi.json
+        spec_json_url: Option<String>,
         /// URL for openapi.yaml, for example: /openapi.yaml
         spec_yaml_url: Option<String>,
+        /// Path of an HTML template served instead of the built-in page, see
+        /// [`OpenAPIType::fields`]
+        custom_html_path: Option<String>,
+        /// Where the Swagger UI assets are loaded from, `embedded` by default,
+        /// `cdn` when only the `swagger-cdn` feature is enabled
+        #[serde(default)]
+        assets: SwaggerAssets,
+        /// Base URL of the `swagger-ui-dist` assets with `assets: cdn`,
+        /// defaults to `https://unpkg.com/swagger-ui-dist@5`
+        cdn_url: Option<String>,
+        /// Swagger UI configuration options, e.g.
+        /// `{docExpansion: none, tryItOutEnabled: true}`
+        ///
+        /// With `assets: cdn` all options are passed to Swagger UI, the
+        /// embedded assets only support the options of
+        /// `utoipa_swagger_ui::Config`
+        ui_config: Option<Value>,
+    },
+    /// Stoplight Elements configuration
+    /// Example:
+    /// ```yaml
+    /// initializers:
+    ///   openapi:
+    ///     elements:
+    ///       url: /elements
+    /// ```
+    #[cfg(feature = "elements")]
+    #[serde(rename = "elements")]
+    Elements {
+        /// URL for where to host the Elements `OpenAPI` spec, example:
+        /// /elements
+        ///
+        /// `/elements/` is redirected (307) to `/elements`, or the other way
+        /// around when `url` ends with a slash
+        url: String,
+        /// URL for openapi.json, defaults to `{url}/openapi.json` since the
+        /// Elements web component loads the spec from it
+        spec_json_url: Option<String>,
+        /// URL for openapi.yaml, for example: /openapi.yaml
+        spec_yaml_url: Option<String>,
+        /// Path of an HTML template served instead of the built-in page, see
+        /// [`OpenAPIType::fields`]
+        custom_html_path: Option<String>,
     },
 }
 
+impl OpenAPIType {
+    /// The visualizer URL, the JSON and YAML spec URLs, and the path of the
+    /// custom HTML template
+    ///
+    /// In the custom HTML template `$specUrl` is replaced with the JSON spec
+    /// URL, which defaults to `{url}/openapi.json`, and `$spec` with the JSON
+    /// spec itself
+    #[must_use]
+    pub fn fields(&self) -> (&String, &Option<String>, &Option<String>, &Option<String>) {
+        match *self {
+            #[cfg(feature = "redoc")]
+            Self::Redoc {
+                ref url,
+                ref spec_json_url,
+                ref spec_yaml_url,
+                ref custom_html_path,
+            } => (url, spec_json_url, spec_yaml_url, custom_html_path),
+            #[cfg(feature = "scalar")]
+            Self::Scalar {
+                ref url,
+                ref spec_json_url,
+                ref spec_yaml_url,
+                ref custom_html_path,
+            } => (url, spec_json_url, spec_yaml_url, custom_html_path),
+            #[cfg(feature = "swagger-cdn")]
+            Self::Swagger {
+                ref url,
+                ref spec_json_url,
+                ref spec_yaml_url,
+                ref custom_html_path,
+                ..
+            } => (url, spec_json_url, spec_yaml_url, custom_html_path),
+            #[cfg(feature = "elements")]
+            Self::Elements {
+                ref url,
+                ref spec_json_url,
+                ref spec_yaml_url,
+                ref custom_html_path,
+            } => (url, spec_json_url, spec_yaml_url, custom_html_path),
+        }
+    }
+
+    fn fields_mut(
+        &mut self,
+    ) -> (
+        &mut String,
+        &mut Option<String>,
+        &mut Option<String>,
+        &mut Option<String>,
+    ) {
+        match *self {
+            #[cfg(feature = "redoc")]
+            Self::Redoc {
+                ref mut url,
+                ref mut spec_json_url,
+                ref mut spec_yaml_url,
+                ref mut custom_html_path,
+            } => (url, spec_json_url, spec_yaml_url, custom_html_path),
+            #[cfg(feature = "scalar")]
+            Self::Scalar {
+                ref mut url,
+                ref mut spec_json_url,
+                ref mut spec_yaml_url,
+                ref mut custom_html_path,
+            } => (url, spec_json_url, spec_yaml_url, custom_html_path),
+            #[cfg(feature = "swagger-cdn")]
+            Self::Swagger {
+                ref mut url,
+                ref mut spec_json_url,
+                ref mut spec_yaml_url,
+                ref mut custom_html_path,
+                ..
+            } => (url, spec_json_url, spec_yaml_url, custom_html_path),
+            #[cfg(feature = "elements")]
+            Self::Elements {
+                ref mut url,
+                ref mut spec_json_url,
+                ref mut spec_yaml_url,
+                ref mut custom_html_path,
+            } => (url, spec_json_url, spec_yaml_url, custom_html_path),
+        }
+    }
+}
+
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use super::*;
-    #[cfg(any(feature = "swagger", feature = "redoc", feature = "scalar"))]
+    #[cfg(any(
+        feature = "swagger",
+        feature = "redoc",
+        feature = "scalar",
+        feature = "elements"
+    ))]
     use serde_json::json;
 
+    /// Sets an env var for a test and restores its previous value on drop, so
+    /// a failed assertion doesn't leak it into the other tests
+    pub(crate) struct EnvVarGuard {
+        name: &'static str,
+        previous: Option<std::ffi::OsString>,
+    }
+
+    impl EnvVarGuard {
+        pub(crate) fn set(name: &'static str, value: &str) -> Self {
+            let guard = Self {
+                name,
+                previous: std::env::var_os(name),
+            };
+            std::env::set_var(name, value);
+            guard
+        }
+
+        pub(crate) fn remove(name: &'static str) -> Self {
+            let guard = Self {
+                name,
+                previous: std::env::var_os(name),
+            };
+            std::env::remove_var(name);
+            guard
+        }
+    }
+
+    impl Drop for EnvVarGuard {
+        fn drop(&mut self) {
+            match &self.previous {
+                Some(value) => std::env::set_var(self.name, value),
+                None => std::env::remove_var(self.name),
+            }
+        }
+    }
+
     // Helper function to create a mock configuration
-    #[cfg(any(feature = "swagger", feature = "redoc", feature = "scalar"))]
+    #[cfg(any(
+        feature = "swagger",
+        feature = "redoc",
+        feature = "scalar",
+        feature = "elements"
+    ))]
     fn create_mock_config() -> BTreeMap<String, Value> {
         let mut config = BTreeMap::new();
 
@@ -214,12 +1383,28 @@ mod tests {
             );
         }
 
+        // Add elements config conditionally
+        #[cfg(feature = "elements")]
+        {
+            openapi_config.insert(
+                "elements".to_string(),
+                json!({
+                    "url": "/elements"
+                }),
+            );
+        }
+
         config.insert("openapi".to_string(), Value::Object(openapi_config));
         config
     }
 
     #[test]
-    #[cfg(any(feature = "swagger", feature = "redoc", feature = "scalar"))]
+    #[cfg(any(
+        feature = "swagger",
+        feature = "redoc",
+        feature = "scalar",
+        feature = "elements"
+    ))]
     fn test_data_conversion() {
         // Test the conversion pipeline with valid data
         let initializers = Some(create_mock_config());
@@ -244,8 +1429,12 @@ mod tests {
 
             let expected = OpenAPIType::Swagger {
                 url: "/swagger".to_string(),
-                spec_json_url: "/api-docs/openapi.json".to_string(),
+                spec_json_url: Some("/api-docs/openapi.json".to_string()),
                 spec_yaml_url: None,
+                custom_html_path: None,
+                assets: SwaggerAssets::Embedded,
+                cdn_url: None,
+                ui_config: None,
             };
             assert_eq!(swagger, Some(&expected));
         }
@@ -259,6 +1448,7 @@ mod tests {
                 url: "/redoc".to_string(),
                 spec_json_url: Some("/redoc/openapi.json".to_string()),
                 spec_yaml_url: Some("/redoc/openapi.yaml".to_string()),
+                custom_html_path: None,
             };
             assert_eq!(redoc, Some(&expected));
         }
@@ -272,9 +1462,24 @@ mod tests {
                 url: "/scalar".to_string(),
                 spec_json_url: Some("/scalar/openapi.json".to_string()),
                 spec_yaml_url: Some("/scalar/openapi.yaml".to_string()),
+                custom_html_path: None,
             };
             assert_eq!(scalar, Some(&expected));
         }
+
+        #[cfg(feature = "elements")]
+        {
+            let elements = config.elements.as_ref();
+            assert!(elements.is_some(), "Elements config should be present");
+
+            let expected = OpenAPIType::Elements {
+                url: "/elements".to_string(),
+                spec_json_url: None,
+                spec_yaml_url: None,
+                custom_html_path: None,
+            };
+            assert_eq!(elements, Some(&expected));
+        }
     }
 
     #[test]
@@ -288,4 +1493,520 @@ mod tests {
         // Verify the conversion handles None correctly
         assert!(openapi_config.is_none(), "OpenAPIConfig should be None");
     }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_set_openapi_config_is_write_once() {
+        let config = |base_path: &str| {
+            serde_json::from_value::<OpenAPIConfig>(serde_json::json!({ "base_path": base_path }))
+                .unwrap()
+        };
+
+        replace_openapi_config(Some(config("/v1"))).unwrap();
+        let stored = set_openapi_config(Some(config("/v2"))).unwrap();
+        assert_eq!(stored.unwrap().base_path.as_deref(), Some("/v1"));
+        assert_eq!(
+            get_openapi_config().unwrap().base_path.as_deref(),
+            Some("/v1")
+        );
+
+        replace_openapi_config(Some(config("/v2"))).unwrap();
+        assert_eq!(
+            get_openapi_config().unwrap().base_path.as_deref(),
+            Some("/v2")
+        );
+
+        replace_openapi_config(None).unwrap();
+        assert!(get_openapi_config().is_none());
+        assert!(set_openapi_config(Some(config("/v3"))).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_unknown_keys() {
+        let openapi = |strict_config: bool| {
+            let mut config = BTreeMap::new();
+            config.insert(
+                "openapi".to_string(),
+                serde_json::json!({
+                    "strict_config": strict_config,
+                    "swaggr": { "url": "/swagger" },
+                    "tags": [{ "name": "album", "descripton": "Albums" }],
+                }),
+            );
+            Some(config)
+        };
+
+        let initializers = openapi(true);
+        let initializer_config = InitializerConfig::from(&initializers);
+        let config: Option<OpenAPIConfig> = initializer_config.into();
+        let err = initializer_config
+            .check_unknown_keys(&config.unwrap())
+            .unwrap_err();
+        assert!(err.to_string().contains("openapi.swaggr"));
+        assert!(err.to_string().contains("openapi.tags[0].descripton"));
+
+        let initializers = openapi(false);
+        let initializer_config = InitializerConfig::from(&initializers);
+        let config: Option<OpenAPIConfig> = initializer_config.into();
+        assert!(initializer_config
+            .check_unknown_keys(&config.unwrap())
+            .is_ok());
+    }
+
+    #[test]
+    fn test_unknown_keys_skip_disabled_views() {
+        let raw = serde_json::json!({
+            "views": [
+                { "type": "redoc", "url": "/redoc" },
+                { "type": "swagger", "url": "/swagger", "ui_config": {}, "assets": "cdn" },
+                { "type": "scalar", "url": "/scalar", "spec_jsn_url": "/openapi.json" },
+            ]
+        });
+        // The swagger view was dropped, as when its feature is disabled
+        let known = serde_json::json!({
+            "views": [
+                { "type": "redoc", "url": "/redoc", "spec_json_url": null },
+                { "type": "scalar", "url": "/scalar", "spec_json_url": null },
+            ]
+        });
+
+        let mut keys = Vec::new();
+        collect_unknown_keys(&raw, &known, "openapi", &mut keys);
+        assert_eq!(keys, vec!["openapi.views[2].spec_jsn_url".to_string()]);
+    }
+
+    #[test]
+    #[cfg(all(feature = "redoc", feature = "swagger"))]
+    fn test_view_list() {
+        let initializers = Some(BTreeMap::from([(
+            "openapi".to_string(),
+            serde_json::json!({
+                "strict_config": true,
+                "views": [
+                    { "type": "redoc", "url": "/redoc" },
+                    { "type": "swagger", "url": "/swagger", "spec_json_url": "/api-docs/openapi.json" }
+                ]
+            }),
+        )]));
+        let initializer_config = InitializerConfig::from(&initializers);
+        let config: OpenAPIConfig =
+            Option::from(initializer_config).expect("config should deserialize");
+
+        assert_eq!(
+            config.views(),
+            vec![
+                &OpenAPIType::Redoc {
+                    url: "/redoc".to_string(),
+                    spec_json_url: None,
+                    spec_yaml_url: None,
+                    custom_html_path: None,
+                },
+                &OpenAPIType::Swagger {
+                    url: "/swagger".to_string(),
+                    spec_json_url: Some("/api-docs/openapi.json".to_string()),
+                    spec_yaml_url: None,
+                    custom_html_path: None,
+                    assets: SwaggerAssets::Embedded,
+                    cdn_url: None,
+                    ui_config: None,
+                },
+            ]
+        );
+        assert!(initializer_config.check_unknown_keys(&config).is_ok());
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_expand_env_vars() {
+        let _env = EnvVarGuard::set("LOCO_OPENAPI_TEST_ENV", "staging");
+        let _unset = EnvVarGuard::remove("LOCO_OPENAPI_TEST_UNSET");
+
+        assert_eq!(
+            expand_env_vars("/docs-${LOCO_OPENAPI_TEST_ENV}/redoc"),
+            ("/docs-staging/redoc".to_string(), vec![])
+        );
+        assert_eq!(
+            expand_env_vars("/docs-${LOCO_OPENAPI_TEST_UNSET:-dev}"),
+            ("/docs-dev".to_string(), vec![])
+        );
+        assert_eq!(
+            expand_env_vars("/docs-${LOCO_OPENAPI_TEST_UNSET}"),
+            (
+                "/docs-".to_string(),
+                vec!["LOCO_OPENAPI_TEST_UNSET".to_string()]
+            )
+        );
+        assert_eq!(
+            expand_env_vars("/docs-${unterminated"),
+            ("/docs-${unterminated".to_string(), vec![])
+        );
+    }
+
+    #[test]
+    fn test_try_into_config() {
+        let openapi = |openapi: Value| Some(BTreeMap::from([("openapi".to_string(), openapi)]));
+
+        let initializers = Some(BTreeMap::new());
+        assert!(InitializerConfig::from(&initializers)
+            .try_into_config()
+            .unwrap()
+            .is_none());
+        assert!(InitializerConfig::from(&openapi(Value::Null))
+            .try_into_config()
+            .unwrap()
+            .is_none());
+
+        let initializers = openapi(serde_json::json!({ "well_known": true }));
+        let config = InitializerConfig::from(&initializers)
+            .try_into_config()
+            .unwrap();
+        assert!(config.is_some_and(|config| config.well_known));
+
+        let initializers = openapi(serde_json::json!({ "well_known": "yes" }));
+        let err = InitializerConfig::from(&initializers)
+            .try_into_config()
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("invalid `initializers.openapi` config"));
+        let config: Option<OpenAPIConfig> = InitializerConfig::from(&initializers).into();
+        assert!(config.is_none());
+    }
+
+    #[test]
+    fn test_missing_features() {
+        let mut config = BTreeMap::new();
+        config.insert(
+            "openapi".to_string(),
+            serde_json::json!({
+                "swagger": { "url": "/swagger" },
+                "groups": { "admin": { "redoc": { "url": "/docs/admin" } } },
+            }),
+        );
+        let initializers = Some(config);
+        let missing = InitializerConfig::from(&initializers).missing_features();
+
+        assert_eq!(
+            missing.contains(&"swagger"),
+            cfg!(not(feature = "swagger-cdn"))
+        );
+        assert_eq!(missing.contains(&"redoc"), cfg!(not(feature = "redoc")));
+        assert!(!missing.contains(&"scalar"));
+        assert!(!missing.contains(&"elements"));
+    }
+
+    #[test]
+    #[cfg(feature = "redoc")]
+    fn test_base_path() {
+        let mut config = BTreeMap::new();
+        config.insert(
+            "openapi".to_string(),
+            serde_json::json!({
+                "base_path": "/api/v2/",
+                "redoc": {
+                    "url": "/redoc",
+                    "spec_json_url": "/api/v2/redoc/openapi.json",
+                    "spec_yaml_url": "https://docs.example.com/openapi.yaml"
+                },
+            }),
+        );
+        let initializers = Some(config);
+        let openapi_config: OpenAPIConfig = Option::from(InitializerConfig::from(&initializers))
+            .expect("config should deserialize");
+
+        assert_eq!(
+            openapi_config.views(),
+            vec![&OpenAPIType::Redoc {
+                url: "/api/v2/redoc".to_string(),
+                spec_json_url: Some("/api/v2/redoc/openapi.json".to_string()),
+                spec_yaml_url: Some("https://docs.example.com/openapi.yaml".to_string()),
+                custom_html_path: None,
+            }]
+        );
+
+        let mut spec = OpenApi::default();
+        openapi_config.modify(&mut spec);
+        let urls = spec
+            .servers
+            .iter()
+            .flatten()
+            .map(|server| server.url.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(urls, ["/api/v2"]);
+    }
+
+    #[test]
+    fn test_base_path_prefixes_endpoint_urls() {
+        let openapi_config = OpenAPIConfig::with_base_path(
+            serde_json::from_value(serde_json::json!({
+                "base_path": "/api/v2/",
+                "spec_url": "/openapi",
+                "version_url": "/api/v2/openapi/version",
+            }))
+            .unwrap(),
+        );
+
+        assert_eq!(openapi_config.spec_url.as_deref(), Some("/api/v2/openapi"));
+        assert_eq!(
+            openapi_config.version_url.as_deref(),
+            Some("/api/v2/openapi/version")
+        );
+        assert_eq!(openapi_config.lean_spec_url(), "/api/v2/openapi.min.json");
+    }
+
+    #[test]
+    fn test_tags_applied_to_spec() {
+        let mut config = BTreeMap::new();
+        config.insert(
+            "openapi".to_string(),
+            serde_json::json!({
+                "tags": [
+                    {
+                        "name": "album",
+                        "description": "Album management",
+                        "external_docs_url": "https://example.com/docs/albums"
+                    }
+                ]
+            }),
+        );
+        let initializers = Some(config);
+        let openapi_config: Option<OpenAPIConfig> = InitializerConfig::from(&initializers).into();
+
+        let mut spec = OpenApi::default();
+        spec.tags = Some(vec![Tag::new("album"), Tag::new("artist")]);
+        openapi_config.unwrap().modify(&mut spec);
+
+        let json = serde_json::to_value(&spec).unwrap();
+        assert_eq!(
+            json["tags"],
+            serde_json::json!([
+                {
+                    "name": "album",
+                    "description": "Album management",
+                    "externalDocs": { "url": "https://example.com/docs/albums" }
+                },
+                { "name": "artist" }
+            ])
+        );
+    }
+
+    #[test]
+    fn test_contact_and_license_applied_to_spec() {
+        let initializers = Some(BTreeMap::from([(
+            "openapi".to_string(),
+            serde_json::json!({
+                "contact": {
+                    "name": "API Support",
+                    "email": "support@example.com"
+                },
+                "license": {
+                    "name": "MIT",
+                    "url": "https://opensource.org/licenses/MIT"
+                }
+            }),
+        )]));
+        let openapi_config: Option<OpenAPIConfig> = InitializerConfig::from(&initializers).into();
+
+        let mut spec = OpenApi::default();
+        openapi_config.unwrap().modify(&mut spec);
+
+        let json = serde_json::to_value(&spec).unwrap();
+        assert_eq!(
+            json["info"]["contact"],
+            serde_json::json!({ "name": "API Support", "email": "support@example.com" })
+        );
+        assert_eq!(
+            json["info"]["license"],
+            serde_json::json!({ "name": "MIT", "url": "https://opensource.org/licenses/MIT" })
+        );
+    }
+
+    #[test]
+    fn test_security_schemes_applied_to_spec() {
+        let initializers = Some(BTreeMap::from([(
+            "openapi".to_string(),
+            serde_json::json!({
+                "security_schemes": {
+                    "jwt_token": { "type": "http", "scheme": "bearer", "bearerFormat": "JWT" },
+                    "broken": { "type": "unknown" }
+                }
+            }),
+        )]));
+        let openapi_config: Option<OpenAPIConfig> = InitializerConfig::from(&initializers).into();
+
+        let mut spec = OpenApi::default();
+        openapi_config.unwrap().modify(&mut spec);
+
+        let json = serde_json::to_value(&spec).unwrap();
+        assert_eq!(
+            json["components"]["securitySchemes"],
+            serde_json::json!({
+                "jwt_token": { "type": "http", "scheme": "bearer", "bearerFormat": "JWT" }
+            })
+        );
+    }
+
+    #[test]
+    fn test_tag_order() {
+        let initializers = Some(BTreeMap::from([(
+            "openapi".to_string(),
+            serde_json::json!({ "tag_order": ["auth", "album"] }),
+        )]));
+        let openapi_config: Option<OpenAPIConfig> = InitializerConfig::from(&initializers).into();
+
+        let mut spec = OpenApi::default();
+        spec.tags = Some(vec![
+            Tag::new("album"),
+            Tag::new("artist"),
+            Tag::new("auth"),
+        ]);
+        openapi_config.unwrap().modify(&mut spec);
+
+        let names = spec
+            .tags
+            .unwrap()
+            .into_iter()
+            .map(|tag| tag.name)
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["auth", "album", "artist"]);
+    }
+
+    #[test]
+    fn test_terms_of_service_and_summary_applied_to_spec() {
+        let initializers = Some(BTreeMap::from([(
+            "openapi".to_string(),
+            serde_json::json!({
+                "terms_of_service": "https://example.com/terms",
+                "summary": "Albums and artists of the catalog"
+            }),
+        )]));
+        let openapi_config: Option<OpenAPIConfig> = InitializerConfig::from(&initializers).into();
+
+        let mut spec = OpenApi::default();
+        openapi_config.unwrap().modify(&mut spec);
+
+        let json = serde_json::to_value(&spec).unwrap();
+        assert_eq!(json["info"]["termsOfService"], "https://example.com/terms");
+        assert_eq!(json["info"]["summary"], "Albums and artists of the catalog");
+    }
+
+    #[test]
+    fn test_external_docs_applied_to_spec() {
+        let initializers = Some(BTreeMap::from([(
+            "openapi".to_string(),
+            serde_json::json!({
+                "external_docs": {
+                    "description": "Developer guide",
+                    "url": "https://example.com/guide"
+                }
+            }),
+        )]));
+        let openapi_config: Option<OpenAPIConfig> = InitializerConfig::from(&initializers).into();
+
+        let mut spec = OpenApi::default();
+        openapi_config.unwrap().modify(&mut spec);
+
+        let json = serde_json::to_value(&spec).unwrap();
+        assert_eq!(
+            json["externalDocs"],
+            serde_json::json!({
+                "description": "Developer guide",
+                "url": "https://example.com/guide"
+            })
+        );
+
+        let initializers = Some(BTreeMap::from([(
+            "openapi".to_string(),
+            serde_json::json!({ "external_docs": { "url": "https://example.com/guide" } }),
+        )]));
+        let openapi_config: Option<OpenAPIConfig> = InitializerConfig::from(&initializers).into();
+        let mut spec = OpenApi::default();
+        openapi_config.unwrap().modify(&mut spec);
+
+        let json = serde_json::to_value(&spec).unwrap();
+        assert_eq!(
+            json["externalDocs"],
+            serde_json::json!({ "url": "https://example.com/guide" })
+        );
+    }
+
+    #[test]
+    fn test_json_schema_dialect() {
+        let dialect = "https://spec.openapis.org/oas/3.1/dialect/base";
+        let initializers = Some(BTreeMap::from([(
+            "openapi".to_string(),
+            serde_json::json!({ "json_schema_dialect": dialect }),
+        )]));
+        let openapi_config: Option<OpenAPIConfig> = InitializerConfig::from(&initializers).into();
+
+        let mut spec = OpenApi::default();
+        let json = serde_json::to_value(&spec).unwrap();
+        assert!(json.get("jsonSchemaDialect").is_none());
+
+        openapi_config.unwrap().modify(&mut spec);
+        let json = serde_json::to_value(&spec).unwrap();
+        assert_eq!(json["jsonSchemaDialect"], dialect);
+    }
+
+    #[test]
+    #[cfg(feature = "redoc")]
+    fn test_group_views() {
+        let mut config = BTreeMap::new();
+        config.insert(
+            "openapi".to_string(),
+            json!({
+                "groups": {
+                    "admin": {
+                        "redoc": {
+                            "url": "/docs/admin",
+                            "spec_json_url": "/docs/admin/openapi.json"
+                        }
+                    }
+                }
+            }),
+        );
+        let initializers = Some(config);
+
+        let openapi_config: Option<OpenAPIConfig> = InitializerConfig::from(&initializers).into();
+        let openapi_config = openapi_config.unwrap();
+
+        assert!(openapi_config.views().is_empty());
+        assert_eq!(
+            openapi_config.groups.get("admin"),
+            Some(&OpenAPIType::Redoc {
+                url: "/docs/admin".to_string(),
+                spec_json_url: Some("/docs/admin/openapi.json".to_string()),
+                spec_yaml_url: None,
+                custom_html_path: None,
+            })
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "swagger")]
+    fn test_swagger_without_spec_json_url() {
+        let mut config = BTreeMap::new();
+        config.insert(
+            "openapi".to_string(),
+            json!({
+                "swagger": {
+                    "url": "/swagger"
+                }
+            }),
+        );
+        let initializers = Some(config);
+
+        let openapi_config: Option<OpenAPIConfig> = InitializerConfig::from(&initializers).into();
+
+        let expected = OpenAPIType::Swagger {
+            url: "/swagger".to_string(),
+            spec_json_url: None,
+            spec_yaml_url: None,
+            custom_html_path: None,
+            assets: SwaggerAssets::Embedded,
+            cdn_url: None,
+            ui_config: None,
+        };
+        assert!(openapi_config.unwrap().views().contains(&&expected));
+    }
 }
@@ -0,0 +1,286 @@
+use std::collections::BTreeSet;
+
+use serde_json::Value;
+use utoipa::openapi::{schema::Schema, OpenApi, RefOr};
+
+use crate::openapi::operations;
+
+/// Changes between two `OpenAPI` specs, see [`diff_specs`]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SpecDiff {
+    /// Paths only in the new spec
+    pub added_paths: Vec<String>,
+    /// Paths only in the old spec
+    pub removed_paths: Vec<String>,
+    /// Operations of paths in both specs only in the old spec, as
+    /// `METHOD /path`
+    pub removed_operations: Vec<String>,
+    /// Operations in both specs whose documented response codes changed
+    pub changed_responses: Vec<ResponseChange>,
+    /// Component schemas only in the old spec
+    pub removed_schemas: Vec<String>,
+    /// Properties of component schemas in both specs that were removed, as
+    /// `Schema.property`
+    pub removed_schema_fields: Vec<String>,
+}
+
+/// Response codes of an operation that changed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResponseChange {
+    /// The operation, as `METHOD /path`
+    pub operation: String,
+    /// Response codes only in the new spec
+    pub added: Vec<String>,
+    /// Response codes only in the old spec
+    pub removed: Vec<String>,
+}
+
+impl SpecDiff {
+    /// Whether the specs document the same paths, responses and fields
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.added_paths.is_empty()
+            && self.removed_paths.is_empty()
+            && self.removed_operations.is_empty()
+            && self.changed_responses.is_empty()
+            && self.removed_schemas.is_empty()
+            && self.removed_schema_fields.is_empty()
+    }
+
+    /// Whether a client of the old spec can break, because a path,
+    /// operation, response code, schema or schema field it may rely on was
+    /// removed
+    #[must_use]
+    pub fn is_breaking(&self) -> bool {
+        !self.removed_paths.is_empty()
+            || !self.removed_operations.is_empty()
+            || !self.removed_schemas.is_empty()
+            || !self.removed_schema_fields.is_empty()
+            || self
+                .changed_responses
+                .iter()
+                .any(|change| !change.removed.is_empty())
+    }
+}
+
+/// Compare the `new` spec against the `old` one, e.g. the spec of the
+/// previous deploy, to detect breaking API changes
+#[must_use]
+pub fn diff_specs(old: &OpenApi, new: &OpenApi) -> SpecDiff {
+    let old_paths = old.paths.paths.keys().collect::<BTreeSet<_>>();
+    let new_paths = new.paths.paths.keys().collect::<BTreeSet<_>>();
+
+    let old_operations = operations(&old.paths);
+    let new_operations = operations(&new.paths);
+    let removed_operations = old_operations
+        .iter()
+        .filter(|(method, path, _)| {
+            new.paths.paths.contains_key(*path)
+                && !new_operations
+                    .iter()
+                    .any(|(new_method, new_path, _)| new_method == method && new_path == path)
+        })
+        .map(|(method, path, _)| format!("{method} {path}"))
+        .collect();
+    let changed_responses = new_operations
+        .into_iter()
+        .filter_map(|(method, path, new_operation)| {
+            let (_, _, old_operation) = old_operations
+                .iter()
+                .find(|(old_method, old_path, _)| *old_method == method && *old_path == path)?;
+            let old_codes = old_operation
+                .responses
+                .responses
+                .keys()
+                .collect::<BTreeSet<_>>();
+            let new_codes = new_operation
+                .responses
+                .responses
+                .keys()
+                .collect::<BTreeSet<_>>();
+            let change = ResponseChange {
+                operation: format!("{method} {path}"),
+                added: difference(&new_codes, &old_codes),
+                removed: difference(&old_codes, &new_codes),
+            };
+            (!change.added.is_empty() || !change.removed.is_empty()).then_some(change)
+        })
+        .collect();
+
+    SpecDiff {
+        added_paths: difference(&new_paths, &old_paths),
+        removed_paths: difference(&old_paths, &new_paths),
+        removed_operations,
+        changed_responses,
+        removed_schemas: removed_schemas(old, new),
+        removed_schema_fields: removed_schema_fields(old, new),
+    }
+}
+
+fn difference(left: &BTreeSet<&String>, right: &BTreeSet<&String>) -> Vec<String> {
+    left.difference(right)
+        .map(|value| (*value).clone())
+        .collect()
+}
+
+fn removed_schemas(old: &OpenApi, new: &OpenApi) -> Vec<String> {
+    let schemas = |spec: &OpenApi| {
+        spec.components
+            .iter()
+            .flat_map(|components| components.schemas.keys().cloned())
+            .collect::<BTreeSet<_>>()
+    };
+    let new_schemas = schemas(new);
+    schemas(old)
+        .into_iter()
+        .filter(|name| !new_schemas.contains(name))
+        .collect()
+}
+
+fn removed_schema_fields(old: &OpenApi, new: &OpenApi) -> Vec<String> {
+    let (Some(old_components), Some(new_components)) = (&old.components, &new.components) else {
+        return Vec::new();
+    };
+    let properties = |schema: &RefOr<Schema>| -> BTreeSet<String> {
+        serde_json::to_value(schema)
+            .ok()
+            .and_then(|schema| match schema.get("properties") {
+                Some(Value::Object(properties)) => Some(properties.keys().cloned().collect()),
+                _ => None,
+            })
+            .unwrap_or_default()
+    };
+
+    old_components
+        .schemas
+        .iter()
+        .filter_map(|(name, old_schema)| {
+            let new_schema = new_components.schemas.get(name)?;
+            let new_properties = properties(new_schema);
+            Some(
+                properties(old_schema)
+                    .into_iter()
+                    .filter(move |property| !new_properties.contains(property))
+                    .map(move |property| format!("{name}.{property}")),
+            )
+        })
+        .flatten()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use utoipa::{OpenApi as _, ToSchema};
+    use utoipa_axum::{router::OpenApiRouter, routes};
+
+    use super::*;
+
+    mod v1 {
+        use super::*;
+
+        #[allow(dead_code)]
+        #[derive(ToSchema)]
+        pub struct Album {
+            title: String,
+            rating: u32,
+        }
+
+        #[allow(dead_code)]
+        #[derive(ToSchema)]
+        pub struct Review {
+            text: String,
+        }
+
+        #[utoipa::path(
+            get,
+            path = "/api/album",
+            responses(
+                (status = 200, description = "Album", body = Album),
+                (status = 404, description = "Not found"),
+            ),
+        )]
+        pub async fn get_album() {}
+
+        #[utoipa::path(
+            delete,
+            path = "/api/album",
+            responses((status = 200, description = "Review", body = Review)),
+        )]
+        pub async fn delete_album() {}
+    }
+
+    mod v2 {
+        use super::*;
+
+        #[allow(dead_code)]
+        #[derive(ToSchema)]
+        pub struct Album {
+            title: String,
+        }
+
+        #[utoipa::path(
+            get,
+            path = "/api/album",
+            responses(
+                (status = 200, description = "Album", body = Album),
+                (status = 401, description = "Unauthorized"),
+            ),
+        )]
+        pub async fn get_album() {}
+
+        #[utoipa::path(get, path = "/api/artist", responses((status = 200, description = "Artist")))]
+        pub async fn get_artist() {}
+    }
+
+    #[derive(utoipa::OpenApi)]
+    struct ApiDoc;
+
+    #[test]
+    fn test_diff_specs() {
+        let (_, old) = OpenApiRouter::<()>::with_openapi(ApiDoc::openapi())
+            .routes(routes!(v1::get_album, v1::delete_album))
+            .split_for_parts();
+        let (_, new) = OpenApiRouter::<()>::with_openapi(ApiDoc::openapi())
+            .routes(routes!(v2::get_album))
+            .routes(routes!(v2::get_artist))
+            .split_for_parts();
+
+        let diff = diff_specs(&old, &new);
+
+        assert_eq!(
+            diff,
+            SpecDiff {
+                added_paths: vec!["/api/artist".to_string()],
+                removed_paths: vec![],
+                removed_operations: vec!["DELETE /api/album".to_string()],
+                changed_responses: vec![ResponseChange {
+                    operation: "GET /api/album".to_string(),
+                    added: vec!["401".to_string()],
+                    removed: vec!["404".to_string()],
+                }],
+                removed_schemas: vec!["Review".to_string()],
+                removed_schema_fields: vec!["Album.rating".to_string()],
+            }
+        );
+        assert!(diff.is_breaking());
+        assert!(diff_specs(&old, &old).is_empty());
+    }
+
+    #[test]
+    fn test_removed_operation_is_breaking() {
+        let (_, old) = OpenApiRouter::<()>::with_openapi(ApiDoc::openapi())
+            .routes(routes!(v2::get_album, v1::delete_album))
+            .split_for_parts();
+        let (_, new) = OpenApiRouter::<()>::with_openapi(ApiDoc::openapi())
+            .routes(routes!(v2::get_album))
+            .split_for_parts();
+
+        let diff = diff_specs(&old, &new);
+        assert_eq!(
+            diff.removed_operations,
+            vec!["DELETE /api/album".to_string()]
+        );
+        assert_eq!(diff.removed_schemas, vec!["Review".to_string()]);
+        assert!(diff.is_breaking());
+    }
+}
@@ -0,0 +1,275 @@
+use loco_rs::Result;
+use serde_json::{Map, Value};
+use utoipa::openapi::OpenApi;
+
+/// Version of the downgraded spec, see [`to_openapi_3_0`]
+pub const OPENAPI_3_0_VERSION: &str = "3.0.3";
+
+/// Members whose keys are names, e.g. of properties or status codes, rather
+/// than keywords
+const NAMED_MEMBERS: [&str; 14] = [
+    "properties",
+    "patternProperties",
+    "$defs",
+    "schemas",
+    "responses",
+    "parameters",
+    "headers",
+    "requestBodies",
+    "securitySchemes",
+    "links",
+    "callbacks",
+    "content",
+    "encoding",
+    "paths",
+];
+
+/// Members holding example or literal values rather than schemas
+const VALUE_MEMBERS: [&str; 5] = ["example", "examples", "default", "enum", "const"];
+
+/// The spec as an `OpenAPI` 3.0 document, for tools that don't support 3.1
+///
+/// utoipa documents 3.1, where an `Option<T>` field is a union with `null`.
+/// 3.0 has no `null` type, so the unions are rewritten to `nullable: true`:
+/// `type: [string, "null"]` becomes `type: string`, and a `oneOf` or `anyOf`
+/// with a `null` variant keeps the other variants, a single `$ref` variant
+/// is wrapped in `allOf` since 3.0 ignores the siblings of a `$ref`. The
+/// `examples` of a schema become its first `example`
+///
+/// The 3.1 members 3.0 has no place for are dropped: `webhooks`,
+/// `jsonSchemaDialect`, `components.pathItems` and `info.summary`. A license
+/// `identifier` becomes the `url` of the SPDX license. Other 3.1 keywords are
+/// kept as they are
+///
+/// # Errors
+///
+/// Will return `Err` if the spec can't be serialized
+pub fn to_openapi_3_0(spec: &OpenApi) -> Result<Value> {
+    let mut document = serde_json::to_value(spec)?;
+    if let Value::Object(document) = &mut document {
+        drop_3_1_members(document);
+        document.insert("openapi".to_string(), OPENAPI_3_0_VERSION.into());
+    }
+    rewrite_schemas(&mut document, false);
+    Ok(document)
+}
+
+fn drop_3_1_members(document: &mut Map<String, Value>) {
+    document.remove("webhooks");
+    document.remove("jsonSchemaDialect");
+    if let Some(Value::Object(components)) = document.get_mut("components") {
+        components.remove("pathItems");
+    }
+    let Some(Value::Object(info)) = document.get_mut("info") else {
+        return;
+    };
+    info.remove("summary");
+    if let Some(Value::Object(license)) = info.get_mut("license") {
+        if let Some(Value::String(identifier)) = license.remove("identifier") {
+            license
+                .entry("url")
+                .or_insert_with(|| format!("https://spdx.org/licenses/{identifier}.html").into());
+        }
+    }
+}
+
+/// Rewrite the schemas of a member, `named` when its keys are names
+fn rewrite_schemas(value: &mut Value, named: bool) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                if named {
+                    rewrite_schemas(value, false);
+                } else if !VALUE_MEMBERS.contains(&key.as_str()) {
+                    rewrite_schemas(value, NAMED_MEMBERS.contains(&key.as_str()));
+                }
+            }
+            if named {
+                return;
+            }
+            nullable_type(map);
+            for keyword in ["oneOf", "anyOf"] {
+                nullable_variant(map, keyword);
+            }
+            first_example(map);
+        }
+        Value::Array(values) => values
+            .iter_mut()
+            .for_each(|value| rewrite_schemas(value, false)),
+        _ => {}
+    }
+}
+
+/// The `examples` array of a schema to its first `example`, the `examples`
+/// map of a media type or parameter is already 3.0
+fn first_example(schema: &mut Map<String, Value>) {
+    if !schema.get("examples").is_some_and(Value::is_array) {
+        return;
+    }
+    if let Some(Value::Array(examples)) = schema.remove("examples") {
+        if let Some(example) = examples.into_iter().next() {
+            schema.entry("example").or_insert(example);
+        }
+    }
+}
+
+/// `type: [T, "null"]` to `type: T, nullable: true`, several types to an
+/// `anyOf` of the types, only `null` to `enum: [null]`
+fn nullable_type(schema: &mut Map<String, Value>) {
+    let Some(Value::Array(types)) = schema.get("type") else {
+        return;
+    };
+    let nullable = types.iter().any(|schema_type| schema_type == "null");
+    let mut types = types
+        .iter()
+        .filter(|schema_type| *schema_type != "null")
+        .cloned()
+        .collect::<Vec<_>>();
+    if types.is_empty() {
+        schema.remove("type");
+        schema.insert("enum".to_string(), Value::Array(vec![Value::Null]));
+    } else if types.len() == 1 {
+        schema.insert("type".to_string(), types.remove(0));
+    } else {
+        schema.remove("type");
+        let variants = types
+            .into_iter()
+            .map(|schema_type| serde_json::json!({ "type": schema_type }))
+            .collect();
+        schema.insert("anyOf".to_string(), Value::Array(variants));
+    }
+    if nullable {
+        schema.insert("nullable".to_string(), Value::Bool(true));
+    }
+}
+
+/// A `null` variant of a `oneOf` or `anyOf` to `nullable: true`
+fn nullable_variant(schema: &mut Map<String, Value>, keyword: &str) {
+    let Some(Value::Array(variants)) = schema.get_mut(keyword) else {
+        return;
+    };
+    let is_null = |variant: &Value| {
+        variant
+            .get("type")
+            .is_some_and(|schema_type| schema_type == "null")
+    };
+    if !variants.iter().any(is_null) {
+        return;
+    }
+    variants.retain(|variant| !is_null(variant));
+    if variants.len() == 1 {
+        let Some(Value::Array(mut variants)) = schema.remove(keyword) else {
+            return;
+        };
+        let variant = variants.remove(0);
+        match variant {
+            Value::Object(variant) if !variant.contains_key("$ref") => {
+                for (key, value) in variant {
+                    schema.entry(key).or_insert(value);
+                }
+            }
+            variant => {
+                schema.insert("allOf".to_string(), Value::Array(vec![variant]));
+            }
+        }
+    }
+    schema.insert("nullable".to_string(), Value::Bool(true));
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use utoipa::{OpenApi as _, ToSchema};
+
+    use super::*;
+
+    #[allow(dead_code)]
+    #[derive(ToSchema)]
+    struct Artist {
+        name: String,
+    }
+
+    #[allow(dead_code)]
+    #[derive(ToSchema)]
+    struct Album {
+        title: String,
+        subtitle: Option<String>,
+        artist: Option<Artist>,
+    }
+
+    #[derive(utoipa::OpenApi)]
+    #[openapi(components(schemas(Album, Artist)))]
+    struct ApiDoc;
+
+    #[test]
+    fn test_to_openapi_3_0() {
+        let document = to_openapi_3_0(&ApiDoc::openapi()).unwrap();
+
+        assert_eq!(document["openapi"], OPENAPI_3_0_VERSION);
+        let properties = &document["components"]["schemas"]["Album"]["properties"];
+        assert_eq!(
+            properties["subtitle"],
+            json!({ "type": "string", "nullable": true })
+        );
+        assert_eq!(
+            properties["artist"],
+            json!({ "allOf": [{ "$ref": "#/components/schemas/Artist" }], "nullable": true })
+        );
+        assert_eq!(properties["title"], json!({ "type": "string" }));
+    }
+
+    #[test]
+    fn test_3_1_members() {
+        let spec = crate::members::from_value(&json!({
+            "openapi": "3.1.0",
+            "jsonSchemaDialect": "https://spec.openapis.org/oas/3.1/dialect/base",
+            "info": {
+                "title": "albums",
+                "version": "1.0.0",
+                "summary": "Albums",
+                "license": { "name": "MIT", "identifier": "MIT" }
+            },
+            "paths": {},
+            "components": {
+                "schemas": {
+                    "Album": {
+                        "type": "object",
+                        "properties": {
+                            "examples": { "type": ["null"] },
+                            "title": { "type": "string", "examples": ["Abbey Road", "Help!"] }
+                        },
+                        "examples": [{ "title": "Abbey Road", "type": ["string", "null"] }]
+                    }
+                }
+            },
+            "webhooks": {
+                "album.created": { "post": { "responses": { "200": { "description": "Ok" } } } }
+            }
+        }))
+        .unwrap();
+
+        let document = to_openapi_3_0(&spec).unwrap();
+
+        assert!(document.get("webhooks").is_none());
+        assert!(document.get("jsonSchemaDialect").is_none());
+        assert!(document["info"].get("summary").is_none());
+        assert_eq!(
+            document["info"]["license"],
+            json!({ "name": "MIT", "url": "https://spdx.org/licenses/MIT.html" })
+        );
+        let album = &document["components"]["schemas"]["Album"];
+        assert_eq!(
+            album["properties"]["examples"],
+            json!({ "enum": [null], "nullable": true })
+        );
+        assert_eq!(
+            album["properties"]["title"],
+            json!({ "type": "string", "example": "Abbey Road" })
+        );
+        assert_eq!(
+            album["example"],
+            json!({ "title": "Abbey Road", "type": ["string", "null"] })
+        );
+        assert!(album.get("examples").is_none());
+    }
+}
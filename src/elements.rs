@@ -0,0 +1,69 @@
+use axum::{response::Html, routing::get, Router as AxumRouter};
+
+/// Default HTML template for the Stoplight Elements web component
+///
+/// `$specUrl` is replaced with the URL of the JSON spec
+const DEFAULT_HTML: &str = r#"<!doctype html>
+<html lang="en">
+  <head>
+    <meta charset="utf-8" />
+    <meta name="viewport" content="width=device-width, initial-scale=1, shrink-to-fit=no" />
+    <title>Elements</title>
+    <script src="https://unpkg.com/@stoplight/elements/web-components.min.js"></script>
+    <link rel="stylesheet" href="https://unpkg.com/@stoplight/elements/styles.min.css" />
+  </head>
+  <body>
+    <elements-api apiDescriptionUrl="$specUrl" router="hash" layout="sidebar"></elements-api>
+  </body>
+</html>
+"#;
+
+/// Stoplight Elements `OpenAPI` visualizer
+///
+/// Unlike Redoc and Scalar the spec is not embedded in the page, the web
+/// component fetches it from `spec_url` instead
+#[derive(Debug, Clone)]
+pub struct Elements {
+    /// URL for where to host the Elements page, example: /elements
+    url: String,
+    /// URL the web component loads the JSON spec from
+    spec_url: String,
+}
+
+impl Elements {
+    #[must_use]
+    pub fn with_url(url: impl Into<String>, spec_url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            spec_url: spec_url.into(),
+        }
+    }
+
+    /// Render the Elements HTML page
+    #[must_use]
+    pub fn to_html(&self) -> String {
+        DEFAULT_HTML.replace("$specUrl", &self.spec_url)
+    }
+}
+
+impl<S> From<Elements> for AxumRouter<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    fn from(elements: Elements) -> Self {
+        let html = elements.to_html();
+        Self::new().route(&elements.url, get(move || async move { Html(html) }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_html_points_at_spec_url() {
+        let html = Elements::with_url("/docs", "/docs/openapi.json").to_html();
+        assert!(html.contains(r#"apiDescriptionUrl="/docs/openapi.json""#));
+        assert!(!html.contains("$specUrl"));
+    }
+}
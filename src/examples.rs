@@ -0,0 +1,101 @@
+use std::collections::BTreeMap;
+use std::sync::{Mutex, OnceLock};
+
+use serde_json::Value;
+use utoipa::{
+    openapi::{schema::Schema, OpenApi, RefOr},
+    Modify,
+};
+
+static SCHEMA_EXAMPLES: OnceLock<Mutex<BTreeMap<String, Value>>> = OnceLock::new();
+
+fn get_schema_examples() -> &'static Mutex<BTreeMap<String, Value>> {
+    SCHEMA_EXAMPLES.get_or_init(|| Mutex::new(BTreeMap::new()))
+}
+
+/// Set the example of a component schema, keeping example data out of the
+/// `ToSchema` definitions. It replaces the `examples` of the schema
+/// ```rust
+/// use by_loco_openapi::prelude::*;
+///
+/// set_schema_example("Album", serde_json::json!({ "title": "VH II", "rating": 10 }));
+/// ```
+pub fn set_schema_example(name: &str, example: Value) {
+    if let Ok(mut examples) = get_schema_examples().lock() {
+        examples.insert(name.to_string(), example);
+    }
+}
+
+/// Clears the examples set with [`set_schema_example`], mostly used for testing
+pub fn clear_schema_examples() {
+    if let Ok(mut examples) = get_schema_examples().lock() {
+        examples.clear();
+    }
+}
+
+/// Sets the registered examples on the matching component schemas
+pub struct SchemaExamples;
+
+impl Modify for SchemaExamples {
+    fn modify(&self, openapi: &mut OpenApi) {
+        let Some(components) = openapi.components.as_mut() else {
+            return;
+        };
+        let Ok(examples) = get_schema_examples().lock() else {
+            return;
+        };
+        for (name, example) in examples.iter() {
+            let Some(RefOr::T(schema)) = components.schemas.get_mut(name) else {
+                continue;
+            };
+            let examples = vec![example.clone()];
+            match schema {
+                Schema::Object(object) => object.examples = examples,
+                Schema::Array(array) => array.examples = examples,
+                Schema::OneOf(one_of) => one_of.examples = examples,
+                Schema::AllOf(all_of) => all_of.examples = examples,
+                Schema::AnyOf(any_of) => any_of.examples = examples,
+                _ => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Serialize;
+    use utoipa::{OpenApi as _, ToSchema};
+
+    use super::*;
+
+    #[derive(Serialize, ToSchema)]
+    #[allow(dead_code)]
+    struct Album {
+        title: String,
+        rating: u32,
+    }
+
+    #[derive(utoipa::OpenApi)]
+    #[openapi(components(schemas(Album)))]
+    struct ApiDoc;
+
+    #[test]
+    #[serial_test::serial]
+    fn test_example_set_on_schema() {
+        set_schema_example(
+            "Album",
+            serde_json::json!({ "title": "VH II", "rating": 10 }),
+        );
+
+        let mut spec = ApiDoc::openapi();
+        SchemaExamples.modify(&mut spec);
+
+        let json = serde_json::to_value(&spec).unwrap();
+        assert_eq!(
+            json["components"]["schemas"]["Album"]["examples"],
+            serde_json::json!([{ "title": "VH II", "rating": 10 }])
+        );
+
+        clear_schema_examples();
+    }
+}
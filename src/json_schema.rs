@@ -0,0 +1,182 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use axum::{
+    extract::Path,
+    http::{header, StatusCode},
+    response::IntoResponse,
+    routing::get,
+    Router as AxumRouter,
+};
+use serde_json::Value;
+use tower_http::compression::CompressionLayer;
+use utoipa::openapi::OpenApi;
+
+use crate::openapi::COMPONENT_SCHEMA_PREFIX;
+use crate::utils::{CachedSpec, SpecRendering};
+
+const JSON_SCHEMA_DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// Standalone JSON Schema documents of the component schemas, by name
+///
+/// Refs to other component schemas are rewritten to the relative
+/// `{name}.json` file of the referenced schema
+#[must_use]
+pub fn json_schemas(spec: &OpenApi) -> BTreeMap<String, Value> {
+    let Some(components) = &spec.components else {
+        return BTreeMap::new();
+    };
+    components
+        .schemas
+        .iter()
+        .filter_map(|(name, schema)| {
+            let mut schema = serde_json::to_value(schema).ok()?;
+            rewrite_refs(&mut schema);
+            if let Value::Object(schema) = &mut schema {
+                schema.insert("$schema".to_string(), JSON_SCHEMA_DIALECT.into());
+                schema
+                    .entry("title")
+                    .or_insert_with(|| name.as_str().into());
+            }
+            Some((name.clone(), schema))
+        })
+        .collect()
+}
+
+fn rewrite_refs(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            if let Some(Value::String(reference)) = map.get_mut("$ref") {
+                if let Some(name) = reference.strip_prefix(COMPONENT_SCHEMA_PREFIX) {
+                    *reference = format!("{name}.json");
+                }
+            }
+            map.values_mut().for_each(rewrite_refs);
+        }
+        Value::Array(values) => values.iter_mut().for_each(rewrite_refs),
+        _ => {}
+    }
+}
+
+/// Adds an endpoint serving every component schema of the spec as a JSON
+/// Schema document at `{url}/{name}.json`
+///
+/// The documents are serialized on the first request, and again after the
+/// spec is replaced
+pub fn add_json_schema_endpoints<T>(
+    app: AxumRouter<T>,
+    spec: Arc<CachedSpec>,
+    url: &str,
+) -> AxumRouter<T>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    let documents = SpecRendering::new(spec, |spec| {
+        Arc::new(
+            json_schemas(spec.spec())
+                .into_iter()
+                .map(|(name, schema)| (format!("{name}.json"), schema.to_string()))
+                .collect::<BTreeMap<_, _>>(),
+        )
+    });
+    app.route(
+        &format!("{}/{{file}}", url.trim_end_matches('/')),
+        get(move |Path(file): Path<String>| {
+            let document = documents.get().get(&file).cloned();
+            async move {
+                match document {
+                    Some(body) => {
+                        ([(header::CONTENT_TYPE, "application/schema+json")], body).into_response()
+                    }
+                    None => StatusCode::NOT_FOUND.into_response(),
+                }
+            }
+        })
+        .layer(CompressionLayer::new()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{body::Body, http::Request};
+    use tower::ServiceExt;
+    use utoipa::{OpenApi as _, ToSchema};
+
+    use super::*;
+
+    #[allow(dead_code)]
+    #[derive(ToSchema)]
+    struct Artist {
+        name: String,
+    }
+
+    #[allow(dead_code)]
+    #[derive(ToSchema)]
+    struct Album {
+        title: String,
+        artist: Artist,
+    }
+
+    #[derive(utoipa::OpenApi)]
+    #[openapi(components(schemas(Album, Artist)))]
+    struct ApiDoc;
+
+    #[test]
+    fn test_json_schemas() {
+        let schemas = json_schemas(&ApiDoc::openapi());
+
+        assert_eq!(
+            schemas["Album"],
+            serde_json::json!({
+                "$schema": JSON_SCHEMA_DIALECT,
+                "title": "Album",
+                "type": "object",
+                "required": ["title", "artist"],
+                "properties": {
+                    "title": { "type": "string" },
+                    "artist": { "$ref": "Artist.json" }
+                }
+            })
+        );
+        assert_eq!(schemas["Artist"]["title"], "Artist");
+    }
+
+    #[tokio::test]
+    async fn test_json_schema_endpoints() {
+        let spec = Arc::new(CachedSpec::new(ApiDoc::openapi()).unwrap());
+        let app: AxumRouter = add_json_schema_endpoints(AxumRouter::new(), spec, "/schemas/");
+
+        let res = app
+            .clone()
+            .oneshot(
+                Request::get("/schemas/Artist.json")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(
+            res.headers()[header::CONTENT_TYPE],
+            "application/schema+json"
+        );
+        let body = axum::body::to_bytes(res.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(
+            serde_json::from_slice::<Value>(&body).unwrap(),
+            json_schemas(&ApiDoc::openapi())["Artist"]
+        );
+
+        let res = app
+            .oneshot(
+                Request::get("/schemas/Label.json")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    }
+}
@@ -1,39 +1,159 @@
+// The functions return `loco_rs::Error` to compose with the `Initializer`
+// hooks and handlers of Loco, the size of that error isn't ours to change
+#![allow(clippy::result_large_err)]
+
+use std::collections::BTreeSet;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::Arc;
+
 use async_trait::async_trait;
 use axum::Router as AxumRouter;
-use loco_rs::prelude::*;
-use utoipa::openapi::OpenApi;
+#[cfg(any(
+    feature = "redoc",
+    feature = "scalar",
+    feature = "swagger-cdn",
+    feature = "elements"
+))]
+use axum::{
+    response::{Html, Redirect},
+    routing::get,
+};
+use loco_rs::{app::Hooks, prelude::*};
+use utoipa::{
+    openapi::{OpenApi, Server},
+    Modify,
+};
 use utoipa_axum::router::OpenApiRouter;
 #[cfg(feature = "redoc")]
-use utoipa_redoc::{Redoc, Servable};
+use utoipa_redoc::Redoc;
 #[cfg(feature = "scalar")]
-use utoipa_scalar::{Scalar, Servable as ScalarServable};
+use utoipa_scalar::Scalar;
 #[cfg(feature = "swagger")]
-use utoipa_swagger_ui::SwaggerUi;
+use utoipa_swagger_ui::{Config as SwaggerConfig, SwaggerUi};
 
-use crate::config::{get_openapi_config, set_openapi_config, InitializerConfig};
-use crate::openapi::get_merged_router;
-// Always used
-use crate::utils::set_openapi_spec;
+use crate::auth::JwtSchemeRequirements;
+use crate::callbacks::OperationCallbacks;
+#[cfg(feature = "swagger-cdn")]
+use crate::config::SwaggerAssets;
+use crate::config::{
+    get_openapi_config, set_openapi_config, InitializerConfig, OpenAPIConfig, OpenAPIType,
+};
+#[cfg(feature = "elements")]
+use crate::elements::Elements;
+use crate::examples::SchemaExamples;
+use crate::links::ResponseLinks;
+#[cfg(not(feature = "validate"))]
+use crate::openapi::check_refs;
+use crate::openapi::{
+    clear_route_registries, finalize_spec, get_collected_group_routes, get_collected_routes,
+    get_groups, get_hidden_spec, get_operation_id_strategy, merge_external_spec, merge_routes,
+    merge_webhooks, operations, without_registration, RouteCollector,
+};
+use crate::parameters::SharedParameters;
+use crate::rate_limit::RateLimiter;
+use crate::responses::{DefaultErrorResponses, GlobalResponseHeaders};
+use crate::schemas::{DeprecatedFields, RegisteredSchemas};
+#[cfg(feature = "swagger-cdn")]
+use crate::swagger_cdn::SwaggerCdn;
+use crate::utils::{
+    add_allowed_hosts, add_negotiated_spec_endpoint, add_page_branding, add_request_id,
+    add_spec_endpoints, add_version_endpoint, get_cached_openapi_group_spec,
+    get_cached_openapi_spec, set_openapi_group_specs, set_openapi_lean_spec, set_openapi_spec,
+    strip_descriptions, try_get_cached_openapi_spec, BuildInfo, CachedSpec, WELL_KNOWN_SPEC_URL,
+};
 // Only used in feature blocks
-#[cfg(any(feature = "redoc", feature = "scalar", feature = "swagger"))]
-use crate::utils::{add_openapi_endpoints, get_openapi_spec};
+#[cfg(feature = "markdown")]
+use crate::utils::add_markdown_endpoint;
+#[cfg(feature = "metrics")]
+use crate::utils::add_metrics;
+#[cfg(any(
+    feature = "redoc",
+    feature = "scalar",
+    feature = "swagger-cdn",
+    feature = "elements"
+))]
+use crate::utils::alternate_trailing_slash;
+#[cfg(any(
+    feature = "redoc",
+    feature = "scalar",
+    feature = "swagger-cdn",
+    feature = "elements"
+))]
+use crate::utils::SpecRendering;
 
 pub mod auth;
+pub mod callbacks;
 pub mod config;
+pub mod diff;
+pub mod downgrade;
+#[cfg(feature = "elements")]
+pub mod elements;
+pub mod examples;
+#[cfg(feature = "json-schema")]
+pub mod json_schema;
+pub mod links;
+pub mod lint;
+mod members;
 pub mod openapi;
+pub mod parameters;
+#[cfg(feature = "postman")]
+pub mod postman;
 pub mod prelude;
+pub mod rate_limit;
+pub mod responses;
+pub mod schemas;
+#[cfg(feature = "swagger-cdn")]
+pub mod swagger_cdn;
+#[cfg(feature = "test-support")]
+pub mod testing;
 pub mod utils;
+#[cfg(feature = "validate")]
+pub mod validate;
 
 type RouterList = Option<Vec<OpenApiRouter<AppContext>>>;
-type InitialSpec = dyn Fn(&AppContext) -> OpenApi + Send + Sync + 'static;
+type InitialSpec = dyn Fn(&AppContext) -> Result<OpenApi> + Send + Sync + 'static;
+type SpecFuture = Pin<Box<dyn Future<Output = Result<OpenApi>> + Send + 'static>>;
+type AsyncInitialSpec = dyn Fn(&AppContext) -> SpecFuture + Send + Sync + 'static;
+type Modifier = dyn Modify + Send + Sync + 'static;
+type RouteLister = dyn Fn(&AppContext) -> Vec<(String, String)> + Send + Sync + 'static;
+type RouterHook = dyn Fn(AxumRouter) -> AxumRouter + Send + Sync + 'static;
+type EnabledFn = dyn Fn(&AppContext) -> bool + Send + Sync + 'static;
 
 /// Loco initializer for `OpenAPI` with custom initial spec setup
 #[derive(Default)]
 pub struct OpenapiInitializerWithSetup {
     /// Custom setup for the initial `OpenAPI` spec, if any
     initial_spec: Option<Box<InitialSpec>>,
+    /// Custom async setup for the initial `OpenAPI` spec, takes precedence
+    /// over `initial_spec`
+    async_initial_spec: Option<Box<AsyncInitialSpec>>,
     /// Routes to add to the `OpenAPI` spec
     routes_setup: RouterList,
+    /// Modifiers applied to the collected `OpenAPI` spec
+    modifiers: Vec<Box<Modifier>>,
+    /// Servers to set on the collected `OpenAPI` spec
+    servers: Vec<Server>,
+    /// App name and version, used for the spec `info` when it doesn't set
+    /// them
+    app_info: Option<(String, String)>,
+    /// Externally authored specs merged into the collected `OpenAPI` spec
+    external_specs: Vec<OpenApi>,
+    /// Lists the method and path of every app route, to warn about the
+    /// undocumented ones
+    app_routes: Option<Box<RouteLister>>,
+    /// Post-processes the router of the visualizer and spec routes before it
+    /// is merged into the app router, applied on every boot
+    router_hook: Option<Arc<RouterHook>>,
+    /// Only collect the specs, the app mounts the visualizer and spec routes
+    /// with [`build_ui_router`]
+    detached: bool,
+    /// Checked on boot, the visualizer and spec routes are only mounted when
+    /// it returns `true`
+    enabled_fn: Option<Box<EnabledFn>>,
+    /// Collectors whose routes are added to the main spec
+    collectors: Vec<RouteCollector>,
 }
 
 impl OpenapiInitializerWithSetup {
@@ -41,11 +161,406 @@ impl OpenapiInitializerWithSetup {
     pub fn new<F>(initial_spec: F, routes_setup: RouterList) -> Self
     where
         F: Fn(&AppContext) -> OpenApi + Send + Sync + 'static,
+    {
+        Self::try_new(move |ctx| Ok(initial_spec(ctx)), routes_setup)
+    }
+
+    /// Like [`Self::new`], with an initial spec setup that can fail, e.g.
+    /// when it reads a file. An error aborts the boot
+    #[must_use]
+    pub fn try_new<F>(initial_spec: F, routes_setup: RouterList) -> Self
+    where
+        F: Fn(&AppContext) -> Result<OpenApi> + Send + Sync + 'static,
     {
         Self {
             initial_spec: Some(Box::new(initial_spec)),
             routes_setup,
+            ..Default::default()
+        }
+    }
+
+    /// Initializer documenting the manually collected `routers`, without
+    /// wrapping them in `Some(vec![...])`
+    /// ```rust
+    /// # use by_loco_openapi::prelude::*;
+    /// # use loco_rs::prelude::*;
+    /// # #[derive(OpenApi)]
+    /// # struct ApiDoc;
+    /// # fn api_routes() -> OpenApiRouter<AppContext> { OpenApiRouter::new() }
+    /// let initializer = by_loco_openapi::OpenapiInitializerWithSetup::from_routers([api_routes()])
+    ///     .with_initial_spec(|_ctx| ApiDoc::openapi());
+    /// ```
+    #[must_use]
+    pub fn from_routers<I>(routers: I) -> Self
+    where
+        I: IntoIterator<Item = OpenApiRouter<AppContext>>,
+    {
+        Self {
+            routes_setup: Some(routers.into_iter().collect()),
+            ..Default::default()
+        }
+    }
+
+    /// Initializer with the initial spec read from a JSON or YAML file, by
+    /// extension, e.g. to keep the base `info`, `servers` and `tags` in an
+    /// `openapi.base.yaml`. The file is read at boot, a missing or invalid
+    /// file aborts the boot
+    /// ```rust
+    /// let initializer = by_loco_openapi::OpenapiInitializerWithSetup::from_file("openapi.base.yaml");
+    /// ```
+    #[must_use]
+    pub fn from_file(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        Self::try_new(move |_ctx| read_spec_file(&path), None)
+    }
+
+    /// Set the custom setup for the initial `OpenAPI` spec
+    #[must_use]
+    pub fn with_initial_spec<F>(mut self, initial_spec: F) -> Self
+    where
+        F: Fn(&AppContext) -> OpenApi + Send + Sync + 'static,
+    {
+        self.initial_spec = Some(Box::new(move |ctx| Ok(initial_spec(ctx))));
+        self
+    }
+
+    /// Post-process the router of the visualizer and spec routes before it is
+    /// merged into the app router, e.g. to add layers only around the docs
+    /// ```rust
+    /// # use by_loco_openapi::prelude::*;
+    /// # use loco_rs::prelude::*;
+    /// # #[derive(OpenApi)]
+    /// # struct ApiDoc;
+    /// let initializer = by_loco_openapi::OpenapiInitializerWithSetup::new(|_ctx| ApiDoc::openapi(), None)
+    ///     .with_router_hook(|router| router.layer(tower_http::compression::CompressionLayer::new()));
+    /// ```
+    #[must_use]
+    pub fn with_router_hook<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(AxumRouter) -> AxumRouter + Send + Sync + 'static,
+    {
+        self.router_hook = Some(Arc::new(hook));
+        self
+    }
+
+    /// Only collect the specs in `after_routes`, without mounting the
+    /// visualizer and spec routes at the top level of the app. Mount them
+    /// with [`build_ui_router`] instead, e.g. nested under another router.
+    /// The router hook isn't applied to that router
+    #[must_use]
+    pub fn detached(mut self) -> Self {
+        self.detached = true;
+        self
+    }
+
+    /// Add the routes of `collector` to the main spec, along with the routes
+    /// registered with `openapi()`, see [`RouteCollector`]
+    #[must_use]
+    pub fn with_collector(mut self, collector: &RouteCollector) -> Self {
+        self.collectors.push(collector.clone());
+        self
+    }
+
+    /// Only mount the visualizer and spec routes when `enabled` returns
+    /// `true` on boot, e.g. to gate the docs behind a runtime feature flag.
+    /// The specs are collected either way
+    /// ```rust
+    /// # use by_loco_openapi::prelude::*;
+    /// # #[derive(OpenApi)]
+    /// # struct ApiDoc;
+    /// let initializer = by_loco_openapi::OpenapiInitializerWithSetup::new(|_ctx| ApiDoc::openapi(), None)
+    ///     .with_enabled_fn(|_ctx| std::env::var("DOCS_ENABLED").is_ok_and(|value| value == "1"));
+    /// ```
+    #[must_use]
+    pub fn with_enabled_fn<F>(mut self, enabled: F) -> Self
+    where
+        F: Fn(&AppContext) -> bool + Send + Sync + 'static,
+    {
+        self.enabled_fn = Some(Box::new(enabled));
+        self
+    }
+
+    /// Whether the visualizer and spec routes are mounted, see
+    /// [`Self::with_enabled_fn`]
+    fn is_enabled(&self, ctx: &AppContext) -> bool {
+        self.enabled_fn.as_ref().is_none_or(|enabled| enabled(ctx))
+    }
+
+    /// Apply the router hook, if any
+    fn apply_router_hook(&self, ui_router: AxumRouter) -> AxumRouter {
+        match &self.router_hook {
+            Some(hook) => hook(ui_router),
+            None => ui_router,
+        }
+    }
+
+    /// Start building an initializer, every piece of the setup is optional
+    /// ```rust
+    /// # use by_loco_openapi::prelude::*;
+    /// # use loco_rs::prelude::*;
+    /// # #[derive(OpenApi)]
+    /// # struct ApiDoc;
+    /// # fn api_routes() -> OpenApiRouter<AppContext> { OpenApiRouter::new() }
+    /// let initializer = by_loco_openapi::OpenapiInitializerWithSetup::builder()
+    ///     .initial_spec(|_ctx| ApiDoc::openapi())
+    ///     .add_routes(api_routes())
+    ///     .build();
+    /// ```
+    #[must_use]
+    pub fn builder() -> OpenapiInitializerBuilder {
+        OpenapiInitializerBuilder::default()
+    }
+}
+
+/// Builder for [`OpenapiInitializerWithSetup`]
+#[derive(Default)]
+pub struct OpenapiInitializerBuilder {
+    initializer: OpenapiInitializerWithSetup,
+}
+
+impl OpenapiInitializerBuilder {
+    /// Custom setup for the initial `OpenAPI` spec
+    #[must_use]
+    pub fn initial_spec<F>(self, initial_spec: F) -> Self
+    where
+        F: Fn(&AppContext) -> OpenApi + Send + Sync + 'static,
+    {
+        self.try_initial_spec(move |ctx| Ok(initial_spec(ctx)))
+    }
+
+    /// Custom setup for the initial `OpenAPI` spec that can fail, an error
+    /// aborts the boot
+    #[must_use]
+    pub fn try_initial_spec<F>(mut self, initial_spec: F) -> Self
+    where
+        F: Fn(&AppContext) -> Result<OpenApi> + Send + Sync + 'static,
+    {
+        self.initializer.initial_spec = Some(Box::new(initial_spec));
+        self.initializer.async_initial_spec = None;
+        self
+    }
+
+    /// Custom async setup for the initial `OpenAPI` spec, e.g. to load
+    /// descriptions from a database at boot. An error aborts the boot
+    ///
+    /// The returned future can't borrow the [`AppContext`], clone what it
+    /// needs from it
+    /// ```rust
+    /// # use by_loco_openapi::prelude::*;
+    /// # use loco_rs::prelude::*;
+    /// # #[derive(OpenApi)]
+    /// # struct ApiDoc;
+    /// let initializer = by_loco_openapi::OpenapiInitializerWithSetup::builder()
+    ///     .async_initial_spec(|ctx| {
+    ///         let config = ctx.config.clone();
+    ///         async move {
+    ///             let _ = config; // load the descriptions
+    ///             Ok(ApiDoc::openapi())
+    ///         }
+    ///     })
+    ///     .build();
+    /// ```
+    #[must_use]
+    pub fn async_initial_spec<F, Fut>(mut self, initial_spec: F) -> Self
+    where
+        F: Fn(&AppContext) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<OpenApi>> + Send + 'static,
+    {
+        self.initializer.async_initial_spec = Some(Box::new(move |ctx| {
+            Box::pin(initial_spec(ctx)) as SpecFuture
+        }));
+        self.initializer.initial_spec = None;
+        self
+    }
+
+    /// Add manually collected routes to the `OpenAPI` spec, can be called
+    /// multiple times
+    #[must_use]
+    pub fn add_routes(mut self, routes: OpenApiRouter<AppContext>) -> Self {
+        self.initializer
+            .routes_setup
+            .get_or_insert_with(Vec::new)
+            .push(routes);
+        self
+    }
+
+    /// Apply a modifier to the collected `OpenAPI` spec, can be called
+    /// multiple times
+    #[must_use]
+    pub fn with_modifier<M>(mut self, modifier: M) -> Self
+    where
+        M: Modify + Send + Sync + 'static,
+    {
+        self.initializer.modifiers.push(Box::new(modifier));
+        self
+    }
+
+    /// Set the servers of the collected `OpenAPI` spec
+    #[must_use]
+    pub fn with_servers<I>(mut self, servers: I) -> Self
+    where
+        I: IntoIterator<Item = Server>,
+    {
+        self.initializer.servers.extend(servers);
+        self
+    }
+
+    /// Default the spec `info.title` and `info.version` to
+    /// [`Hooks::app_name`] and [`Hooks::app_version`], values set by the
+    /// initial spec take precedence
+    #[must_use]
+    pub fn with_app_info<H: Hooks>(mut self) -> Self {
+        self.initializer.app_info = Some((H::app_name().to_string(), H::app_version()));
+        self
+    }
+
+    /// Merge an externally authored spec, e.g. of a legacy proxy endpoint,
+    /// into the collected `OpenAPI` spec, can be called multiple times. The
+    /// collected operations and components win on conflicts, see
+    /// [`openapi::merge_external_spec`]
+    #[must_use]
+    pub fn with_external_spec(mut self, spec: OpenApi) -> Self {
+        self.initializer.external_specs.push(spec);
+        self
+    }
+
+    /// Post-process the router of the visualizer and spec routes, see
+    /// [`OpenapiInitializerWithSetup::with_router_hook`]
+    #[must_use]
+    pub fn with_router_hook<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(AxumRouter) -> AxumRouter + Send + Sync + 'static,
+    {
+        self.initializer = self.initializer.with_router_hook(hook);
+        self
+    }
+
+    /// Don't mount the visualizer and spec routes, see
+    /// [`OpenapiInitializerWithSetup::detached`]
+    #[must_use]
+    pub fn detached(mut self) -> Self {
+        self.initializer.detached = true;
+        self
+    }
+
+    /// Add the routes of `collector` to the main spec, see
+    /// [`OpenapiInitializerWithSetup::with_collector`]
+    #[must_use]
+    pub fn with_collector(mut self, collector: &RouteCollector) -> Self {
+        self.initializer = self.initializer.with_collector(collector);
+        self
+    }
+
+    /// Gate the visualizer and spec routes at runtime, see
+    /// [`OpenapiInitializerWithSetup::with_enabled_fn`]
+    #[must_use]
+    pub fn enabled_fn<F>(mut self, enabled: F) -> Self
+    where
+        F: Fn(&AppContext) -> bool + Send + Sync + 'static,
+    {
+        self.initializer = self.initializer.with_enabled_fn(enabled);
+        self
+    }
+
+    /// Warn on boot about the routes of [`Hooks::routes`] that aren't in any
+    /// spec, usually handlers added without the `openapi()` wrapper. The
+    /// paths under `exclude_paths` are not reported
+    #[must_use]
+    pub fn warn_undocumented_routes<H: Hooks>(mut self) -> Self {
+        self.initializer.app_routes = Some(Box::new(|ctx| {
+            without_registration(|| H::routes(ctx).collect())
+                .into_iter()
+                .flat_map(|route| {
+                    let uri = route.uri;
+                    route
+                        .actions
+                        .into_iter()
+                        .map(move |method| (method.to_string(), uri.clone()))
+                })
+                .collect()
+        }));
+        self
+    }
+
+    #[must_use]
+    pub fn build(self) -> OpenapiInitializerWithSetup {
+        self.initializer
+    }
+}
+
+impl OpenapiInitializerWithSetup {
+    /// Run the custom setup of the initial `OpenAPI` spec, if any
+    async fn initial_spec(&self, ctx: &AppContext) -> Result<Option<OpenApi>> {
+        if let Some(async_initial_spec) = &self.async_initial_spec {
+            return async_initial_spec(ctx).await.map(Some);
         }
+        self.initial_spec
+            .as_ref()
+            .map(|initial_spec| initial_spec(ctx))
+            .transpose()
+    }
+
+    /// Build the `OpenAPI` spec of a route group, or the main spec from the
+    /// manually and automatically collected routes
+    fn collect_spec(&self, initial_spec: Option<&OpenApi>, group: Option<&str>) -> Result<OpenApi> {
+        let mut api_router: OpenApiRouter<AppContext> = match initial_spec {
+            Some(initial_spec) => OpenApiRouter::with_openapi(initial_spec.clone()),
+            None => OpenApiRouter::new(),
+        };
+
+        let routes = if let Some(group) = group {
+            get_collected_group_routes(group)
+        } else {
+            self.routes_setup
+                .iter()
+                .flatten()
+                .cloned()
+                .chain(self.collectors.iter().flat_map(RouteCollector::routes))
+                .chain(get_collected_routes())
+                .collect()
+        };
+        let strategy = get_openapi_config()
+            .map(|config| config.duplicate_schemas)
+            .unwrap_or_default();
+        for route in routes {
+            api_router = merge_routes(api_router, route, strategy)?;
+        }
+
+        let (_, mut open_api_spec) = api_router.split_for_parts();
+        if group.is_none() {
+            for external_spec in &self.external_specs {
+                merge_external_spec(&mut open_api_spec, external_spec.clone());
+            }
+            merge_webhooks(&mut open_api_spec);
+        }
+        if let Some((app_name, app_version)) = &self.app_info {
+            if open_api_spec.info.title.is_empty() {
+                open_api_spec.info.title.clone_from(app_name);
+            }
+            if open_api_spec.info.version.is_empty() {
+                open_api_spec.info.version.clone_from(app_version);
+            }
+        }
+        if !self.servers.is_empty() {
+            open_api_spec.servers = Some(self.servers.clone());
+        }
+        get_operation_id_strategy().apply(&mut open_api_spec);
+        if let Some(open_api_config) = get_openapi_config() {
+            open_api_config.modify(&mut open_api_spec);
+        }
+        RegisteredSchemas.modify(&mut open_api_spec);
+        DeprecatedFields.modify(&mut open_api_spec);
+        SchemaExamples.modify(&mut open_api_spec);
+        SharedParameters.modify(&mut open_api_spec);
+        DefaultErrorResponses.modify(&mut open_api_spec);
+        GlobalResponseHeaders.modify(&mut open_api_spec);
+        ResponseLinks.modify(&mut open_api_spec);
+        OperationCallbacks.modify(&mut open_api_spec);
+        JwtSchemeRequirements.modify(&mut open_api_spec);
+        for modifier in &self.modifiers {
+            modifier.modify(&mut open_api_spec);
+        }
+        Ok(open_api_spec)
     }
 }
 
@@ -56,76 +571,1323 @@ impl Initializer for OpenapiInitializerWithSetup {
     }
 
     async fn after_routes(&self, router: AxumRouter, ctx: &AppContext) -> Result<AxumRouter> {
-        // Use the InitializerConfig wrapper
-        set_openapi_config(InitializerConfig::from(&ctx.config.initializers).into())?;
+        let config = set_openapi_config(openapi_config(ctx)?)?;
+        let excluded_paths = config
+            .map(|config| config.exclude_paths.as_slice())
+            .unwrap_or_default();
 
-        let mut api_router: OpenApiRouter<AppContext> = self
-            .initial_spec
-            .as_ref()
-            .map_or_else(OpenApiRouter::new, |custom_spec_fn| {
-                OpenApiRouter::with_openapi(custom_spec_fn(ctx))
-            });
+        // Collect the `OpenAPI` spec from the manually and automatically
+        // collected routes
+        let initial_spec = self.initial_spec(ctx).await?;
+        let mut open_api_spec = self.collect_spec(initial_spec.as_ref(), None)?;
+        exclude_paths(&mut open_api_spec, excluded_paths);
+        if let Some(config) = config {
+            add_default_server(
+                &mut open_api_spec,
+                &ctx.config.server,
+                config.public_url.as_deref(),
+            );
+        }
+        log_summary(&open_api_spec);
+        describe_empty_spec(&mut open_api_spec);
+        if config.is_some_and(|config| config.version_url.is_some()) {
+            BuildInfo::from_env().apply(&mut open_api_spec);
+        }
+        #[cfg(feature = "validate")]
+        validate::check_spec("main", &open_api_spec, is_strict())?;
+        #[cfg(not(feature = "validate"))]
+        if is_strict() {
+            check_refs("main", &open_api_spec)?;
+        }
+        if let Some(lint) = config.and_then(|config| config.lint.as_ref()) {
+            lint::check_lint("main", &open_api_spec, lint, is_strict())?;
+        }
+        set_openapi_spec(open_api_spec)?;
 
-        // Merge all manually collected routes
-        if let Some(ref routes_setup) = self.routes_setup {
-            for route in routes_setup {
-                api_router = api_router.merge(route.clone());
+        // Collect a separate `OpenAPI` spec for every route group
+        let group_specs = get_groups()
+            .into_iter()
+            .chain(config.into_iter().flat_map(|c| c.groups.keys().cloned()))
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .map(|group| {
+                let mut spec = self.collect_spec(initial_spec.as_ref(), Some(&group))?;
+                exclude_paths(&mut spec, excluded_paths);
+                if let Some(config) = config {
+                    add_default_server(&mut spec, &ctx.config.server, config.public_url.as_deref());
+                }
+                describe_empty_spec(&mut spec);
+                #[cfg(feature = "validate")]
+                validate::check_spec(&group, &spec, is_strict())?;
+                #[cfg(not(feature = "validate"))]
+                if is_strict() {
+                    check_refs(&group, &spec)?;
+                }
+                if let Some(lint) = config.and_then(|config| config.lint.as_ref()) {
+                    lint::check_lint(&group, &spec, lint, is_strict())?;
+                }
+                Ok((group, spec))
+            })
+            .collect::<Result<_>>()?;
+        let group_specs = set_openapi_group_specs(group_specs)?;
+        if let Some(app_routes) = &self.app_routes {
+            let main_spec = get_cached_openapi_spec();
+            let hidden_spec = get_hidden_spec();
+            let specs = std::iter::once(&main_spec)
+                .chain(group_specs.values())
+                .map(|spec| spec.spec())
+                .chain(std::iter::once(&hidden_spec));
+            let undocumented = undocumented_routes(&app_routes(ctx), specs, excluded_paths);
+            if !undocumented.is_empty() {
+                tracing::warn!(
+                    "routes missing from the OpenAPI spec, wrap their handlers with openapi(): {}",
+                    undocumented.join(", ")
+                );
             }
         }
+        finalize_spec();
+        if config.is_some_and(|config| config.reset_on_boot) {
+            clear_route_registries();
+        }
 
-        // Merge all automatically collected routes
-        api_router = api_router.merge(get_merged_router());
+        if self.detached || config.is_none() || !self.is_enabled(ctx) {
+            // No config, mounted by the app or disabled, return original router
+            return Ok(router);
+        }
+        let ui_router = self.apply_router_hook(build_ui_router()?);
 
-        // Collect the `OpenAPI` spec
-        let (_, open_api_spec) = api_router.split_for_parts();
-        set_openapi_spec(open_api_spec);
+        // Merge the UI router with the main router
+        Ok(router.merge(ui_router))
+    }
+}
 
-        // Use `_` prefix as config might be unused if no features are enabled
-        let Some(_open_api_config) = get_openapi_config() else {
-            // No config, return original router
-            return Ok(router);
-        };
+/// The `openapi` config of the app, with the environment variables of its
+/// URLs expanded
+pub(crate) fn openapi_config(ctx: &AppContext) -> Result<Option<OpenAPIConfig>> {
+    // Use the InitializerConfig wrapper
+    let initializer_config = InitializerConfig::from(&ctx.config.initializers);
+    for feature in initializer_config.missing_features() {
+        tracing::warn!(
+            feature,
+            "`{feature}` is configured in `initializers.openapi` but the `{feature}` feature of \
+             loco-openapi is not enabled, it will not be served"
+        );
+    }
+    let config = initializer_config
+        .try_into_config()?
+        .map(OpenAPIConfig::with_env_vars)
+        .transpose()?;
+    if let Some(config) = &config {
+        initializer_config.check_unknown_keys(config)?;
+        if config.dynamic_servers && config.allowed_hosts.is_empty() {
+            tracing::warn!(
+                "`dynamic_servers` is set without `allowed_hosts`, the forwarded origins of \
+                 requests aren't trusted and the servers of the spec are left unchanged"
+            );
+        }
+    }
+    Ok(config)
+}
 
-        // Create a new router for UI endpoints
-        #[allow(unused_mut)]
-        let mut ui_router = AxumRouter::new();
+/// Build the router of the visualizer and spec routes, to mount it yourself,
+/// e.g. nested under another router, with
+/// [`OpenapiInitializerWithSetup::detached`]
+///
+/// The specs must be collected first, by the `after_routes` of the `openapi`
+/// initializer. Without an `openapi` config the router is empty
+/// ```rust,no_run
+/// # fn nest(router: axum::Router) -> loco_rs::Result<axum::Router> {
+/// let router = router.nest("/internal", by_loco_openapi::build_ui_router()?);
+/// # Ok(router)
+/// # }
+/// ```
+///
+/// # Errors
+///
+/// Will return `Err` if the specs aren't collected yet or a visualizer can't
+/// be mounted
+pub fn build_ui_router() -> Result<AxumRouter> {
+    let Some(open_api_config) = get_openapi_config() else {
+        return Ok(AxumRouter::new());
+    };
+    let Some(spec) = try_get_cached_openapi_spec() else {
+        return Err(Error::string(
+            "the OpenAPI spec isn't collected yet, build the UI router after the `openapi` \
+             initializer ran",
+        ));
+    };
+
+    // Create a new router for UI endpoints
+    let mut ui_router = AxumRouter::new();
+
+    // Serve the `OpenAPI` spec using the enabled `OpenAPI` visualizers
+    for view in open_api_config.views() {
+        ui_router = add_view(ui_router, view, Arc::clone(&spec))?;
+    }
+    for (group, view) in &open_api_config.groups {
+        if let Some(spec) = get_cached_openapi_group_spec(group) {
+            ui_router = add_view(ui_router, view, spec)?;
+        }
+    }
+    if open_api_config.page_title.is_some() || open_api_config.favicon_url.is_some() {
+        ui_router = add_page_branding(
+            ui_router,
+            open_api_config.page_title.as_deref(),
+            open_api_config.favicon_url.as_deref(),
+        );
+    }
+
+    if open_api_config.well_known {
+        ui_router = add_spec_endpoints(
+            ui_router,
+            Arc::clone(&spec),
+            &Some(WELL_KNOWN_SPEC_URL.to_string()),
+            &None,
+        );
+    }
+
+    if open_api_config.strip_descriptions {
+        let lean_spec = set_openapi_lean_spec(strip_descriptions(spec.spec())?)?;
+        ui_router = add_spec_endpoints(
+            ui_router,
+            lean_spec,
+            &Some(open_api_config.lean_spec_url()),
+            &None,
+        );
+    }
+
+    if let Some(spec_url) = &open_api_config.spec_url {
+        ui_router = add_negotiated_spec_endpoint(ui_router, Arc::clone(&spec), spec_url);
+    }
+
+    if let Some(version_url) = &open_api_config.version_url {
+        ui_router = add_version_endpoint(ui_router, Arc::clone(&spec), version_url);
+    }
+
+    #[cfg(feature = "json-schema")]
+    if let Some(json_schema_url) = &open_api_config.json_schema_url {
+        ui_router =
+            json_schema::add_json_schema_endpoints(ui_router, Arc::clone(&spec), json_schema_url);
+    }
+
+    #[cfg(feature = "markdown")]
+    if let Some(markdown_url) = &open_api_config.markdown_url {
+        ui_router = add_markdown_endpoint(ui_router, Arc::clone(&spec), markdown_url);
+    }
+
+    #[cfg(feature = "postman")]
+    if let Some(postman_url) = &open_api_config.postman_url {
+        ui_router = postman::add_postman_endpoint(ui_router, Arc::clone(&spec), postman_url);
+    }
+
+    #[cfg(feature = "metrics")]
+    {
+        ui_router = add_metrics(ui_router);
+    }
+
+    ui_router = add_request_id(ui_router);
+
+    if let Some(rate_limit) = &open_api_config.rate_limit {
+        ui_router = RateLimiter::per_minute(rate_limit.requests_per_minute).layer(ui_router);
+    }
+    ui_router = add_allowed_hosts(ui_router, &open_api_config.allowed_hosts);
+
+    Ok(ui_router)
+}
+
+/// Parse a JSON or YAML `OpenAPI` spec file, by extension
+fn read_spec_file(path: &Path) -> Result<OpenApi> {
+    let display = path.display();
+    let content = std::fs::read_to_string(path).map_err(|err| {
+        Error::string(&format!(
+            "can't read the OpenAPI spec file `{display}`: {err}"
+        ))
+    })?;
+    let invalid = |err: &dyn std::fmt::Display| {
+        Error::string(&format!("invalid OpenAPI spec file `{display}`: {err}"))
+    };
+    let extension = path.extension().and_then(|extension| extension.to_str());
+    let value: serde_json::Value = match extension {
+        Some("json") => serde_json::from_str(&content).map_err(|err| invalid(&err))?,
+        Some("yaml" | "yml") => serde_yaml::from_str(&content).map_err(|err| invalid(&err))?,
+        _ => {
+            return Err(Error::string(&format!(
+                "OpenAPI spec file `{display}` must have a .json, .yaml or .yml extension"
+            )));
+        }
+    };
+    members::from_value(&value).map_err(|err| invalid(&err))
+}
+
+/// Log how many operations and tags were collected into the spec
+fn log_summary(spec: &OpenApi) {
+    let operations = operations(&spec.paths);
+    let tags = operations
+        .iter()
+        .flat_map(|(_, _, operation)| operation.tags.iter().flatten())
+        .collect::<BTreeSet<_>>();
+    tracing::info!(
+        "OpenAPI: collected {} operations across {} tags",
+        operations.len(),
+        tags.len()
+    );
+}
+
+/// The `(method, path)` app routes that aren't documented in any of the
+/// `specs`, formatted as `METHOD /path`
+///
+/// `HEAD` is skipped, axum answers it for every `GET` route
+fn undocumented_routes<'a>(
+    routes: &[(String, String)],
+    specs: impl IntoIterator<Item = &'a OpenApi>,
+    excluded_paths: &[String],
+) -> Vec<String> {
+    let normalize = |path: &str| path.trim_end_matches('/').to_string();
+    let documented = specs
+        .into_iter()
+        .flat_map(|spec| operations(&spec.paths))
+        .map(|(method, path, _)| (method.to_string(), normalize(path)))
+        .collect::<BTreeSet<_>>();
+    routes
+        .iter()
+        .filter(|(method, path)| {
+            method != "HEAD"
+                && !is_excluded(path, excluded_paths)
+                && !documented.contains(&(method.to_uppercase(), normalize(path)))
+        })
+        .map(|(method, path)| format!("{method} {path}"))
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+/// Remove the paths under the `prefixes`, see
+/// [`OpenAPIConfig::exclude_paths`]
+fn exclude_paths(spec: &mut OpenApi, prefixes: &[String]) {
+    spec.paths
+        .paths
+        .retain(|path, _| !is_excluded(path, prefixes));
+}
+
+/// Whether `path` is one of the `prefixes` or below one of them
+fn is_excluded(path: &str, prefixes: &[String]) -> bool {
+    prefixes.iter().any(|prefix| {
+        let prefix = prefix.trim_end_matches('/');
+        path.strip_prefix(prefix)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+    })
+}
 
-        // Serve the `OpenAPI` spec using the enabled `OpenAPI` visualizers
+const EMPTY_SPEC_DESCRIPTION: &str =
+    "No documented endpoints found — wrap handlers with openapi().";
+
+/// Point the spec at the app when it documents no servers, so "Try it out"
+/// doesn't have to guess: at `public_url` behind a reverse proxy, otherwise at
+/// the host and port of Loco's `server` config
+///
+/// A loopback or unspecified host isn't reachable from the browser of a
+/// deployed app, the spec is left without servers then, so "Try it out" uses
+/// the origin of the docs page
+fn add_default_server(
+    spec: &mut OpenApi,
+    server: &loco_rs::config::Server,
+    public_url: Option<&str>,
+) {
+    if spec
+        .servers
+        .as_ref()
+        .is_some_and(|servers| !servers.is_empty())
+    {
+        return;
+    }
+    let url = match public_url {
+        Some(public_url) => public_url.to_string(),
+        None if is_local_host(&server.host) => return,
+        None => server.full_url(),
+    };
+    spec.servers = Some(vec![Server::new(url)]);
+}
+
+/// Whether the host of Loco's `server` config, e.g. `http://localhost`, is a
+/// loopback or unspecified address
+fn is_local_host(host: &str) -> bool {
+    let host = host.split_once("://").map_or(host, |(_, host)| host);
+    let host = host.split('/').next().unwrap_or_default();
+    let host = match host.strip_prefix('[') {
+        Some(ipv6) => ipv6.split(']').next().unwrap_or_default(),
+        None => host.split(':').next().unwrap_or_default(),
+    };
+    host.eq_ignore_ascii_case("localhost")
+        || host
+            .parse::<std::net::IpAddr>()
+            .is_ok_and(|ip| ip.is_loopback() || ip.is_unspecified())
+}
+
+/// Point out in the description of a spec without paths why the visualizers
+/// are blank, usually the handlers weren't wrapped with `openapi()`
+fn describe_empty_spec(spec: &mut OpenApi) {
+    if !spec.paths.paths.is_empty() {
+        return;
+    }
+    spec.info.description = Some(match spec.info.description.take() {
+        Some(description) => format!("{EMPTY_SPEC_DESCRIPTION}\n\n{description}"),
+        None => EMPTY_SPEC_DESCRIPTION.to_string(),
+    });
+}
+
+/// Whether an invalid spec fails the boot
+fn is_strict() -> bool {
+    get_openapi_config().is_some_and(|config| config.strict)
+}
+
+/// Mount a single configured `OpenAPI` visualizer and its spec endpoints
+#[cfg(any(
+    feature = "redoc",
+    feature = "scalar",
+    feature = "swagger-cdn",
+    feature = "elements"
+))]
+fn add_view(
+    ui_router: AxumRouter,
+    view: &OpenAPIType,
+    spec: Arc<CachedSpec>,
+) -> Result<AxumRouter> {
+    if let (url, spec_json_url, spec_yaml_url, Some(custom_html_path)) = view.fields() {
+        return add_custom_html_view(
+            ui_router,
+            url,
+            custom_html_path,
+            spec,
+            spec_json_url,
+            spec_yaml_url,
+        );
+    }
+
+    let ui_router = match view {
         #[cfg(feature = "redoc")]
-        if let Some(config::OpenAPIType::Redoc {
+        OpenAPIType::Redoc {
             url,
             spec_json_url,
             spec_yaml_url,
-        }) = get_openapi_config().and_then(|c| c.redoc.as_ref())
-        {
-            ui_router = ui_router.merge(Redoc::with_url(url, get_openapi_spec().clone()));
-            ui_router = add_openapi_endpoints(ui_router, spec_json_url, spec_yaml_url);
+            ..
+        } => {
+            let ui_router = route_spec_page(ui_router, url, Arc::clone(&spec), |spec| {
+                Redoc::new(spec.spec().clone()).to_html()
+            });
+            let ui_router = add_trailing_slash_redirect(ui_router, url);
+            add_spec_endpoints(ui_router, spec, spec_json_url, spec_yaml_url)
         }
-
         #[cfg(feature = "scalar")]
-        if let Some(config::OpenAPIType::Scalar {
+        OpenAPIType::Scalar {
             url,
             spec_json_url,
             spec_yaml_url,
-        }) = get_openapi_config().and_then(|c| c.scalar.as_ref())
-        {
-            ui_router = ui_router.merge(Scalar::with_url(url, get_openapi_spec().clone()));
-            ui_router = add_openapi_endpoints(ui_router, spec_json_url, spec_yaml_url);
+            ..
+        } => {
+            let ui_router = route_spec_page(ui_router, url, Arc::clone(&spec), |spec| {
+                Scalar::new(spec.spec().clone()).to_html()
+            });
+            let ui_router = add_trailing_slash_redirect(ui_router, url);
+            add_spec_endpoints(ui_router, spec, spec_json_url, spec_yaml_url)
         }
-
-        #[cfg(feature = "swagger")]
-        if let Some(config::OpenAPIType::Swagger {
+        #[cfg(feature = "swagger-cdn")]
+        OpenAPIType::Swagger {
+            url,
+            spec_json_url,
+            spec_yaml_url,
+            assets,
+            cdn_url,
+            ui_config,
+            ..
+        } => {
+            let spec_json_url = spec_json_url
+                .clone()
+                .unwrap_or_else(|| default_spec_json_url(url));
+            let ui_router = match assets {
+                // SwaggerUi only points at the cached JSON spec instead of
+                // serving it, and already redirects `url` to `url/`
+                #[cfg(feature = "swagger")]
+                SwaggerAssets::Embedded => ui_router.merge(
+                    SwaggerUi::new(url.clone())
+                        .config(swagger_ui_config(&spec_json_url, ui_config.as_ref())),
+                ),
+                #[cfg(not(feature = "swagger"))]
+                SwaggerAssets::Embedded => {
+                    return Err(Error::string(&format!(
+                        "Swagger UI `{url}` needs the `swagger` feature for `assets: embedded`, \
+                         use `assets: cdn` with only the `swagger-cdn` feature"
+                    )));
+                }
+                SwaggerAssets::Cdn => {
+                    let cdn_url = cdn_url.as_deref().unwrap_or(swagger_cdn::DEFAULT_CDN_URL);
+                    let mut swagger = SwaggerCdn::with_url(url, &spec_json_url, cdn_url);
+                    if let Some(ui_config) = ui_config {
+                        swagger = swagger.ui_config(ui_config);
+                    }
+                    let ui_router = ui_router.merge(swagger);
+                    add_trailing_slash_redirect(ui_router, url)
+                }
+            };
+            add_spec_endpoints(ui_router, spec, &Some(spec_json_url), spec_yaml_url)
+        }
+        #[cfg(feature = "elements")]
+        OpenAPIType::Elements {
             url,
             spec_json_url,
             spec_yaml_url,
-        }) = get_openapi_config().and_then(|c| c.swagger.as_ref())
+            ..
+        } => {
+            // The web component needs a JSON spec to load, so always serve one
+            let spec_json_url = spec_json_url
+                .clone()
+                .unwrap_or_else(|| default_spec_json_url(url));
+            let ui_router = ui_router.merge(Elements::with_url(url, &spec_json_url));
+            let ui_router = add_trailing_slash_redirect(ui_router, url);
+            add_spec_endpoints(ui_router, spec, &Some(spec_json_url), spec_yaml_url)
+        }
+    };
+    Ok(ui_router)
+}
+
+/// Mount a visualizer served from a custom HTML template, with `$specUrl`
+/// replaced with the JSON spec URL and `$spec` with the JSON spec
+#[cfg(any(
+    feature = "redoc",
+    feature = "scalar",
+    feature = "swagger-cdn",
+    feature = "elements"
+))]
+fn add_custom_html_view(
+    ui_router: AxumRouter,
+    url: &str,
+    template_path: &str,
+    spec: Arc<CachedSpec>,
+    spec_json_url: &Option<String>,
+    spec_yaml_url: &Option<String>,
+) -> Result<AxumRouter> {
+    let template = std::fs::read_to_string(template_path).map_err(|err| {
+        Error::string(&format!(
+            "can't read the OpenAPI HTML template `{template_path}`: {err}"
+        ))
+    })?;
+    let spec_json_url = spec_json_url
+        .clone()
+        .unwrap_or_else(|| default_spec_json_url(url));
+    let template = template.replace("$specUrl", &spec_json_url);
+
+    let ui_router = route_spec_page(ui_router, url, Arc::clone(&spec), move |spec| {
+        template.replace(
+            "$spec",
+            &script_safe_json(&String::from_utf8_lossy(spec.json())),
+        )
+    });
+    let ui_router = add_trailing_slash_redirect(ui_router, url);
+    Ok(add_spec_endpoints(
+        ui_router,
+        spec,
+        &Some(spec_json_url),
+        spec_yaml_url,
+    ))
+}
+
+/// The spec JSON to embed in a `<script>` block of a template, with the `</`
+/// and `<!--` that would end or break the block escaped. Both only occur in
+/// strings of the JSON, where the escapes are valid
+#[cfg(any(
+    feature = "redoc",
+    feature = "scalar",
+    feature = "swagger-cdn",
+    feature = "elements"
+))]
+fn script_safe_json(json: &str) -> String {
+    json.replace("</", "<\\/").replace("<!--", "\\u003c!--")
+}
+
+/// Serve an HTML page embedding the spec, rendered again after the spec is
+/// replaced
+#[cfg(any(
+    feature = "redoc",
+    feature = "scalar",
+    feature = "swagger-cdn",
+    feature = "elements"
+))]
+fn route_spec_page<F>(
+    ui_router: AxumRouter,
+    url: &str,
+    spec: Arc<CachedSpec>,
+    render: F,
+) -> AxumRouter
+where
+    F: Fn(&CachedSpec) -> String + Send + Sync + 'static,
+{
+    let page = SpecRendering::new(spec, render);
+    ui_router.route(
+        url,
+        get(move || {
+            let page = page.get();
+            async move { Html(page) }
+        }),
+    )
+}
+
+/// Swagger UI configuration pointing at the JSON spec, with the `ui_config`
+/// options `utoipa_swagger_ui::Config` supports, other options are ignored
+/// with a warning
+#[cfg(feature = "swagger")]
+fn swagger_ui_config(
+    spec_json_url: &str,
+    ui_config: Option<&serde_json::Value>,
+) -> SwaggerConfig<'static> {
+    use serde_json::Value;
+
+    let mut config = SwaggerConfig::new([spec_json_url.to_string()]);
+    let Some(ui_config) = ui_config.and_then(Value::as_object) else {
+        return config;
+    };
+    for (key, value) in ui_config {
+        let depth = value.as_i64().and_then(|depth| isize::try_from(depth).ok());
+        config = match (key.as_str(), value, depth) {
+            ("deepLinking", Value::Bool(value), _) => config.deep_linking(*value),
+            ("displayOperationId", Value::Bool(value), _) => config.display_operation_id(*value),
+            ("displayRequestDuration", Value::Bool(value), _) => {
+                config.display_request_duration(*value)
+            }
+            ("docExpansion", Value::String(value), _) => config.doc_expansion(value),
+            ("defaultModelsExpandDepth", _, Some(depth)) => {
+                config.default_models_expand_depth(depth)
+            }
+            ("defaultModelExpandDepth", _, Some(depth)) => config.default_model_expand_depth(depth),
+            ("defaultModelRendering", Value::String(value), _) => {
+                config.default_model_rendering(value)
+            }
+            ("filter", Value::Bool(value), _) => config.filter(*value),
+            ("persistAuthorization", Value::Bool(value), _) => config.persist_authorization(*value),
+            ("requestSnippetsEnabled", Value::Bool(value), _) => {
+                config.request_snippets_enabled(*value)
+            }
+            ("showExtensions", Value::Bool(value), _) => config.show_extensions(*value),
+            ("showCommonExtensions", Value::Bool(value), _) => {
+                config.show_common_extensions(*value)
+            }
+            ("tryItOutEnabled", Value::Bool(value), _) => config.try_it_out_enabled(*value),
+            ("withCredentials", Value::Bool(value), _) => config.with_credentials(*value),
+            _ => {
+                tracing::warn!(
+                    option = %key,
+                    "Swagger UI option is not supported with embedded assets, ignoring it"
+                );
+                config
+            }
+        };
+    }
+    config
+}
+
+/// Redirect the configured `url` with the trailing slash toggled to `url`
+/// itself, so the visualizer works both with and without it
+#[cfg(any(
+    feature = "redoc",
+    feature = "scalar",
+    feature = "swagger-cdn",
+    feature = "elements"
+))]
+fn add_trailing_slash_redirect(ui_router: AxumRouter, url: &str) -> AxumRouter {
+    let Some(alternate_url) = alternate_trailing_slash(url) else {
+        return ui_router;
+    };
+
+    let url = url.to_string();
+    ui_router.route(
+        &alternate_url,
+        get(move || async move { Redirect::temporary(&url) }),
+    )
+}
+
+/// JSON spec URL used by visualizers that need one when none is configured
+#[cfg(any(
+    feature = "redoc",
+    feature = "scalar",
+    feature = "swagger-cdn",
+    feature = "elements"
+))]
+fn default_spec_json_url(url: &str) -> String {
+    format!("{}/openapi.json", url.trim_end_matches('/'))
+}
+
+/// No visualizer features are enabled, so there is nothing to mount
+#[cfg(not(any(
+    feature = "redoc",
+    feature = "scalar",
+    feature = "swagger-cdn",
+    feature = "elements"
+)))]
+fn add_view(_: AxumRouter, view: &OpenAPIType, _: Arc<CachedSpec>) -> Result<AxumRouter> {
+    match *view {}
+}
+
+#[cfg(all(test, feature = "redoc"))]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode},
+    };
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::config::{replace_openapi_config, tests::EnvVarGuard};
+    use crate::openapi::clear_routes;
+
+    // The config is set once per process, replace it with the config of `ctx`
+    // for the next boot
+    fn replace_config(ctx: &AppContext) {
+        replace_openapi_config(openapi_config(ctx).unwrap()).unwrap();
+    }
+
+    fn leaked_config(openapi: serde_json::Value) -> &'static OpenAPIConfig {
+        let initializers = Some(BTreeMap::from([("openapi".to_string(), openapi)]));
+        let config: Option<OpenAPIConfig> = InitializerConfig::from(&initializers).into();
+        Box::leak(Box::new(config.expect("config should deserialize")))
+    }
+
+    fn empty_spec() -> Arc<CachedSpec> {
+        Arc::new(CachedSpec::new(OpenApi::default()).unwrap())
+    }
+
+    async fn status(router: &AxumRouter, uri: &str) -> StatusCode {
+        router
+            .clone()
+            .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+            .status()
+    }
+
+    #[cfg(feature = "swagger")]
+    #[tokio::test]
+    async fn test_swagger_ui_config() {
+        let ui_config = serde_json::json!({"docExpansion": "none", "tryItOutEnabled": true});
+        let router: AxumRouter = AxumRouter::new().merge(
+            SwaggerUi::new("/swagger")
+                .config(swagger_ui_config("/swagger/openapi.json", Some(&ui_config))),
+        );
+
+        let res = router
+            .oneshot(
+                Request::get("/swagger/swagger-initializer.js")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(res.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let js = String::from_utf8(body.to_vec()).unwrap();
+        assert!(js.contains(r#""docExpansion": "none""#));
+        assert!(js.contains(r#""tryItOutEnabled": true"#));
+        assert!(js.contains("/swagger/openapi.json"));
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn test_reset_on_boot() {
+        clear_routes();
+        let mut ctx = loco_rs::tests_cfg::app::get_app_context().await;
+        ctx.config.initializers = Some(BTreeMap::from([(
+            "openapi".to_string(),
+            serde_json::json!({ "reset_on_boot": true }),
+        )]));
+
+        #[utoipa::path(get, path = "/api/album", responses((status = 200, description = "Album")))]
+        async fn get_album() {}
+        #[utoipa::path(get, path = "/api/artist", responses((status = 200, description = "Artist")))]
+        async fn get_artist() {}
+        let initializer = OpenapiInitializerWithSetup::new(|_ctx| OpenApi::default(), None);
+
+        replace_config(&ctx);
+        crate::openapi::add_route(OpenApiRouter::new().routes(utoipa_axum::routes!(get_album)));
+        crate::responses::set_default_error_response("5XX", "ErrorResponse");
+        let _ = initializer
+            .after_routes(AxumRouter::new(), &ctx)
+            .await
+            .unwrap();
+        assert!(get_collected_routes().is_empty());
+
+        crate::openapi::add_route(OpenApiRouter::new().routes(utoipa_axum::routes!(get_artist)));
+        assert_eq!(get_collected_routes().len(), 1);
+        let _ = initializer
+            .after_routes(AxumRouter::new(), &ctx)
+            .await
+            .unwrap();
+
+        let spec = serde_json::to_value(&*crate::utils::get_openapi_spec()).unwrap();
+        assert_eq!(
+            spec["paths"]
+                .as_object()
+                .unwrap()
+                .keys()
+                .collect::<Vec<_>>(),
+            ["/api/artist"]
+        );
+        // The other registries outlive the boot
+        assert!(spec["paths"]["/api/artist"]["get"]["responses"]
+            .get("5XX")
+            .is_some());
+        assert!(get_collected_routes().is_empty());
+        replace_openapi_config(None).unwrap();
+        clear_routes();
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn test_enabled_fn() {
+        clear_routes();
+        let mut ctx = loco_rs::tests_cfg::app::get_app_context().await;
+        ctx.config.initializers = Some(BTreeMap::from([(
+            "openapi".to_string(),
+            serde_json::json!({ "redoc": { "url": "/redoc" } }),
+        )]));
+
+        replace_config(&ctx);
+        for enabled in [false, true] {
+            let router = OpenapiInitializerWithSetup::new(|_ctx| OpenApi::default(), None)
+                .with_enabled_fn(move |_ctx| enabled)
+                .after_routes(AxumRouter::new(), &ctx)
+                .await
+                .unwrap();
+            let expected = if enabled {
+                StatusCode::OK
+            } else {
+                StatusCode::NOT_FOUND
+            };
+            assert_eq!(status(&router, "/redoc").await, expected);
+        }
+
+        replace_openapi_config(None).unwrap();
+        clear_routes();
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn test_build_ui_router_nested() {
+        clear_routes();
+        let mut ctx = loco_rs::tests_cfg::app::get_app_context().await;
+        ctx.config.initializers = Some(BTreeMap::from([(
+            "openapi".to_string(),
+            serde_json::json!({ "redoc": { "url": "/redoc", "spec_json_url": "/redoc/openapi.json" } }),
+        )]));
+
+        replace_config(&ctx);
+        let router = OpenapiInitializerWithSetup::new(|_ctx| OpenApi::default(), None)
+            .detached()
+            .after_routes(AxumRouter::new(), &ctx)
+            .await
+            .unwrap();
+        assert_eq!(status(&router, "/redoc").await, StatusCode::NOT_FOUND);
+
+        let app = AxumRouter::new().nest("/internal", build_ui_router().unwrap());
+        assert_eq!(status(&app, "/internal/redoc").await, StatusCode::OK);
+        assert_eq!(
+            status(&app, "/internal/redoc/openapi.json").await,
+            StatusCode::OK
+        );
+        assert_eq!(status(&app, "/redoc").await, StatusCode::NOT_FOUND);
+
+        replace_openapi_config(None).unwrap();
+        clear_routes();
+    }
+
+    #[tokio::test]
+    async fn test_add_default_server() {
+        let mut ctx = loco_rs::tests_cfg::app::get_app_context().await;
+        ctx.config.server.host = "http://docs.internal".to_string();
+        ctx.config.server.port = 7070;
+
+        let mut spec = OpenApi::default();
+        add_default_server(&mut spec, &ctx.config.server, None);
+        assert_eq!(spec.servers.unwrap()[0].url, "http://docs.internal:7070");
+
+        for host in [
+            "http://localhost",
+            "http://127.0.0.1",
+            "http://0.0.0.0",
+            "http://[::1]",
+        ] {
+            ctx.config.server.host = host.to_string();
+            let mut spec = OpenApi::default();
+            add_default_server(&mut spec, &ctx.config.server, None);
+            assert!(spec.servers.is_none(), "{host}");
+        }
+
+        let mut spec = OpenApi::default();
+        add_default_server(
+            &mut spec,
+            &ctx.config.server,
+            Some("https://api.example.com"),
+        );
+        assert_eq!(spec.servers.unwrap()[0].url, "https://api.example.com");
+
+        let mut spec = OpenApi::default();
+        spec.servers = Some(vec![Server::new("/api/v2")]);
+        add_default_server(&mut spec, &ctx.config.server, None);
+        assert_eq!(spec.servers.unwrap()[0].url, "/api/v2");
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn test_default_server_on_boot() {
+        clear_routes();
+        let mut ctx = loco_rs::tests_cfg::app::get_app_context().await;
+        ctx.config.server.host = "http://docs.internal".to_string();
+        ctx.config.server.port = 7070;
+        let initializer = OpenapiInitializerWithSetup::new(|_ctx| OpenApi::default(), None);
+        let boot = |openapi: Option<serde_json::Value>| {
+            let mut ctx = ctx.clone();
+            ctx.config.initializers =
+                openapi.map(|openapi| BTreeMap::from([("openapi".to_string(), openapi)]));
+            let initializer = &initializer;
+            async move {
+                replace_config(&ctx);
+                let _ = initializer
+                    .after_routes(AxumRouter::new(), &ctx)
+                    .await
+                    .unwrap();
+                let server_url =
+                    |spec: &OpenApi| spec.servers.as_ref().map(|servers| servers[0].url.clone());
+                (
+                    server_url(&crate::utils::get_openapi_spec()),
+                    crate::utils::get_openapi_group_spec("admin")
+                        .and_then(|spec| server_url(&spec)),
+                )
+            }
+        };
+
+        let groups = serde_json::json!({ "admin": { "redoc": { "url": "/docs/admin" } } });
+        let expected = Some("http://docs.internal:7070".to_string());
+        assert_eq!(
+            boot(Some(serde_json::json!({ "groups": groups }))).await,
+            (expected.clone(), expected)
+        );
+
+        let expected = Some("https://api.example.com".to_string());
+        assert_eq!(
+            boot(Some(serde_json::json!({
+                "groups": groups,
+                "public_url": "https://api.example.com",
+            })))
+            .await,
+            (expected.clone(), expected)
+        );
+
+        assert_eq!(boot(None).await.0, None);
+
+        replace_openapi_config(None).unwrap();
+        clear_routes();
+    }
+
+    #[test]
+    fn test_from_routers() {
+        let initializer =
+            OpenapiInitializerWithSetup::from_routers([OpenApiRouter::new(), OpenApiRouter::new()])
+                .with_initial_spec(|_ctx| OpenApi::default());
+
+        assert_eq!(initializer.routes_setup.map(|routes| routes.len()), Some(2));
+        assert!(initializer.initial_spec.is_some());
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn test_from_file() {
+        crate::openapi::clear_routes();
+        let ctx = loco_rs::tests_cfg::app::get_app_context().await;
+        let path =
+            std::env::temp_dir().join(format!("loco-openapi-base-{}.yaml", std::process::id()));
+        std::fs::write(
+            &path,
+            "openapi: 3.1.0\n\
+             info:\n  title: Albums API\n  version: 2.0.0\n\
+             servers:\n  - url: https://api.example.com\n\
+             paths: {}\n\
+             webhooks: {album.created: {post: {responses: {'200': {description: Ok}}}}}\n",
+        )
+        .unwrap();
+
+        #[utoipa::path(get, path = "/api/album", responses((status = 200, description = "Album")))]
+        async fn get_album() {}
+        let initializer = OpenapiInitializerWithSetup {
+            routes_setup: Some(vec![
+                OpenApiRouter::new().routes(utoipa_axum::routes!(get_album))
+            ]),
+            ..OpenapiInitializerWithSetup::from_file(&path)
+        };
+        let initial_spec = initializer.initial_spec(&ctx).await.unwrap();
+        let spec = initializer
+            .collect_spec(initial_spec.as_ref(), None)
+            .unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(spec.info.title, "Albums API");
+        assert_eq!(
+            spec.servers.as_ref().unwrap()[0].url,
+            "https://api.example.com".to_string()
+        );
+        assert!(spec.paths.paths.contains_key("/api/album"));
+        // Members utoipa has no field for are kept
+        let json = serde_json::to_value(&spec).unwrap();
+        assert!(json["webhooks"]["album.created"]["post"].is_object());
+
+        let Err(err) = OpenapiInitializerWithSetup::from_file("missing/openapi.base.json")
+            .initial_spec(&ctx)
+            .await
+        else {
+            panic!("a missing base file should fail");
+        };
+        assert!(err.to_string().contains("missing/openapi.base.json"));
+    }
+
+    #[tokio::test]
+    async fn test_router_hook() {
+        let initializer = OpenapiInitializerWithSetup::new(|_ctx| OpenApi::default(), None)
+            .with_router_hook(|router| {
+                router.layer(axum::middleware::map_response(
+                    |mut response: axum::response::Response| async {
+                        response
+                            .headers_mut()
+                            .insert("x-docs", "true".parse().unwrap());
+                        response
+                    },
+                ))
+            });
+        // The hook applies on every boot
+        for _ in 0..2 {
+            let ui_router = add_view(
+                AxumRouter::new(),
+                leaked_config(serde_json::json!({ "redoc": { "url": "/redoc" } })).views()[0],
+                empty_spec(),
+            )
+            .unwrap();
+
+            let ui_router = initializer.apply_router_hook(ui_router);
+
+            let res = ui_router
+                .oneshot(Request::get("/redoc").body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            assert_eq!(res.headers()["x-docs"], "true");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_async_initial_spec() {
+        let ctx = loco_rs::tests_cfg::app::get_app_context().await;
+        let initializer = OpenapiInitializerWithSetup::builder()
+            .async_initial_spec(|_ctx| async {
+                tokio::task::yield_now().await;
+                let mut spec = OpenApi::default();
+                spec.info.title = "Loaded at boot".to_string();
+                Ok(spec)
+            })
+            .build();
+
+        let initial_spec = initializer.initial_spec(&ctx).await.unwrap();
+        let spec = initializer
+            .collect_spec(initial_spec.as_ref(), None)
+            .unwrap();
+
+        assert_eq!(spec.info.title, "Loaded at boot");
+    }
+
+    #[test]
+    fn test_undocumented_routes() {
+        let (_, spec) = OpenApiRouter::<()>::new()
+            .routes(utoipa_axum::routes!(get_album))
+            .split_for_parts();
+        let routes = [
+            ("GET", "/api/album/"),
+            ("HEAD", "/api/album"),
+            ("POST", "/api/album"),
+            ("GET", "/api/artist"),
+            ("GET", "/_health"),
+        ]
+        .map(|(method, path)| (method.to_string(), path.to_string()));
+
+        assert_eq!(
+            undocumented_routes(&routes, [&spec], &["/_health".to_string()]),
+            vec!["GET /api/artist".to_string(), "POST /api/album".to_string()]
+        );
+    }
+
+    #[utoipa::path(get, path = "/api/album", responses((status = 200, description = "Album")))]
+    async fn get_album() {}
+
+    #[test]
+    #[serial_test::serial]
+    fn test_hidden_routes_are_not_undocumented() {
+        clear_routes();
+        let _ = crate::openapi::openapi_hidden(
+            axum::routing::get(get_album),
+            utoipa_axum::routes!(get_album),
+        );
+        let routes = [("GET".to_string(), "/api/album".to_string())];
+
+        assert!(undocumented_routes(&routes, [&get_hidden_spec()], &[]).is_empty());
+
+        clear_routes();
+    }
+
+    #[test]
+    fn test_exclude_paths() {
+        let mut spec = OpenApi::default();
+        for path in ["/_health", "/_healthz", "/api/internal/jobs", "/api/album"] {
+            spec.paths
+                .paths
+                .insert(path.to_string(), utoipa::openapi::PathItem::default());
+        }
+
+        exclude_paths(
+            &mut spec,
+            &["/_health".to_string(), "/api/internal/".to_string()],
+        );
+
+        assert_eq!(
+            spec.paths.paths.keys().collect::<Vec<_>>(),
+            ["/_healthz", "/api/album"]
+        );
+    }
+
+    #[test]
+    fn test_describe_empty_spec() {
+        let mut spec = OpenApi::default();
+        describe_empty_spec(&mut spec);
+        assert_eq!(
+            spec.info.description.as_deref(),
+            Some(EMPTY_SPEC_DESCRIPTION)
+        );
+
+        let mut spec = OpenApi::default();
+        spec.paths
+            .paths
+            .insert("/album".to_string(), utoipa::openapi::PathItem::default());
+        spec.info.description = Some("Albums".to_string());
+        describe_empty_spec(&mut spec);
+        assert_eq!(spec.info.description.as_deref(), Some("Albums"));
+    }
+
+    // Stands in for a handler behind a disabled cargo feature
+    #[cfg(any())]
+    #[utoipa::path(get, path = "/api/beta", responses((status = 200, description = "Beta")))]
+    async fn beta() {}
+
+    fn feature_gated_routes() -> OpenApiRouter<AppContext> {
+        #[allow(unused_mut)]
+        let mut routes = OpenApiRouter::new();
+        #[cfg(any())]
         {
-            ui_router = ui_router
-                .merge(SwaggerUi::new(url).url(spec_json_url.clone(), get_openapi_spec().clone()));
-            ui_router = add_openapi_endpoints(ui_router, &None, spec_yaml_url);
+            routes = routes.routes(utoipa_axum::routes!(beta));
         }
+        routes
+    }
 
-        // Merge the UI router with the main router
-        Ok(router.merge(ui_router))
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn test_disabled_feature_routes_not_documented() {
+        crate::openapi::clear_routes();
+        let ctx = loco_rs::tests_cfg::app::get_app_context().await;
+        let initializer = OpenapiInitializerWithSetup::from_routers([feature_gated_routes()]);
+        let (app_router, _) = feature_gated_routes().split_for_parts();
+
+        let mut spec = initializer.collect_spec(None, None).unwrap();
+        describe_empty_spec(&mut spec);
+        let spec = Arc::new(CachedSpec::new(spec).unwrap());
+        let config = leaked_config(serde_json::json!({
+            "redoc": { "url": "/redoc", "spec_json_url": "/openapi.json" }
+        }));
+        let ui_router = add_view(AxumRouter::new(), config.views()[0], spec).unwrap();
+
+        assert_eq!(
+            status(&app_router.with_state(ctx), "/api/beta").await,
+            StatusCode::NOT_FOUND
+        );
+        assert_eq!(status(&ui_router, "/redoc").await, StatusCode::OK);
+        let res = ui_router
+            .oneshot(Request::get("/openapi.json").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(res.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["paths"], serde_json::json!({}));
+        assert!(json["info"]["description"]
+            .as_str()
+            .is_some_and(|description| description.starts_with(EMPTY_SPEC_DESCRIPTION)));
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn test_view_serves_replaced_spec() {
+        let titled = |title: &str| {
+            let mut spec = OpenApi::default();
+            spec.info.title = title.to_string();
+            spec
+        };
+        let spec = set_openapi_spec(titled("First boot")).unwrap();
+        let config = leaked_config(serde_json::json!({
+            "redoc": { "url": "/redoc", "spec_json_url": "/openapi.json" }
+        }));
+        let ui_router = add_view(AxumRouter::new(), config.views()[0], spec).unwrap();
+
+        set_openapi_spec(titled("Second boot")).unwrap();
+
+        for uri in ["/redoc", "/openapi.json"] {
+            let res = ui_router
+                .clone()
+                .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            let body = axum::body::to_bytes(res.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            let body = String::from_utf8_lossy(&body);
+            assert!(body.contains("Second boot"), "{uri}");
+            assert!(!body.contains("First boot"), "{uri}");
+        }
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn test_env_vars_expanded_in_view_routes() {
+        let _env = EnvVarGuard::set("LOCO_OPENAPI_TEST_DOCS_ENV", "staging");
+        let openapi = |strict_env: bool| {
+            serde_json::json!({
+                "redoc": {
+                    "url": "/docs-${LOCO_OPENAPI_TEST_DOCS_ENV}/redoc",
+                    "spec_yaml_url": "/docs-${LOCO_OPENAPI_TEST_DOCS_UNSET}/openapi.yaml",
+                },
+                "strict_env": strict_env,
+            })
+        };
+        assert!(leaked_config(openapi(true))
+            .clone()
+            .with_env_vars()
+            .is_err());
+
+        let config: &'static OpenAPIConfig = Box::leak(Box::new(
+            leaked_config(openapi(false))
+                .clone()
+                .with_env_vars()
+                .unwrap(),
+        ));
+        let router = add_view(AxumRouter::new(), config.views()[0], empty_spec()).unwrap();
+
+        assert_eq!(status(&router, "/docs-staging/redoc").await, StatusCode::OK);
+        assert_eq!(status(&router, "/docs-/openapi.yaml").await, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_base_path_prefixes_view_routes() {
+        let config = leaked_config(serde_json::json!({
+            "base_path": "/api/v2",
+            "redoc": { "url": "/redoc", "spec_json_url": "/redoc/openapi.json" },
+        }));
+
+        let mut ui_router = AxumRouter::new();
+        for view in config.views() {
+            ui_router = add_view(ui_router, view, empty_spec()).unwrap();
+        }
+
+        assert_eq!(status(&ui_router, "/api/v2/redoc").await, StatusCode::OK);
+        assert_eq!(
+            status(&ui_router, "/api/v2/redoc/openapi.json").await,
+            StatusCode::OK
+        );
+        assert_eq!(status(&ui_router, "/redoc").await, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_custom_html_template() {
+        let template_path =
+            std::env::temp_dir().join(format!("loco-openapi-template-{}.html", std::process::id()));
+        std::fs::write(
+            &template_path,
+            r#"<html><body data-spec-url="$specUrl">Corporate docs</body></html>"#,
+        )
+        .unwrap();
+        let config = leaked_config(serde_json::json!({
+            "redoc": {
+                "url": "/redoc",
+                "custom_html_path": template_path.to_str().unwrap(),
+            },
+        }));
+
+        let mut ui_router = AxumRouter::new();
+        for view in config.views() {
+            ui_router = add_view(ui_router, view, empty_spec()).unwrap();
+        }
+        std::fs::remove_file(&template_path).unwrap();
+
+        let res = ui_router
+            .clone()
+            .oneshot(Request::get("/redoc").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let body = axum::body::to_bytes(res.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(
+            body,
+            r#"<html><body data-spec-url="/redoc/openapi.json">Corporate docs</body></html>"#
+        );
+        assert_eq!(
+            status(&ui_router, "/redoc/openapi.json").await,
+            StatusCode::OK
+        );
+    }
+
+    #[tokio::test]
+    async fn test_custom_html_template_escapes_script_end() {
+        let template_path = std::env::temp_dir().join(format!(
+            "loco-openapi-script-template-{}.html",
+            std::process::id()
+        ));
+        std::fs::write(
+            &template_path,
+            "<html><script>const spec = $spec;</script></html>",
+        )
+        .unwrap();
+        let config = leaked_config(serde_json::json!({
+            "redoc": {
+                "url": "/redoc",
+                "custom_html_path": template_path.to_str().unwrap(),
+            },
+        }));
+        let description = "Ends early </script><script>alert(1)</script> <!-- comment";
+        let mut spec = OpenApi::default();
+        spec.info.description = Some(description.to_string());
+        let spec = Arc::new(CachedSpec::new(spec).unwrap());
+
+        let ui_router = add_view(AxumRouter::new(), config.views()[0], spec).unwrap();
+        std::fs::remove_file(&template_path).unwrap();
+
+        let res = ui_router
+            .oneshot(Request::get("/redoc").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let body = axum::body::to_bytes(res.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body = String::from_utf8_lossy(&body);
+        let script = body
+            .strip_prefix("<html><script>const spec = ")
+            .and_then(|body| body.strip_suffix(";</script></html>"))
+            .unwrap();
+        assert!(!script.contains("</"));
+        assert!(!script.contains("<!--"));
+        let json: serde_json::Value = serde_json::from_str(script).unwrap();
+        assert_eq!(json["info"]["description"], description);
     }
 }
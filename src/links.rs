@@ -0,0 +1,177 @@
+use std::collections::BTreeMap;
+use std::sync::{Mutex, OnceLock};
+
+use serde_json::Value;
+use utoipa::{
+    openapi::{link::Link, OpenApi, RefOr},
+    Modify,
+};
+
+use crate::members;
+use crate::openapi::for_each_operation;
+
+/// Links by `operationId`, status and link name
+type LinkRegistry = BTreeMap<String, BTreeMap<String, BTreeMap<String, Link>>>;
+
+static RESPONSE_LINKS: OnceLock<Mutex<LinkRegistry>> = OnceLock::new();
+
+fn get_response_links() -> &'static Mutex<LinkRegistry> {
+    RESPONSE_LINKS.get_or_init(|| Mutex::new(BTreeMap::new()))
+}
+
+/// Document a link from a response of an operation to another operation,
+/// e.g. from an album to the artist of the album, for HATEOAS-aware clients
+///
+/// The link is added under `links` of the `status` response of the operation
+/// with the given `operationId`, if the operation documents that status
+/// ```rust
+/// use by_loco_openapi::prelude::*;
+/// use utoipa::openapi::link::LinkBuilder;
+///
+/// add_response_link(
+///     "get_album",
+///     "200",
+///     "GetArtist",
+///     LinkBuilder::new()
+///         .operation_id("get_artist")
+///         .parameter("id", "$response.body#/artist_id")
+///         .build(),
+/// );
+/// ```
+pub fn add_response_link(operation_id: &str, status: &str, name: &str, link: Link) {
+    if let Ok(mut links) = get_response_links().lock() {
+        links
+            .entry(operation_id.to_string())
+            .or_default()
+            .entry(status.to_string())
+            .or_default()
+            .insert(name.to_string(), link);
+    }
+}
+
+/// Clears the links added with [`add_response_link`], mostly used for testing
+pub fn clear_response_links() {
+    if let Ok(mut links) = get_response_links().lock() {
+        links.clear();
+    }
+}
+
+/// Adds the registered links to the responses of their operations
+pub struct ResponseLinks;
+
+impl Modify for ResponseLinks {
+    fn modify(&self, openapi: &mut OpenApi) {
+        let Ok(links) = get_response_links().lock() else {
+            return;
+        };
+        if links.is_empty() {
+            return;
+        }
+
+        for_each_operation(&mut openapi.paths, |_, operation| {
+            let Some(operation_links) = operation
+                .operation_id
+                .as_ref()
+                .and_then(|operation_id| links.get(operation_id))
+            else {
+                return;
+            };
+            for (status, status_links) in operation_links {
+                let Some(RefOr::T(response)) = operation.responses.responses.get_mut(status) else {
+                    tracing::warn!(
+                        operation_id = operation.operation_id.as_deref(),
+                        status,
+                        "response of a registered link is not documented, skipping it"
+                    );
+                    continue;
+                };
+                let existing_links = std::mem::take(&mut response.links);
+                let Some(response_links) = members::object_mut(&mut response.extensions, "links")
+                else {
+                    continue;
+                };
+                let links = existing_links
+                    .into_iter()
+                    .map(|(name, link)| match link {
+                        RefOr::T(link) => (name, link_value(&link)),
+                        RefOr::Ref(reference) => (name, serde_json::to_value(reference)),
+                    })
+                    .chain(
+                        status_links
+                            .iter()
+                            .map(|(name, link)| (name.clone(), link_value(link))),
+                    );
+                for (name, link) in links {
+                    match link {
+                        Ok(link) => {
+                            response_links.insert(name, link);
+                        }
+                        Err(err) => tracing::warn!(name, "can't serialize the link: {err}"),
+                    }
+                }
+            }
+        });
+    }
+}
+
+/// The link as JSON, utoipa serializes its fields in snake case
+fn link_value(link: &Link) -> serde_json::Result<Value> {
+    let mut value = serde_json::to_value(link)?;
+    if let Value::Object(fields) = &mut value {
+        for (field, member) in [
+            ("operation_ref", "operationRef"),
+            ("operation_id", "operationId"),
+            ("request_body", "requestBody"),
+        ] {
+            if let Some(field) = fields.remove(field) {
+                fields.insert(member.to_string(), field);
+            }
+        }
+    }
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use utoipa::openapi::link::LinkBuilder;
+    use utoipa_axum::{router::OpenApiRouter, routes};
+
+    use super::*;
+
+    #[utoipa::path(
+        get,
+        path = "/api/album/{id}",
+        responses((status = 200, description = "Album")),
+    )]
+    async fn get_album() {}
+
+    #[test]
+    #[serial_test::serial]
+    fn test_response_link() {
+        add_response_link(
+            "get_album",
+            "200",
+            "GetArtist",
+            LinkBuilder::new()
+                .operation_id("get_artist")
+                .parameter("id", "$response.body#/artist_id")
+                .build(),
+        );
+
+        let (_, mut spec) = OpenApiRouter::<()>::new()
+            .routes(routes!(get_album))
+            .split_for_parts();
+        ResponseLinks.modify(&mut spec);
+
+        let json = serde_json::to_value(&spec).unwrap();
+        assert_eq!(
+            json["paths"]["/api/album/{id}"]["get"]["responses"]["200"]["links"]["GetArtist"],
+            serde_json::json!({
+                "operationId": "get_artist",
+                "parameters": { "id": "$response.body#/artist_id" }
+            })
+        );
+
+        clear_response_links();
+    }
+}
@@ -0,0 +1,153 @@
+use loco_rs::{Error, Result};
+use utoipa::openapi::{schema::Schema, OpenApi, RefOr};
+
+use crate::{
+    config::LintConfig,
+    openapi::{operations, COMPONENT_SCHEMA_PREFIX},
+};
+
+/// Operations of the spec that don't follow the documentation standards
+/// enabled in the `lint` config
+///
+/// With `require_descriptions` every operation needs a description, utoipa
+/// takes it from the lines of the doc comment after the summary line. With
+/// `require_examples` every JSON-like content of a 2xx response needs an
+/// example, of the content or of the component schema it references
+#[must_use]
+pub fn lint_spec(spec: &OpenApi, config: &LintConfig) -> Vec<String> {
+    let mut problems = Vec::new();
+    for (method, path, operation) in operations(&spec.paths) {
+        if config.require_descriptions
+            && operation
+                .description
+                .as_deref()
+                .is_none_or(|description| description.trim().is_empty())
+        {
+            problems.push(format!("`{method} {path}` has no description"));
+        }
+        if !config.require_examples {
+            continue;
+        }
+        for (status, response) in &operation.responses.responses {
+            let RefOr::T(response) = response else {
+                continue;
+            };
+            if !status.starts_with('2') {
+                continue;
+            }
+            for (content_type, content) in &response.content {
+                let has_example = content.example.is_some()
+                    || !content.examples.is_empty()
+                    || content
+                        .schema
+                        .as_ref()
+                        .is_some_and(|schema| schema_has_example(spec, schema));
+                if !has_example {
+                    problems.push(format!(
+                        "`{method} {path}` has no example for the `{content_type}` content of \
+                         the {status} response"
+                    ));
+                }
+            }
+        }
+    }
+    problems
+}
+
+fn schema_has_example(spec: &OpenApi, schema: &RefOr<Schema>) -> bool {
+    let schema = match schema {
+        RefOr::Ref(reference) => {
+            let Some(schema) = reference
+                .ref_location
+                .strip_prefix(COMPONENT_SCHEMA_PREFIX)
+                .and_then(|name| spec.components.as_ref()?.schemas.get(name))
+            else {
+                return false;
+            };
+            serde_json::to_value(schema)
+        }
+        RefOr::T(schema) => serde_json::to_value(schema),
+    };
+    schema.is_ok_and(|schema| schema.get("example").is_some() || schema.get("examples").is_some())
+}
+
+/// Lint the spec, logging the problems as warnings or failing when `strict`
+/// is set
+///
+/// # Errors
+///
+/// Will return `Err` in strict mode when the spec has problems
+pub fn check_lint(name: &str, spec: &OpenApi, config: &LintConfig, strict: bool) -> Result<()> {
+    let problems = lint_spec(spec, config);
+    if problems.is_empty() {
+        return Ok(());
+    }
+    if strict {
+        return Err(Error::string(&format!(
+            "OpenAPI spec `{name}` doesn't pass the lint: {}",
+            problems.join(", ")
+        )));
+    }
+    for problem in &problems {
+        tracing::warn!(spec = name, "OpenAPI lint: {problem}");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use utoipa::ToSchema;
+    use utoipa_axum::{router::OpenApiRouter, routes};
+
+    use super::*;
+
+    #[allow(dead_code)]
+    #[derive(ToSchema, serde::Serialize)]
+    struct Album {
+        title: String,
+    }
+
+    /// Get album
+    ///
+    /// Returns the album with its tracks
+    #[utoipa::path(
+        get,
+        path = "/api/album",
+        responses((status = 200, description = "Album", body = Album, example = json!({"title": "Blue Train"}))),
+    )]
+    async fn get_album() {}
+
+    /// List albums
+    #[utoipa::path(
+        get,
+        path = "/api/albums",
+        responses((status = 200, description = "Albums", body = Vec<Album>)),
+    )]
+    async fn list_albums() {}
+
+    const ALL: LintConfig = LintConfig {
+        require_examples: true,
+        require_descriptions: true,
+    };
+
+    #[test]
+    fn test_lint_spec() {
+        let (_, spec) = OpenApiRouter::<()>::new()
+            .routes(routes!(get_album))
+            .routes(routes!(list_albums))
+            .split_for_parts();
+
+        assert_eq!(
+            lint_spec(&spec, &ALL),
+            vec![
+                "`GET /api/albums` has no description".to_string(),
+                "`GET /api/albums` has no example for the `application/json` content of the 200 \
+                 response"
+                    .to_string(),
+            ]
+        );
+        assert!(check_lint("main", &spec, &ALL, true).is_err());
+        assert!(check_lint("main", &spec, &ALL, false).is_ok());
+        assert!(lint_spec(&spec, &LintConfig::default()).is_empty());
+    }
+}
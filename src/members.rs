@@ -0,0 +1,196 @@
+//! Members of the `OpenAPI` 3.1 document utoipa has no field for, e.g.
+//! `webhooks` or the `callbacks` of an operation
+//!
+//! They're kept under their own key in the extensions of the object they
+//! belong to, which are flattened into it when serialized. utoipa only
+//! deserializes the `x-` extensions, so a spec read from JSON or YAML goes
+//! through [`from_value`] to keep them
+
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use utoipa::openapi::{
+    extensions::Extensions,
+    path::{Operation, PathItem},
+    OpenApi, RefOr,
+};
+
+/// Members of the top-level object
+const SPEC_MEMBERS: [&str; 2] = ["webhooks", "jsonSchemaDialect"];
+/// Members of the `info` object
+const INFO_MEMBERS: [&str; 1] = ["summary"];
+/// Members of the `components` object
+const COMPONENTS_MEMBERS: [&str; 1] = ["parameters"];
+/// Members of an operation
+const OPERATION_MEMBERS: [&str; 1] = ["callbacks"];
+/// Members of a response, utoipa's links have snake case fields
+const RESPONSE_MEMBERS: [&str; 1] = ["links"];
+
+/// Set a member of the object owning the extensions
+pub(crate) fn set(extensions: &mut Option<Extensions>, key: &str, value: Value) {
+    extensions
+        .get_or_insert_with(Extensions::default)
+        .insert(key.to_string(), value);
+}
+
+/// The object member of the object owning the extensions, inserted when
+/// missing, `None` when the member isn't an object
+pub(crate) fn object_mut<'a>(
+    extensions: &'a mut Option<Extensions>,
+    key: &str,
+) -> Option<&'a mut Map<String, Value>> {
+    extensions
+        .get_or_insert_with(Extensions::default)
+        .entry(key.to_string())
+        .or_insert_with(|| Value::Object(Map::new()))
+        .as_object_mut()
+}
+
+/// Deserialize a spec, keeping the members utoipa has no field for
+pub(crate) fn from_value(value: &Value) -> serde_json::Result<OpenApi> {
+    // utoipa declares the `callbacks` of an operation as a string and reads
+    // the links of a response in snake case, so they are only restored from
+    // the extensions
+    let mut without_members = value.clone();
+    for operation in operations_mut(&mut without_members) {
+        for member in OPERATION_MEMBERS {
+            operation.remove(member);
+        }
+        let responses = operation
+            .get_mut("responses")
+            .and_then(Value::as_object_mut)
+            .into_iter()
+            .flat_map(|responses| responses.values_mut())
+            .filter_map(Value::as_object_mut);
+        for response in responses {
+            for member in RESPONSE_MEMBERS {
+                response.remove(member);
+            }
+        }
+    }
+    let mut spec = OpenApi::deserialize(&without_members)?;
+    restore(value, &mut spec.extensions, &SPEC_MEMBERS);
+    restore(&value["info"], &mut spec.info.extensions, &INFO_MEMBERS);
+    if let Some(components) = spec.components.as_mut() {
+        restore(
+            &value["components"],
+            &mut components.extensions,
+            &COMPONENTS_MEMBERS,
+        );
+    }
+    let Some(paths) = value["paths"].as_object() else {
+        return Ok(spec);
+    };
+    for (path, item) in paths {
+        let (Some(item), Some(methods)) = (spec.paths.paths.get_mut(path), item.as_object()) else {
+            continue;
+        };
+        for (method, operation_value) in methods {
+            let Some(operation) = operation_mut(item, method) else {
+                continue;
+            };
+            restore(
+                operation_value,
+                &mut operation.extensions,
+                &OPERATION_MEMBERS,
+            );
+            let Some(responses) = operation_value["responses"].as_object() else {
+                continue;
+            };
+            for (status, response_value) in responses {
+                if let Some(RefOr::T(response)) = operation.responses.responses.get_mut(status) {
+                    restore(response_value, &mut response.extensions, &RESPONSE_MEMBERS);
+                }
+            }
+        }
+    }
+    Ok(spec)
+}
+
+/// The serialized operations of the paths
+fn operations_mut(value: &mut Value) -> impl Iterator<Item = &mut Map<String, Value>> {
+    value
+        .get_mut("paths")
+        .and_then(Value::as_object_mut)
+        .into_iter()
+        .flat_map(|paths| paths.values_mut())
+        .filter_map(Value::as_object_mut)
+        .flat_map(|item| item.values_mut())
+        .filter_map(Value::as_object_mut)
+}
+
+/// Copy the members present in the serialized object to the extensions
+fn restore(object: &Value, extensions: &mut Option<Extensions>, members: &[&str]) {
+    for member in members {
+        if let Some(value) = object.get(member) {
+            set(extensions, member, value.clone());
+        }
+    }
+}
+
+/// The operation of a lowercase method, e.g. `get`
+fn operation_mut<'a>(item: &'a mut PathItem, method: &str) -> Option<&'a mut Operation> {
+    match method {
+        "get" => item.get.as_mut(),
+        "put" => item.put.as_mut(),
+        "post" => item.post.as_mut(),
+        "delete" => item.delete.as_mut(),
+        "options" => item.options.as_mut(),
+        "head" => item.head.as_mut(),
+        "patch" => item.patch.as_mut(),
+        "trace" => item.trace.as_mut(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_members_survive_deserialize() {
+        let value = serde_json::json!({
+            "openapi": "3.1.0",
+            "jsonSchemaDialect": "https://spec.openapis.org/oas/3.1/dialect/base",
+            "info": { "title": "albums", "version": "1.0.0", "summary": "Albums" },
+            "paths": {
+                "/api/exports": {
+                    "post": {
+                        "responses": {
+                            "202": {
+                                "description": "Export started",
+                                "links": { "GetExport": { "operationId": "get_export" } }
+                            }
+                        },
+                        "callbacks": { "exportReady": {} }
+                    }
+                }
+            },
+            "components": {
+                "parameters": { "TenantId": { "name": "X-Tenant-Id", "in": "header" } }
+            },
+            "webhooks": {
+                "user.created": {
+                    "post": { "responses": { "200": { "description": "Received" } } }
+                }
+            }
+        });
+
+        let json = serde_json::to_value(from_value(&value).unwrap()).unwrap();
+
+        assert_eq!(json["jsonSchemaDialect"], value["jsonSchemaDialect"]);
+        assert_eq!(json["webhooks"], value["webhooks"]);
+        assert_eq!(json["info"]["summary"], "Albums");
+        assert_eq!(
+            json["components"]["parameters"],
+            value["components"]["parameters"]
+        );
+        assert_eq!(
+            json["paths"]["/api/exports"]["post"]["callbacks"],
+            value["paths"]["/api/exports"]["post"]["callbacks"]
+        );
+        assert_eq!(
+            json["paths"]["/api/exports"]["post"]["responses"]["202"]["links"],
+            value["paths"]["/api/exports"]["post"]["responses"]["202"]["links"]
+        );
+    }
+}
@@ -1,45 +1,714 @@
-use loco_rs::app::AppContext;
-use std::sync::{Mutex, OnceLock};
+use axum::http::HeaderValue;
+use loco_rs::{app::AppContext, Error, Result};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, Mutex, OnceLock, RwLock,
+};
+use utoipa::openapi::{
+    extensions::Extensions,
+    path::{Operation, PathItem, Paths},
+    security::SecurityRequirement,
+    Deprecated, OpenApi, RefOr, Required, Schema,
+};
 use utoipa_axum::router::{OpenApiRouter, UtoipaMethodRouter};
 
+use crate::auth::{clear_api_key_schemes, clear_jwt_locations, clear_jwt_scopes};
+use crate::callbacks::clear_callbacks;
+use crate::config::DuplicateSchemaStrategy;
+use crate::examples::clear_schema_examples;
+use crate::links::clear_response_links;
+use crate::members;
+use crate::parameters::clear_shared_parameters;
+use crate::responses::{clear_default_error_responses, clear_response_headers};
+use crate::schemas::{clear_deprecated_fields, clear_registered_schemas};
+
 static OPENAPI_ROUTES: OnceLock<Mutex<Vec<OpenApiRouter<AppContext>>>> = OnceLock::new();
+static OPENAPI_GROUP_ROUTES: OnceLock<Mutex<BTreeMap<String, Vec<OpenApiRouter<AppContext>>>>> =
+    OnceLock::new();
+static OPENAPI_WEBHOOKS: OnceLock<Mutex<BTreeMap<String, OpenApiRouter<AppContext>>>> =
+    OnceLock::new();
+static OPENAPI_HIDDEN_ROUTES: OnceLock<Mutex<Vec<OpenApiRouter<AppContext>>>> = OnceLock::new();
+static NAMED_OPERATIONS: OnceLock<Mutex<BTreeSet<(&'static str, String)>>> = OnceLock::new();
+/// Prefix of the `$ref`s to component schemas
+pub(crate) const COMPONENT_SCHEMA_PREFIX: &str = "#/components/schemas/";
+
+static SPEC_FINALIZED: AtomicBool = AtomicBool::new(false);
+static REGISTRATION_PAUSED: AtomicBool = AtomicBool::new(false);
 
 fn get_routes() -> &'static Mutex<Vec<OpenApiRouter<AppContext>>> {
     OPENAPI_ROUTES.get_or_init(|| Mutex::new(Vec::new()))
 }
 
+fn get_group_routes() -> &'static Mutex<BTreeMap<String, Vec<OpenApiRouter<AppContext>>>> {
+    OPENAPI_GROUP_ROUTES.get_or_init(|| Mutex::new(BTreeMap::new()))
+}
+
+fn get_webhooks() -> &'static Mutex<BTreeMap<String, OpenApiRouter<AppContext>>> {
+    OPENAPI_WEBHOOKS.get_or_init(|| Mutex::new(BTreeMap::new()))
+}
+
+// The routes of `openapi_hidden`, left out of every spec
+fn get_hidden_routes() -> &'static Mutex<Vec<OpenApiRouter<AppContext>>> {
+    OPENAPI_HIDDEN_ROUTES.get_or_init(|| Mutex::new(Vec::new()))
+}
+
+// The methods and paths of the operations named with `openapi_named`
+fn get_named_operations() -> &'static Mutex<BTreeSet<(&'static str, String)>> {
+    NAMED_OPERATIONS.get_or_init(|| Mutex::new(BTreeSet::new()))
+}
+
+// Mark the spec as final, the registered routes were collected and routes
+// registered from now on have no effect on the served spec
+//
+// The lifecycle of the spec is:
+// 1. `openapi()`, `add_route` and friends register routes while the app
+//    routes are built
+// 2. The initializer collects them in `after_routes` and finalizes the spec
+// 3. The spec is served as is, until `clear_routes` starts over
+pub fn finalize_spec() {
+    SPEC_FINALIZED.store(true, Ordering::SeqCst);
+}
+
+// Whether the spec was finalized, see `finalize_spec`
+#[must_use]
+pub fn is_spec_finalized() -> bool {
+    SPEC_FINALIZED.load(Ordering::SeqCst)
+}
+
+// Run `f` without registering the routes it documents, for building the app
+// routes again only to list them
+pub(crate) fn without_registration<T>(f: impl FnOnce() -> T) -> T {
+    REGISTRATION_PAUSED.store(true, Ordering::SeqCst);
+    let result = f();
+    REGISTRATION_PAUSED.store(false, Ordering::SeqCst);
+    result
+}
+
+fn registration_paused() -> bool {
+    REGISTRATION_PAUSED.load(Ordering::SeqCst)
+}
+
+fn warn_if_finalized(kind: &str) {
+    if is_spec_finalized() {
+        tracing::warn!(
+            "{kind} registered after the OpenAPI spec was collected, it will not be documented"
+        );
+    }
+}
+
 // Register a route for later merging
 pub fn add_route(route: OpenApiRouter<AppContext>) {
+    if registration_paused() {
+        return;
+    }
+    warn_if_finalized("route");
+    log_operations(&route);
     if let Ok(mut routes) = get_routes().lock() {
         routes.push(route);
     }
 }
 
-// Clears all registered routes in the `OPENAPI_ROUTES`
+// Register a route of a named group for later merging, grouped routes are
+// only included in the spec of their group
+pub fn add_group_route(group: &str, route: OpenApiRouter<AppContext>) {
+    if registration_paused() {
+        return;
+    }
+    warn_if_finalized("route");
+    log_operations(&route);
+    if let Ok(mut groups) = get_group_routes().lock() {
+        groups.entry(group.to_string()).or_default().push(route);
+    }
+}
+
+/// Collects documented routes independently of the process-wide registry of
+/// `openapi()`, e.g. for a library registering its own routes next to the
+/// ones of the host app
+///
+/// Clones share the same routes. The initializer documents the routes of
+/// the collectors passed to
+/// [`crate::OpenapiInitializerWithSetup::with_collector`] in the main spec
+/// ```rust
+/// # use by_loco_openapi::prelude::*;
+/// # use loco_rs::prelude::*;
+/// # #[utoipa::path(get, path = "/api/album", responses((status = 200, description = "Album")))]
+/// # pub async fn get_album(State(_ctx): State<AppContext>) -> Result<Response> {
+/// #     format::empty()
+/// # }
+/// let collector = RouteCollector::new();
+/// Routes::new().add("/album", collector.openapi(get(get_album), routes!(get_album)));
+///
+/// let initializer = by_loco_openapi::OpenapiInitializerWithSetup::builder()
+///     .with_collector(&collector)
+///     .build();
+/// ```
+#[derive(Clone, Default)]
+pub struct RouteCollector {
+    routes: Arc<Mutex<Vec<OpenApiRouter<AppContext>>>>,
+}
+
+impl RouteCollector {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a route of the collector for later merging
+    pub fn add_route(&self, route: OpenApiRouter<AppContext>) {
+        if registration_paused() {
+            return;
+        }
+        if let Ok(mut routes) = self.routes.lock() {
+            routes.push(route);
+        }
+    }
+
+    /// Auto collect the openapi routes into the collector, like [`openapi`]
+    /// does into the process-wide registry
+    pub fn openapi(
+        &self,
+        method: axum::routing::MethodRouter<AppContext>,
+        method_openapi: UtoipaMethodRouter<AppContext>,
+    ) -> axum::routing::MethodRouter<AppContext> {
+        self.add_route(OpenApiRouter::new().routes(method_openapi));
+        method
+    }
+
+    /// The collected routes, unmerged
+    #[must_use]
+    pub fn routes(&self) -> Vec<OpenApiRouter<AppContext>> {
+        self.routes
+            .lock()
+            .map(|routes| routes.clone())
+            .unwrap_or_default()
+    }
+
+    /// Clear the collected routes
+    pub fn clear(&self) {
+        if let Ok(mut routes) = self.routes.lock() {
+            routes.clear();
+        }
+    }
+}
+
+// Clears all registered routes, see `clear_route_registries`, and the schemas,
+// examples, parameters, responses, links, callbacks and security schemes of
+// the registries
 // Mostly used for testing, to prevent routes added from different test runs from overlapping
 pub fn clear_routes() {
+    clear_route_registries();
+    clear_registries();
+}
+
+// Clears the registered routes in the `OPENAPI_ROUTES`, `OPENAPI_GROUP_ROUTES`
+// and `OPENAPI_HIDDEN_ROUTES` and the webhooks in `OPENAPI_WEBHOOKS`, and
+// un-finalizes the spec. The other registries are kept, they are usually
+// filled once at startup rather than on every boot
+pub(crate) fn clear_route_registries() {
+    SPEC_FINALIZED.store(false, Ordering::SeqCst);
     if let Ok(mut routes) = get_routes().lock() {
         routes.clear();
     }
+    if let Ok(mut groups) = get_group_routes().lock() {
+        groups.clear();
+    }
+    if let Ok(mut hidden) = get_hidden_routes().lock() {
+        hidden.clear();
+    }
+    if let Ok(mut webhooks) = get_webhooks().lock() {
+        webhooks.clear();
+    }
+    if let Ok(mut named) = get_named_operations().lock() {
+        named.clear();
+    }
 }
 
-// Get a merged router containing all collected routes
+// Clears the registries applied to every spec by `collect_spec`
+fn clear_registries() {
+    clear_registered_schemas();
+    clear_deprecated_fields();
+    clear_schema_examples();
+    clear_shared_parameters();
+    clear_default_error_responses();
+    clear_response_headers();
+    clear_response_links();
+    clear_callbacks();
+    clear_jwt_locations();
+    clear_jwt_scopes();
+    clear_api_key_schemes();
+}
+
+// Get a merged router containing all collected routes that are not in a group
 #[must_use]
 pub fn get_merged_router() -> OpenApiRouter<AppContext> {
-    let mut result = OpenApiRouter::new();
+    get_collected_routes()
+        .into_iter()
+        .fold(OpenApiRouter::new(), OpenApiRouter::merge)
+}
+
+// Get a merged router containing all collected routes of a group
+#[must_use]
+pub fn get_merged_group_router(group: &str) -> OpenApiRouter<AppContext> {
+    get_collected_group_routes(group)
+        .into_iter()
+        .fold(OpenApiRouter::new(), OpenApiRouter::merge)
+}
+
+// The collected routes that are not in a group, unmerged
+pub(crate) fn get_collected_routes() -> Vec<OpenApiRouter<AppContext>> {
+    get_routes()
+        .lock()
+        .map(|routes| routes.clone())
+        .unwrap_or_default()
+}
+
+// The collected routes of a group, unmerged
+pub(crate) fn get_collected_group_routes(group: &str) -> Vec<OpenApiRouter<AppContext>> {
+    get_group_routes()
+        .lock()
+        .map(|groups| groups.get(group).cloned().unwrap_or_default())
+        .unwrap_or_default()
+}
 
-    if let Ok(routes) = get_routes().lock() {
-        for route in routes.iter() {
-            result = result.merge(route.clone());
+/// Merge `route` into `router`, handling component schemas of `route` that
+/// have the name of a different schema already in `router` with `strategy`
+///
+/// utoipa keeps only one of the schemas, so the spec silently documents the
+/// wrong fields for the routes using the other one
+///
+/// # Errors
+///
+/// With [`DuplicateSchemaStrategy::Error`], when a schema name collides
+pub fn merge_routes<S>(
+    router: OpenApiRouter<S>,
+    route: OpenApiRouter<S>,
+    strategy: DuplicateSchemaStrategy,
+) -> Result<OpenApiRouter<S>>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    let duplicates = duplicate_schemas(router.get_openapi(), route.get_openapi());
+    if duplicates.is_empty() {
+        return Ok(router.merge(route));
+    }
+    match strategy {
+        DuplicateSchemaStrategy::Warn => {
+            for name in &duplicates {
+                tracing::warn!(
+                    schema = name,
+                    "two different schemas are named `{name}`, only one is documented"
+                );
+            }
+            Ok(router.merge(route))
+        }
+        DuplicateSchemaStrategy::Error => Err(Error::string(&format!(
+            "different OpenAPI schemas share the names: {}",
+            duplicates.join(", ")
+        ))),
+        DuplicateSchemaStrategy::Rename => {
+            let (route, mut spec) = route.split_for_parts();
+            for name in &duplicates {
+                let new_name = renamed_schema_name(router.get_openapi(), &spec, name);
+                tracing::warn!(
+                    schema = name,
+                    "two different schemas are named `{name}`, renaming one to `{new_name}`"
+                );
+                spec = rename_schema(spec, name, &new_name)?;
+            }
+            let mut router = router.merge(OpenApiRouter::from(route));
+            router.get_openapi_mut().merge(spec);
+            Ok(router)
         }
     }
-    result
+}
+
+// Names of the component schemas in both specs with different definitions
+fn duplicate_schemas(spec: &OpenApi, other: &OpenApi) -> Vec<String> {
+    let (Some(components), Some(other_components)) = (&spec.components, &other.components) else {
+        return Vec::new();
+    };
+    other_components
+        .schemas
+        .iter()
+        .filter(|(name, schema)| {
+            components
+                .schemas
+                .get(*name)
+                .is_some_and(|existing| existing != *schema)
+        })
+        .map(|(name, _)| name.clone())
+        .collect()
+}
+
+// The `{name}2`, `{name}3`, ... of `spec` already defined like the schema
+// `name` of `other`, so every merge of the same schema shares one name, or
+// else the first that is not a schema of either spec
+fn renamed_schema_name(spec: &OpenApi, other: &OpenApi, name: &str) -> String {
+    fn schemas(spec: &OpenApi) -> Option<&BTreeMap<String, RefOr<Schema>>> {
+        spec.components
+            .as_ref()
+            .map(|components| &components.schemas)
+    }
+
+    // Compared as JSON, the renamed schemas were rebuilt from theirs
+    let schema = schemas(other)
+        .and_then(|schemas| schemas.get(name))
+        .and_then(|schema| serde_json::to_value(schema).ok());
+    let is_renamed = |candidate: &str| {
+        candidate
+            .strip_prefix(name)
+            .and_then(|suffix| suffix.parse::<u32>().ok())
+            .is_some_and(|suffix| suffix >= 2)
+    };
+    let reused = schemas(spec)
+        .into_iter()
+        .flatten()
+        .find(|(candidate, existing)| {
+            is_renamed(candidate)
+                && schema.is_some()
+                && serde_json::to_value(existing).ok() == schema
+        });
+    if let Some((reused, _)) = reused {
+        return reused.clone();
+    }
+
+    let taken = |candidate: &String| {
+        [spec, other]
+            .iter()
+            .any(|spec| schemas(spec).is_some_and(|schemas| schemas.contains_key(candidate)))
+    };
+    (2..)
+        .map(|suffix| format!("{name}{suffix}"))
+        .find(|candidate| !taken(candidate))
+        .unwrap_or_else(|| name.to_string())
+}
+
+// Rename a component schema and the `$ref`s pointing to it
+fn rename_schema(spec: OpenApi, name: &str, new_name: &str) -> Result<OpenApi> {
+    fn rewrite(value: &mut Value, from: &str, to: &str) {
+        match value {
+            Value::Object(map) => {
+                if let Some(Value::String(reference)) = map.get_mut("$ref") {
+                    if reference == from {
+                        *reference = to.to_string();
+                    }
+                }
+                map.values_mut().for_each(|value| rewrite(value, from, to));
+            }
+            Value::Array(values) => values.iter_mut().for_each(|value| rewrite(value, from, to)),
+            _ => {}
+        }
+    }
+
+    let mut json = serde_json::to_value(spec)?;
+    rewrite(
+        &mut json,
+        &format!("{COMPONENT_SCHEMA_PREFIX}{name}"),
+        &format!("{COMPONENT_SCHEMA_PREFIX}{new_name}"),
+    );
+    if let Some(Value::Object(schemas)) = json.pointer_mut("/components/schemas") {
+        if let Some(schema) = schemas.remove(name) {
+            schemas.insert(new_name.to_string(), schema);
+        }
+    }
+    Ok(members::from_value(&json)?)
+}
+
+// Get the names of all groups with collected routes
+#[must_use]
+pub fn get_groups() -> Vec<String> {
+    get_group_routes()
+        .lock()
+        .map(|groups| groups.keys().cloned().collect())
+        .unwrap_or_default()
+}
+
+/// Register a webhook operation, documented under `webhooks` in the spec
+///
+/// No HTTP route is created, the `#[utoipa::path]` of the handler only
+/// describes the request the app sends. The path of the operation is ignored
+/// ```rust
+/// # use by_loco_openapi::prelude::*;
+/// # #[derive(serde::Serialize, ToSchema)]
+/// # pub struct User {
+/// #     id: u64,
+/// # }
+/// #[utoipa::path(
+///     post,
+///     path = "/user.created",
+///     request_body = User,
+///     responses((status = 200, description = "Webhook received")),
+/// )]
+/// async fn user_created() {}
+///
+/// add_webhook("user.created", routes!(user_created));
+/// ```
+pub fn add_webhook(name: &str, method_openapi: UtoipaMethodRouter<AppContext>) {
+    if registration_paused() {
+        return;
+    }
+    warn_if_finalized("webhook");
+    if let Ok(mut webhooks) = get_webhooks().lock() {
+        webhooks.insert(
+            name.to_string(),
+            OpenApiRouter::new().routes(method_openapi),
+        );
+    }
+}
+
+// Add the registered webhooks to the spec, next to the webhooks of the initial
+// spec, and their schemas to the components
+pub(crate) fn merge_webhooks(spec: &mut OpenApi) {
+    let Ok(webhooks) = get_webhooks().lock() else {
+        return;
+    };
+    if webhooks.is_empty() {
+        return;
+    }
+
+    let mut items = serde_json::Map::new();
+    for (name, router) in webhooks.iter() {
+        let (_, mut webhook_spec) = router.clone().split_for_parts();
+        let mut path_items = std::mem::take(&mut webhook_spec.paths.paths).into_values();
+        if let Some(mut item) = path_items.next() {
+            for other in path_items {
+                item.merge_operations(other);
+            }
+            if let Ok(value) = serde_json::to_value(item) {
+                items.insert(name.clone(), value);
+            }
+        }
+        spec.merge(webhook_spec);
+    }
+    if let Some(spec_items) = members::object_mut(&mut spec.extensions, "webhooks") {
+        spec_items.extend(items);
+    }
+}
+
+/// Merge an externally authored spec into the collected spec
+///
+/// On conflicts the collected spec wins: operations of a path are only added
+/// for the methods the collected spec doesn't document, and schemas,
+/// responses and security schemes with an existing name are skipped. The
+/// `info` and `servers` of the external spec are ignored
+pub fn merge_external_spec(spec: &mut OpenApi, mut external: OpenApi) {
+    for (path, external_item) in std::mem::take(&mut external.paths.paths) {
+        let Some(item) = spec.paths.paths.get_mut(&path) else {
+            spec.paths.paths.insert(path, external_item);
+            continue;
+        };
+        let operations = [
+            ("GET", &mut item.get, external_item.get),
+            ("PUT", &mut item.put, external_item.put),
+            ("POST", &mut item.post, external_item.post),
+            ("DELETE", &mut item.delete, external_item.delete),
+            ("OPTIONS", &mut item.options, external_item.options),
+            ("HEAD", &mut item.head, external_item.head),
+            ("PATCH", &mut item.patch, external_item.patch),
+            ("TRACE", &mut item.trace, external_item.trace),
+        ];
+        for (method, operation, external_operation) in operations {
+            match (operation.is_some(), external_operation) {
+                (true, Some(_)) => tracing::warn!(
+                    method,
+                    path,
+                    "operation of the external OpenAPI spec is already collected, skipping it"
+                ),
+                (false, external_operation) => *operation = external_operation,
+                (true, None) => {}
+            }
+        }
+    }
+    // Servers are set for the whole app, not per merged spec
+    external.servers = None;
+    spec.merge(external);
+}
+
+/// How the `operationId`s of the collected operations are generated
+///
+/// utoipa derives `operationId`s from the handler function names, which
+/// collide when handlers in different modules share a name (e.g. `get`)
+#[derive(Default, Debug, Clone, Copy)]
+pub enum OperationIdStrategy {
+    /// Keep the `operationId`s derived by utoipa
+    #[default]
+    Derived,
+    /// Prefix the `operationId` with the first tag of the operation,
+    /// `get` tagged `album` becomes `album_get`
+    TagPrefixed,
+    /// Generate the `operationId` with a custom function, called with the path
+    /// and the operation. Returning `None` keeps the derived `operationId`
+    Custom(fn(&str, &Operation) -> Option<String>),
+}
+
+impl OperationIdStrategy {
+    /// Rewrite the `operationId`s of all operations in the spec
+    pub fn apply(&self, spec: &mut OpenApi) {
+        let rewrite: fn(&str, &Operation) -> Option<String> = match self {
+            Self::Derived => return,
+            Self::TagPrefixed => |_, operation| {
+                let tag = operation.tags.as_ref()?.first()?;
+                let operation_id = operation.operation_id.as_ref()?;
+                let prefix = format!("{tag}_");
+                (!operation_id.starts_with(&prefix)).then(|| format!("{prefix}{operation_id}"))
+            },
+            Self::Custom(rewrite) => *rewrite,
+        };
+
+        let named = get_named_operations()
+            .lock()
+            .map(|named| named.clone())
+            .unwrap_or_default();
+        for (path, item) in &mut spec.paths.paths {
+            for (method, operation) in path_item_operations(item) {
+                let Some(operation) = operation else {
+                    continue;
+                };
+                if named.contains(&(method, path.clone())) {
+                    continue;
+                }
+                if let Some(operation_id) = rewrite(path, operation) {
+                    operation.operation_id = Some(operation_id);
+                }
+            }
+        }
+    }
+}
+
+static OPERATION_ID_STRATEGY: RwLock<Option<OperationIdStrategy>> = RwLock::new(None);
+
+// Set the strategy used to generate the `operationId`s of the collected operations,
+// replacing the one set before
+pub fn set_operation_id_strategy(strategy: OperationIdStrategy) {
+    if let Ok(mut current) = OPERATION_ID_STRATEGY.write() {
+        *current = Some(strategy);
+    }
+}
+
+#[must_use]
+pub fn get_operation_id_strategy() -> OperationIdStrategy {
+    OPERATION_ID_STRATEGY
+        .read()
+        .ok()
+        .and_then(|strategy| *strategy)
+        .unwrap_or_default()
+}
+
+fn log_operations(route: &OpenApiRouter<AppContext>) {
+    for (method, path, operation) in operations(&route.get_openapi().paths) {
+        tracing::debug!(
+            method,
+            path,
+            operation_id = operation.operation_id.as_deref(),
+            "collected OpenAPI operation"
+        );
+    }
+}
+
+// The method, path and operation of every operation of each path item
+pub(crate) fn operations(paths: &Paths) -> Vec<(&'static str, &str, &Operation)> {
+    paths
+        .paths
+        .iter()
+        .flat_map(|(path, item)| {
+            [
+                ("GET", &item.get),
+                ("PUT", &item.put),
+                ("POST", &item.post),
+                ("DELETE", &item.delete),
+                ("OPTIONS", &item.options),
+                ("HEAD", &item.head),
+                ("PATCH", &item.patch),
+                ("TRACE", &item.trace),
+            ]
+            .into_iter()
+            .filter_map(move |(method, operation)| {
+                Some((method, path.as_str(), operation.as_ref()?))
+            })
+        })
+        .collect()
+}
+
+// The `$ref`s of the serialized spec that don't point to anything in it,
+// sorted and deduplicated
+pub(crate) fn unresolved_refs(json: &Value) -> Vec<&str> {
+    let mut refs = Vec::new();
+    collect_refs(json, &mut refs);
+    refs.sort_unstable();
+    refs.dedup();
+    refs.retain(|reference| {
+        reference
+            .strip_prefix('#')
+            .is_none_or(|pointer| json.pointer(pointer).is_none())
+    });
+    refs
+}
+
+pub(crate) fn collect_refs<'a>(value: &'a Value, refs: &mut Vec<&'a str>) {
+    match value {
+        Value::Object(map) => {
+            if let Some(Value::String(reference)) = map.get("$ref") {
+                refs.push(reference);
+            }
+            map.values().for_each(|value| collect_refs(value, refs));
+        }
+        Value::Array(values) => values.iter().for_each(|value| collect_refs(value, refs)),
+        _ => {}
+    }
+}
+
+/// Fail when a `$ref` of the spec doesn't resolve, typically a `body = Album`
+/// whose `Album` schema was never registered with `ToSchema`
+///
+/// # Errors
+///
+/// Will return `Err` listing the unresolved refs
+pub fn check_refs(name: &str, spec: &OpenApi) -> Result<()> {
+    let json = serde_json::to_value(spec)?;
+    let refs = unresolved_refs(&json);
+    if refs.is_empty() {
+        return Ok(());
+    }
+    Err(Error::string(&format!(
+        "unresolved `$ref`s in OpenAPI spec `{name}`: {}",
+        refs.join(", ")
+    )))
+}
+
+// Call `f` with the path and every operation of each path item
+pub(crate) fn for_each_operation<F>(paths: &mut Paths, mut f: F)
+where
+    F: FnMut(&str, &mut Operation),
+{
+    for (path, item) in &mut paths.paths {
+        for (_, operation) in path_item_operations(item) {
+            if let Some(operation) = operation {
+                f(path, operation);
+            }
+        }
+    }
+}
+
+// The method and operation slot of each method of the path item
+fn path_item_operations(item: &mut PathItem) -> [(&'static str, &mut Option<Operation>); 8] {
+    [
+        ("GET", &mut item.get),
+        ("PUT", &mut item.put),
+        ("POST", &mut item.post),
+        ("DELETE", &mut item.delete),
+        ("OPTIONS", &mut item.options),
+        ("HEAD", &mut item.head),
+        ("PATCH", &mut item.patch),
+        ("TRACE", &mut item.trace),
+    ]
 }
 
 /// Auto collect the openapi routes
 /// ```rust
 /// # use axum::debug_handler;
-/// use loco_openapi::prelude::*;
+/// use by_loco_openapi::prelude::*;
 /// # use loco_rs::prelude::*;
 /// # use serde::Serialize;
 /// # #[derive(Serialize, Debug, ToSchema)]
@@ -78,3 +747,1081 @@ pub fn openapi(
     add_route(router);
     method
 }
+
+/// Auto collect the openapi routes into a named group, which is documented
+/// in its own spec instead of the main one
+/// ```rust
+/// # use by_loco_openapi::prelude::*;
+/// # use loco_rs::prelude::*;
+/// # #[utoipa::path(get, path = "/api/admin/stats", responses((status = 200, description = "Stats")))]
+/// # pub async fn get_stats(State(_ctx): State<AppContext>) -> Result<Response> {
+/// #     format::empty()
+/// # }
+/// Routes::new()
+///     .add("/stats", openapi_grouped(get(get_stats), routes!(get_stats), "admin"));
+/// ```
+pub fn openapi_grouped(
+    method: axum::routing::MethodRouter<AppContext>,
+    method_openapi: UtoipaMethodRouter<AppContext>,
+    group: &str,
+) -> axum::routing::MethodRouter<AppContext> {
+    let router = OpenApiRouter::new().routes(method_openapi);
+    add_group_route(group, router);
+    method
+}
+
+/// Auto collect the openapi routes, documented under `tag` instead of the
+/// tags of the `#[utoipa::path]` annotation
+/// ```rust
+/// # use by_loco_openapi::prelude::*;
+/// # use loco_rs::prelude::*;
+/// # #[utoipa::path(get, path = "/api/admin/album", tags = ["album"], responses((status = 200, description = "Album")))]
+/// # pub async fn get_album(State(_ctx): State<AppContext>) -> Result<Response> {
+/// #     format::empty()
+/// # }
+/// Routes::new()
+///     .add("/album", openapi_tagged(get(get_album), routes!(get_album), "admin"));
+/// ```
+pub fn openapi_tagged(
+    method: axum::routing::MethodRouter<AppContext>,
+    method_openapi: UtoipaMethodRouter<AppContext>,
+    tag: &str,
+) -> axum::routing::MethodRouter<AppContext> {
+    let method_openapi = map_operations(method_openapi, |_, operation| {
+        operation.tags = Some(vec![tag.to_string()]);
+    });
+    openapi(method, method_openapi)
+}
+
+/// Auto collect the openapi routes, with `operation_id` as the `operationId`
+/// of the operations, so generated client method names stay stable when the
+/// handler is renamed. The [`OperationIdStrategy`] doesn't rewrite it
+/// ```rust
+/// # use by_loco_openapi::prelude::*;
+/// # use loco_rs::prelude::*;
+/// # #[utoipa::path(get, path = "/api/album", responses((status = 200, description = "Album")))]
+/// # pub async fn get_album(State(_ctx): State<AppContext>) -> Result<Response> {
+/// #     format::empty()
+/// # }
+/// Routes::new()
+///     .add("/album", openapi_named(get(get_album), routes!(get_album), "getAlbum"));
+/// ```
+pub fn openapi_named(
+    method: axum::routing::MethodRouter<AppContext>,
+    method_openapi: UtoipaMethodRouter<AppContext>,
+    operation_id: &str,
+) -> axum::routing::MethodRouter<AppContext> {
+    if let Ok(mut named) = get_named_operations().lock() {
+        named.extend(
+            operations(&method_openapi.1)
+                .into_iter()
+                .map(|(method, path, _)| (method, path.to_string())),
+        );
+    }
+    let method_openapi = map_operations(method_openapi, |_, operation| {
+        operation.operation_id = Some(operation_id.to_string());
+    });
+    openapi(method, method_openapi)
+}
+
+/// Auto collect the openapi routes, requiring the `jwt_token` security
+/// scheme, see [`crate::auth::SecurityAddon`]. Meant for routes behind Loco's
+/// JWT auth, so `security(("jwt_token" = []))` doesn't have to be repeated
+/// on each `#[utoipa::path]`. With schemes registered by
+/// [`crate::auth::add_jwt_location`], either scheme is accepted
+/// ```rust
+/// # use by_loco_openapi::prelude::*;
+/// # use loco_rs::prelude::*;
+/// # #[utoipa::path(get, path = "/api/user/current", responses((status = 200, description = "Current user")))]
+/// # pub async fn current(auth: auth::JWT, State(_ctx): State<AppContext>) -> Result<Response> {
+/// #     format::empty()
+/// # }
+/// Routes::new()
+///     .add("/current", openapi_secured(get(current), routes!(current)));
+/// ```
+pub fn openapi_secured(
+    method: axum::routing::MethodRouter<AppContext>,
+    method_openapi: UtoipaMethodRouter<AppContext>,
+) -> axum::routing::MethodRouter<AppContext> {
+    openapi_scoped(method, method_openapi, &[])
+}
+
+/// Auto collect the openapi routes, requiring the `jwt_token` security
+/// scheme with the given scopes, documented as
+/// `security: [{ jwt_token: ["albums:read"] }]`. Declare the scopes on the
+/// scheme with [`crate::auth::add_jwt_scope`]
+/// ```rust
+/// # use by_loco_openapi::prelude::*;
+/// # use loco_rs::prelude::*;
+/// # #[utoipa::path(get, path = "/api/album", responses((status = 200, description = "Albums")))]
+/// # pub async fn list_albums(auth: auth::JWT, State(_ctx): State<AppContext>) -> Result<Response> {
+/// #     format::empty()
+/// # }
+/// Routes::new()
+///     .add("/", openapi_scoped(get(list_albums), routes!(list_albums), &["albums:read"]));
+/// ```
+pub fn openapi_scoped(
+    method: axum::routing::MethodRouter<AppContext>,
+    method_openapi: UtoipaMethodRouter<AppContext>,
+    scopes: &[&str],
+) -> axum::routing::MethodRouter<AppContext> {
+    // The other JWT schemes are added when the spec is collected, see
+    // `JwtSchemeRequirements`
+    let requirement = SecurityRequirement::new("jwt_token", scopes.iter().copied());
+    let method_openapi = map_operations(method_openapi, |_, operation| {
+        let security = operation.security.get_or_insert_with(Vec::new);
+        if !security.contains(&requirement) {
+            security.push(requirement.clone());
+        }
+    });
+    openapi(method, method_openapi)
+}
+
+/// Auto collect the openapi routes, marked as deprecated without editing the
+/// `#[utoipa::path]` of the handler
+/// ```rust
+/// # use by_loco_openapi::prelude::*;
+/// # use loco_rs::prelude::*;
+/// # #[utoipa::path(get, path = "/api/album/legacy", responses((status = 200, description = "Album")))]
+/// # pub async fn legacy_album(State(_ctx): State<AppContext>) -> Result<Response> {
+/// #     format::empty()
+/// # }
+/// Routes::new()
+///     .add("/legacy", openapi_deprecated(get(legacy_album), routes!(legacy_album)));
+/// ```
+pub fn openapi_deprecated(
+    method: axum::routing::MethodRouter<AppContext>,
+    method_openapi: UtoipaMethodRouter<AppContext>,
+) -> axum::routing::MethodRouter<AppContext> {
+    let method_openapi = map_operations(method_openapi, |_, operation| {
+        operation.deprecated = Some(Deprecated::True);
+    });
+    openapi(method, method_openapi)
+}
+
+/// Like [`openapi_deprecated`], and the responses of the route signal the
+/// deprecation at runtime with the `Deprecation: true` and
+/// `Sunset: {sunset}` headers of RFC 8594. `sunset` is the HTTP date the
+/// route goes away, e.g. `Thu, 31 Dec 2026 23:59:59 GMT`
+/// ```rust
+/// # use by_loco_openapi::prelude::*;
+/// # use loco_rs::prelude::*;
+/// # #[utoipa::path(get, path = "/api/album/legacy", responses((status = 200, description = "Album")))]
+/// # pub async fn legacy_album(State(_ctx): State<AppContext>) -> Result<Response> {
+/// #     format::empty()
+/// # }
+/// Routes::new().add(
+///     "/legacy",
+///     openapi_sunset(get(legacy_album), routes!(legacy_album), "Thu, 31 Dec 2026 23:59:59 GMT"),
+/// );
+/// ```
+pub fn openapi_sunset(
+    method: axum::routing::MethodRouter<AppContext>,
+    method_openapi: UtoipaMethodRouter<AppContext>,
+    sunset: &str,
+) -> axum::routing::MethodRouter<AppContext> {
+    let method = match HeaderValue::from_str(sunset) {
+        Ok(sunset) => method.layer(axum::middleware::map_response(
+            move |mut response: axum::response::Response| {
+                let sunset = sunset.clone();
+                async move {
+                    let headers = response.headers_mut();
+                    headers.insert("deprecation", HeaderValue::from_static("true"));
+                    headers.insert("sunset", sunset);
+                    response
+                }
+            },
+        )),
+        Err(_) => {
+            tracing::warn!(sunset, "invalid sunset date, the Sunset header is not sent");
+            method
+        }
+    };
+    openapi_deprecated(method, method_openapi)
+}
+
+/// Auto collect the openapi routes, with `example` as the example of the
+/// request body so "Try it out" is prefilled, without baking the example into
+/// the `#[utoipa::path]` of the handler
+/// ```rust
+/// # use by_loco_openapi::prelude::*;
+/// # use loco_rs::prelude::*;
+/// # #[derive(serde::Deserialize, utoipa::ToSchema)]
+/// # pub struct Album { title: String }
+/// # #[utoipa::path(post, path = "/api/album", request_body = Album, responses((status = 200, description = "Album")))]
+/// # pub async fn create_album(State(_ctx): State<AppContext>) -> Result<Response> {
+/// #     format::empty()
+/// # }
+/// Routes::new().add(
+///     "/album",
+///     openapi_with_example(
+///         post(create_album),
+///         routes!(create_album),
+///         serde_json::json!({ "title": "VH II" }),
+///     ),
+/// );
+/// ```
+pub fn openapi_with_example(
+    method: axum::routing::MethodRouter<AppContext>,
+    method_openapi: UtoipaMethodRouter<AppContext>,
+    example: Value,
+) -> axum::routing::MethodRouter<AppContext> {
+    let method_openapi = map_operations(method_openapi, |_, operation| {
+        let Some(request_body) = operation.request_body.as_mut() else {
+            return;
+        };
+        for content in request_body.content.values_mut() {
+            content.example = Some(example.clone());
+        }
+    });
+    openapi(method, method_openapi)
+}
+
+/// Auto collect the openapi routes, with the request body marked required,
+/// e.g. when `#[utoipa::path]` documents an `Option` body or lists its
+/// content types without a type. All content types of the body are kept
+/// ```rust
+/// # use by_loco_openapi::prelude::*;
+/// # use loco_rs::prelude::*;
+/// # #[derive(serde::Deserialize, utoipa::ToSchema)]
+/// # pub struct Album { title: String }
+/// # #[utoipa::path(
+/// #     post,
+/// #     path = "/api/album",
+/// #     request_body(content((Album = "application/json"), (Album = "application/x-www-form-urlencoded"))),
+/// #     responses((status = 200, description = "Album")),
+/// # )]
+/// # pub async fn create_album(State(_ctx): State<AppContext>) -> Result<Response> {
+/// #     format::empty()
+/// # }
+/// Routes::new().add(
+///     "/album",
+///     openapi_body_required(post(create_album), routes!(create_album)),
+/// );
+/// ```
+pub fn openapi_body_required(
+    method: axum::routing::MethodRouter<AppContext>,
+    method_openapi: UtoipaMethodRouter<AppContext>,
+) -> axum::routing::MethodRouter<AppContext> {
+    let method_openapi = map_operations(method_openapi, |_, operation| {
+        if let Some(request_body) = operation.request_body.as_mut() {
+            request_body.required = Some(Required::True);
+        }
+    });
+    openapi(method, method_openapi)
+}
+
+/// Auto collect the openapi routes, with `x-` vendor extensions on the
+/// operations, e.g. for API gateways reading `x-rate-limit` from the spec.
+/// Keys without the `x-` prefix are skipped
+/// ```rust
+/// # use by_loco_openapi::prelude::*;
+/// # use loco_rs::prelude::*;
+/// # #[utoipa::path(get, path = "/api/album", responses((status = 200, description = "Album")))]
+/// # pub async fn get_album(State(_ctx): State<AppContext>) -> Result<Response> {
+/// #     format::empty()
+/// # }
+/// Routes::new().add(
+///     "/album",
+///     openapi_with_extensions(
+///         get(get_album),
+///         routes!(get_album),
+///         [
+///             ("x-rate-limit", serde_json::json!(100)),
+///             ("x-internal", serde_json::json!(false)),
+///         ],
+///     ),
+/// );
+/// ```
+pub fn openapi_with_extensions<I, K>(
+    method: axum::routing::MethodRouter<AppContext>,
+    method_openapi: UtoipaMethodRouter<AppContext>,
+    extensions: I,
+) -> axum::routing::MethodRouter<AppContext>
+where
+    I: IntoIterator<Item = (K, Value)>,
+    K: Into<String>,
+{
+    let extensions = extensions
+        .into_iter()
+        .map(|(key, value)| (key.into(), value))
+        .filter(|(key, _)| {
+            let vendor = key.starts_with("x-");
+            if !vendor {
+                tracing::warn!(
+                    key,
+                    "operation extensions must start with `x-`, skipping it"
+                );
+            }
+            vendor
+        })
+        .collect::<Vec<_>>();
+    let method_openapi = map_operations(method_openapi, |_, operation| {
+        let operation_extensions = operation.extensions.get_or_insert_with(Extensions::default);
+        for (key, value) in &extensions {
+            operation_extensions.insert(key.clone(), value.clone());
+        }
+    });
+    openapi(method, method_openapi)
+}
+
+/// Route an internal handler without documenting it, the counterpart of
+/// [`openapi`] that states at the call site that the route is deliberately
+/// left out of the published spec
+///
+/// The route is kept in a hidden registry instead, so
+/// [`crate::OpenapiInitializerWithSetup::warn_undocumented_routes`] doesn't
+/// report it
+/// ```rust
+/// # use by_loco_openapi::prelude::*;
+/// # use loco_rs::prelude::*;
+/// # #[utoipa::path(post, path = "/api/reindex", responses((status = 200, description = "Reindexed")))]
+/// # pub async fn reindex(State(_ctx): State<AppContext>) -> Result<Response> {
+/// #     format::empty()
+/// # }
+/// Routes::new()
+///     .add("/reindex", openapi_hidden(post(reindex), routes!(reindex)));
+/// ```
+pub fn openapi_hidden(
+    method: axum::routing::MethodRouter<AppContext>,
+    method_openapi: UtoipaMethodRouter<AppContext>,
+) -> axum::routing::MethodRouter<AppContext> {
+    if !registration_paused() {
+        if let Ok(mut hidden) = get_hidden_routes().lock() {
+            hidden.push(OpenApiRouter::new().routes(method_openapi));
+        }
+    }
+    method
+}
+
+// The spec of the `openapi_hidden` routes, never served, it only tells which
+// routes are left out on purpose
+pub(crate) fn get_hidden_spec() -> OpenApi {
+    let routes = get_hidden_routes()
+        .lock()
+        .map(|routes| routes.clone())
+        .unwrap_or_default();
+    let (_, spec) = routes
+        .into_iter()
+        .fold(OpenApiRouter::new(), OpenApiRouter::merge)
+        .split_for_parts();
+    spec
+}
+
+// Call `f` with every operation documented by the method router
+fn map_operations<F>(
+    mut method_openapi: UtoipaMethodRouter<AppContext>,
+    f: F,
+) -> UtoipaMethodRouter<AppContext>
+where
+    F: FnMut(&str, &mut Operation),
+{
+    for_each_operation(&mut method_openapi.1, f);
+    method_openapi
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::JwtSchemeRequirements;
+    use axum::routing::get;
+    use utoipa_axum::routes;
+
+    #[utoipa::path(get, path = "/api/admin/users", responses((status = 200, description = "Users")))]
+    async fn list_users() {}
+
+    #[utoipa::path(get, path = "/api/admin/stats", responses((status = 200, description = "Stats")))]
+    async fn get_stats() {}
+
+    #[utoipa::path(get, path = "/api/album", responses((status = 200, description = "Album")))]
+    async fn get_album() {}
+
+    #[test]
+    #[serial_test::serial]
+    fn test_grouped_routes_only_in_group_spec() {
+        clear_routes();
+        let _ = openapi(get(get_album), routes!(get_album));
+        let _ = openapi_grouped(get(list_users), routes!(list_users), "admin");
+        let _ = openapi_grouped(get(get_stats), routes!(get_stats), "admin");
+
+        assert_eq!(get_groups(), vec!["admin".to_string()]);
+
+        let (_, admin_spec) = get_merged_group_router("admin").split_for_parts();
+        assert!(admin_spec.paths.paths.contains_key("/api/admin/users"));
+        assert!(admin_spec.paths.paths.contains_key("/api/admin/stats"));
+        assert!(!admin_spec.paths.paths.contains_key("/api/album"));
+
+        let (_, main_spec) = get_merged_router().split_for_parts();
+        assert!(main_spec.paths.paths.contains_key("/api/album"));
+        assert!(!main_spec.paths.paths.contains_key("/api/admin/users"));
+        assert!(!main_spec.paths.paths.contains_key("/api/admin/stats"));
+
+        clear_routes();
+    }
+
+    mod music {
+        #[derive(utoipa::ToSchema)]
+        #[allow(dead_code)]
+        pub struct Album {
+            title: String,
+        }
+
+        #[utoipa::path(get, path = "/api/music/album", responses((status = 200, description = "Album", body = Album)))]
+        pub async fn get_album() {}
+
+        #[utoipa::path(get, path = "/api/music/albums", responses((status = 200, description = "Albums", body = Vec<Album>)))]
+        pub async fn list_albums() {}
+    }
+
+    mod photos {
+        #[derive(utoipa::ToSchema)]
+        #[allow(dead_code)]
+        pub struct Album {
+            photos: Vec<String>,
+        }
+
+        #[utoipa::path(get, path = "/api/photos/album", responses((status = 200, description = "Album", body = Album)))]
+        pub async fn get_album() {}
+
+        #[utoipa::path(get, path = "/api/photos/cover", responses((status = 200, description = "Album of the cover", body = Album)))]
+        pub async fn get_cover() {}
+    }
+
+    fn duplicate_album_routes() -> (OpenApiRouter, OpenApiRouter) {
+        (
+            OpenApiRouter::new().routes(routes!(music::get_album)),
+            OpenApiRouter::new().routes(routes!(photos::get_album)),
+        )
+    }
+
+    #[test]
+    fn test_duplicate_schemas_error() {
+        let (music, photos) = duplicate_album_routes();
+
+        let Err(err) = merge_routes(music, photos, DuplicateSchemaStrategy::Error) else {
+            panic!("the duplicate schemas should fail the merge");
+        };
+
+        assert!(err.to_string().contains("Album"));
+    }
+
+    #[test]
+    fn test_duplicate_schemas_rename() {
+        let (music, photos) = duplicate_album_routes();
+
+        let (_, spec) = merge_routes(music, photos, DuplicateSchemaStrategy::Rename)
+            .unwrap()
+            .split_for_parts();
+
+        let json = serde_json::to_value(&spec).unwrap();
+        let schemas = &json["components"]["schemas"];
+        assert!(schemas["Album"]["properties"]["title"].is_object());
+        assert!(schemas["Album2"]["properties"]["photos"].is_object());
+        let schema_ref = |path: &str| {
+            json["paths"][path]["get"]["responses"]["200"]["content"]["application/json"]["schema"]
+                ["$ref"]
+                .clone()
+        };
+        assert_eq!(schema_ref("/api/music/album"), "#/components/schemas/Album");
+        assert_eq!(
+            schema_ref("/api/photos/album"),
+            "#/components/schemas/Album2"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_schemas_rename_keeps_handlers() {
+        use tower::ServiceExt;
+
+        let (music, photos) = duplicate_album_routes();
+
+        let (router, _) = merge_routes(music, photos, DuplicateSchemaStrategy::Rename)
+            .unwrap()
+            .split_for_parts();
+
+        for path in ["/api/music/album", "/api/photos/album"] {
+            let res = router
+                .clone()
+                .oneshot(
+                    axum::http::Request::get(path)
+                        .body(axum::body::Body::empty())
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(res.status(), axum::http::StatusCode::OK, "{path}");
+        }
+    }
+
+    #[test]
+    fn test_duplicate_schemas_rename_reuses_name() {
+        let (music, photos) = duplicate_album_routes();
+        let covers = OpenApiRouter::new().routes(routes!(photos::get_cover));
+
+        let router = merge_routes(music, photos, DuplicateSchemaStrategy::Rename).unwrap();
+        let (_, spec) = merge_routes(router, covers, DuplicateSchemaStrategy::Rename)
+            .unwrap()
+            .split_for_parts();
+
+        let json = serde_json::to_value(&spec).unwrap();
+        let schemas = json["components"]["schemas"].as_object().unwrap();
+        assert_eq!(schemas.keys().collect::<Vec<_>>(), ["Album", "Album2"]);
+        for path in ["/api/photos/album", "/api/photos/cover"] {
+            assert_eq!(
+                json["paths"][path]["get"]["responses"]["200"]["content"]["application/json"]
+                    ["schema"]["$ref"],
+                "#/components/schemas/Album2",
+                "{path}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_identical_schemas_are_not_duplicates() {
+        let albums: OpenApiRouter = OpenApiRouter::new().routes(routes!(music::get_album));
+        let album_list: OpenApiRouter = OpenApiRouter::new().routes(routes!(music::list_albums));
+
+        let merged = merge_routes(albums, album_list, DuplicateSchemaStrategy::Error);
+
+        assert!(merged.is_ok());
+    }
+
+    #[derive(serde::Serialize, utoipa::ToSchema)]
+    #[allow(dead_code)]
+    struct User {
+        id: u64,
+    }
+
+    #[utoipa::path(
+        post,
+        path = "/user.created",
+        request_body = User,
+        responses((status = 200, description = "Webhook received")),
+    )]
+    async fn user_created() {}
+
+    #[test]
+    #[serial_test::serial]
+    fn test_webhooks_in_spec() {
+        clear_routes();
+        add_webhook("user.created", routes!(user_created));
+
+        let mut spec = OpenApi::default();
+        merge_webhooks(&mut spec);
+
+        let json = serde_json::to_value(&spec).unwrap();
+        assert!(json["webhooks"]["user.created"]["post"].is_object());
+        assert!(json["components"]["schemas"]["User"].is_object());
+        // No route is documented for the webhook
+        assert!(spec.paths.paths.is_empty());
+
+        clear_routes();
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_tagged_route_overrides_tags() {
+        clear_routes();
+        let _ = openapi_tagged(get(album::get), routes!(album::get), "admin");
+
+        let (_, spec) = get_merged_router().split_for_parts();
+        let operation = spec.paths.paths["/api/album"].get.as_ref().unwrap();
+        assert_eq!(operation.tags, Some(vec!["admin".to_string()]));
+
+        clear_routes();
+    }
+
+    #[utoipa::path(
+        get,
+        path = "/api/albums/export",
+        responses(
+            (status = 200, description = "Albums as CSV", content_type = "text/csv", body = String),
+            (status = 202, description = "Archive", content_type = "application/octet-stream", body = Vec<u8>),
+        ),
+    )]
+    async fn export_albums() {}
+
+    #[test]
+    #[serial_test::serial]
+    fn test_non_json_content_types_preserved() {
+        clear_routes();
+        let _ = openapi(get(export_albums), routes!(export_albums));
+        let _ = openapi(get(get_album), routes!(get_album));
+
+        let (_, spec) = get_merged_router().split_for_parts();
+        let json = serde_json::to_value(&spec).unwrap();
+        let responses = &json["paths"]["/api/albums/export"]["get"]["responses"];
+        assert!(responses["200"]["content"]["text/csv"].is_object());
+        assert!(responses["202"]["content"]["application/octet-stream"].is_object());
+
+        clear_routes();
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_independent_route_collectors() {
+        clear_routes();
+        let library = RouteCollector::new();
+        let app = RouteCollector::new();
+        let _ = library.openapi(get(list_users), routes!(list_users));
+        let _ = app.openapi(get(get_album), routes!(get_album));
+        let _ = app.clone().openapi(get(get_stats), routes!(get_stats));
+
+        let paths = |collector: &RouteCollector| {
+            collector
+                .routes()
+                .into_iter()
+                .fold(OpenApiRouter::<AppContext>::new(), OpenApiRouter::merge)
+                .into_openapi()
+                .paths
+                .paths
+                .into_keys()
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(paths(&library), vec!["/api/admin/users"]);
+        assert_eq!(paths(&app), vec!["/api/admin/stats", "/api/album"]);
+        assert!(get_collected_routes().is_empty());
+
+        app.clear();
+        assert!(app.routes().is_empty());
+        assert_eq!(library.routes().len(), 1);
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_finalize_spec() {
+        clear_routes();
+        assert!(!is_spec_finalized());
+
+        finalize_spec();
+        assert!(is_spec_finalized());
+        // Still registered, only warned about
+        let _ = openapi(get(get_album), routes!(get_album));
+        let (_, spec) = get_merged_router().split_for_parts();
+        assert!(spec.paths.paths.contains_key("/api/album"));
+
+        clear_routes();
+        assert!(!is_spec_finalized());
+    }
+
+    #[test]
+    fn test_operations() {
+        let (_, spec) = OpenApiRouter::<()>::new()
+            .routes(routes!(get_album))
+            .routes(routes!(get_stats))
+            .split_for_parts();
+
+        let operations = operations(&spec.paths)
+            .into_iter()
+            .map(|(method, path, operation)| (method, path, operation.operation_id.as_deref()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            operations,
+            vec![
+                ("GET", "/api/admin/stats", Some("get_stats")),
+                ("GET", "/api/album", Some("get_album")),
+            ]
+        );
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_secured_route_requires_jwt() {
+        clear_routes();
+        let _ = openapi_secured(get(list_users), routes!(list_users));
+        let _ = openapi(get(get_album), routes!(get_album));
+
+        let (_, spec) = get_merged_router().split_for_parts();
+        let security = |path: &str| {
+            spec.paths.paths[path]
+                .get
+                .as_ref()
+                .unwrap()
+                .security
+                .as_ref()
+                .map(|security| serde_json::to_value(security).unwrap())
+        };
+        assert_eq!(
+            security("/api/admin/users"),
+            Some(
+                serde_json::to_value(vec![SecurityRequirement::new(
+                    "jwt_token",
+                    Vec::<String>::new()
+                )])
+                .unwrap()
+            )
+        );
+        assert!(security("/api/album").is_none());
+
+        clear_routes();
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn test_sunset_route_headers() {
+        use tower::ServiceExt;
+
+        clear_routes();
+        let ctx = loco_rs::tests_cfg::app::get_app_context().await;
+        let sunset = "Thu, 31 Dec 2026 23:59:59 GMT";
+        let app = axum::Router::new()
+            .route(
+                "/api/album",
+                openapi_sunset(get(get_album), routes!(get_album), sunset),
+            )
+            .with_state(ctx);
+
+        let res = app
+            .oneshot(
+                axum::http::Request::get("/api/album")
+                    .body(axum::body::Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(res.headers()["deprecation"], "true");
+        assert_eq!(res.headers()["sunset"], sunset);
+        let (_, spec) = get_merged_router().split_for_parts();
+        assert_eq!(
+            serde_json::to_value(&spec.paths.paths["/api/album"].get).unwrap()["deprecated"],
+            true
+        );
+
+        clear_routes();
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_route_with_extensions() {
+        clear_routes();
+        let _ = openapi_with_extensions(
+            get(get_album),
+            routes!(get_album),
+            [
+                ("x-rate-limit", serde_json::json!(100)),
+                ("rate-limit", serde_json::json!(100)),
+            ],
+        );
+
+        let (_, spec) = get_merged_router().split_for_parts();
+        let json = serde_json::to_value(&spec).unwrap();
+        let operation = &json["paths"]["/api/album"]["get"];
+        assert_eq!(operation["x-rate-limit"], 100);
+        assert!(operation.get("rate-limit").is_none());
+
+        clear_routes();
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_named_route_keeps_operation_id() {
+        // Another route with the `operationId`, not named with `openapi_named`
+        #[utoipa::path(get, path = "/api/v2/album", operation_id = "getAlbum", responses((status = 200, description = "Album")))]
+        async fn get_album_v2() {}
+
+        clear_routes();
+        let _ = openapi_named(get(get_album), routes!(get_album), "getAlbum");
+        let _ = openapi_tagged(get(get_stats), routes!(get_stats), "admin");
+        let _ = openapi(get(get_album_v2), routes!(get_album_v2));
+
+        let (_, mut spec) = get_merged_router().split_for_parts();
+        OperationIdStrategy::Custom(|_, operation| {
+            Some(format!("v2_{}", operation.operation_id.as_deref()?))
+        })
+        .apply(&mut spec);
+
+        let operation_id = |path: &str| {
+            spec.paths.paths[path]
+                .get
+                .as_ref()
+                .unwrap()
+                .operation_id
+                .clone()
+        };
+        assert_eq!(operation_id("/api/album").as_deref(), Some("getAlbum"));
+        assert_eq!(
+            operation_id("/api/admin/stats").as_deref(),
+            Some("v2_get_stats")
+        );
+        assert_eq!(
+            operation_id("/api/v2/album").as_deref(),
+            Some("v2_getAlbum")
+        );
+
+        clear_routes();
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_secured_route_accepts_any_jwt_scheme() {
+        clear_routes();
+        let _ = openapi_secured(get(get_album), routes!(get_album));
+        let _ = openapi_scoped(get(list_users), routes!(list_users), &["users:read"]);
+        // Registered after the routes, the schemes are resolved with the spec
+        crate::auth::add_jwt_location(
+            "session_cookie",
+            crate::auth::JWTLocation::Cookie("session".to_string()),
+        );
+
+        let (_, mut spec) = get_merged_router().split_for_parts();
+        utoipa::Modify::modify(&JwtSchemeRequirements, &mut spec);
+        let json = serde_json::to_value(&spec).unwrap();
+        assert_eq!(
+            json["paths"]["/api/album"]["get"]["security"],
+            serde_json::json!([{ "jwt_token": [] }, { "session_cookie": [] }])
+        );
+        assert_eq!(
+            json["paths"]["/api/admin/users"]["get"]["security"],
+            serde_json::json!([{ "jwt_token": ["users:read"] }, { "session_cookie": ["users:read"] }])
+        );
+
+        clear_routes();
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_scoped_route_requires_scopes() {
+        clear_routes();
+        let _ = openapi_scoped(get(get_album), routes!(get_album), &["albums:read"]);
+
+        let (_, spec) = get_merged_router().split_for_parts();
+        let json = serde_json::to_value(&spec).unwrap();
+        assert_eq!(
+            json["paths"]["/api/album"]["get"]["security"],
+            serde_json::json!([{ "jwt_token": ["albums:read"] }])
+        );
+
+        clear_routes();
+    }
+
+    #[test]
+    fn test_merge_external_spec() {
+        let (_, mut spec) = OpenApiRouter::<()>::new()
+            .routes(routes!(get_album))
+            .split_for_parts();
+        let external: OpenApi = serde_json::from_value(serde_json::json!({
+            "openapi": "3.1.0",
+            "info": { "title": "Legacy proxy", "version": "1.0.0" },
+            "paths": {
+                "/api/legacy": {
+                    "get": { "responses": { "200": { "description": "Legacy" } } }
+                },
+                "/api/album": {
+                    "get": {
+                        "operationId": "legacy_album",
+                        "responses": { "200": { "description": "Legacy album" } }
+                    },
+                    "delete": { "responses": { "204": { "description": "Deleted" } } }
+                }
+            }
+        }))
+        .unwrap();
+
+        merge_external_spec(&mut spec, external);
+
+        assert!(spec.paths.paths.contains_key("/api/legacy"));
+        let album = &spec.paths.paths["/api/album"];
+        // The collected operation wins, missing methods are added
+        assert_eq!(
+            album.get.as_ref().unwrap().operation_id.as_deref(),
+            Some("get_album")
+        );
+        assert!(album.delete.is_some());
+        // The info of the external spec is ignored
+        assert_ne!(spec.info.title, "Legacy proxy");
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_deprecated_route() {
+        clear_routes();
+        let _ = openapi_deprecated(get(get_album), routes!(get_album));
+
+        let (_, spec) = get_merged_router().split_for_parts();
+        let json = serde_json::to_value(&spec.paths.paths["/api/album"]).unwrap();
+        assert_eq!(json["get"]["deprecated"], true);
+
+        clear_routes();
+    }
+
+    #[utoipa::path(
+        post,
+        path = "/api/user",
+        request_body = User,
+        responses((status = 200, description = "User created")),
+    )]
+    async fn create_user() {}
+
+    #[test]
+    #[serial_test::serial]
+    fn test_route_with_example() {
+        clear_routes();
+        let example = serde_json::json!({ "id": 1 });
+        let _ = openapi_with_example(
+            axum::routing::post(create_user),
+            routes!(create_user),
+            example.clone(),
+        );
+
+        let (_, spec) = get_merged_router().split_for_parts();
+        let json = serde_json::to_value(&spec.paths.paths["/api/user"]).unwrap();
+        assert_eq!(
+            json["post"]["requestBody"]["content"]["application/json"]["example"],
+            example
+        );
+
+        clear_routes();
+    }
+
+    #[utoipa::path(
+        put,
+        path = "/api/user",
+        request_body(
+            content(
+                (User = "application/json"),
+                (User = "application/x-www-form-urlencoded"),
+            ),
+        ),
+        responses((status = 200, description = "User updated")),
+    )]
+    async fn update_user() {}
+
+    #[test]
+    #[serial_test::serial]
+    fn test_body_required_keeps_content_types() {
+        clear_routes();
+        let _ = openapi_body_required(axum::routing::put(update_user), routes!(update_user));
+        let _ = openapi(get(get_album), routes!(get_album));
+
+        let (_, spec) = get_merged_router().split_for_parts();
+        let json = serde_json::to_value(&spec.paths.paths["/api/user"]).unwrap();
+        let request_body = &json["put"]["requestBody"];
+        assert_eq!(request_body["required"], true);
+        for content_type in ["application/json", "application/x-www-form-urlencoded"] {
+            assert_eq!(
+                request_body["content"][content_type]["schema"]["$ref"],
+                "#/components/schemas/User"
+            );
+        }
+
+        clear_routes();
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn test_hidden_route() {
+        use axum::{body::Body, http::Request};
+        use tower::ServiceExt;
+
+        clear_routes();
+        let ctx = loco_rs::tests_cfg::app::get_app_context().await;
+        let app = axum::Router::new()
+            .route(
+                "/api/album",
+                openapi_hidden(get(get_album), routes!(get_album)),
+            )
+            .with_state(ctx);
+
+        let res = app
+            .oneshot(Request::get("/api/album").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert!(res.status().is_success());
+
+        let (_, spec) = get_merged_router().split_for_parts();
+        assert!(spec.paths.paths.is_empty());
+        assert!(get_hidden_spec().paths.paths.contains_key("/api/album"));
+
+        clear_routes();
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_set_operation_id_strategy_replaces_strategy() {
+        set_operation_id_strategy(OperationIdStrategy::TagPrefixed);
+        assert!(matches!(
+            get_operation_id_strategy(),
+            OperationIdStrategy::TagPrefixed
+        ));
+
+        set_operation_id_strategy(OperationIdStrategy::Derived);
+        assert!(matches!(
+            get_operation_id_strategy(),
+            OperationIdStrategy::Derived
+        ));
+    }
+
+    mod album {
+        #[utoipa::path(get, path = "/api/album", tags = ["album"], responses((status = 200, description = "Album")))]
+        pub async fn get() {}
+    }
+
+    mod artist {
+        #[utoipa::path(get, path = "/api/artist", tags = ["artist"], responses((status = 200, description = "Artist")))]
+        pub async fn get() {}
+    }
+
+    #[test]
+    fn test_tag_prefixed_operation_ids() {
+        let (_, mut spec) = OpenApiRouter::<()>::new()
+            .routes(routes!(album::get))
+            .routes(routes!(artist::get))
+            .split_for_parts();
+
+        let operation_id =
+            |spec: &OpenApi, path: &str| spec.paths.paths[path].get.as_ref()?.operation_id.clone();
+        assert_eq!(
+            operation_id(&spec, "/api/album"),
+            operation_id(&spec, "/api/artist")
+        );
+
+        OperationIdStrategy::TagPrefixed.apply(&mut spec);
+        assert_eq!(
+            operation_id(&spec, "/api/album"),
+            Some("album_get".to_string())
+        );
+        assert_eq!(
+            operation_id(&spec, "/api/artist"),
+            Some("artist_get".to_string())
+        );
+
+        // Applying the strategy again doesn't prefix twice
+        OperationIdStrategy::TagPrefixed.apply(&mut spec);
+        assert_eq!(
+            operation_id(&spec, "/api/album"),
+            Some("album_get".to_string())
+        );
+    }
+
+    #[test]
+    fn test_check_refs() {
+        use utoipa::openapi::{
+            path::{HttpMethod, OperationBuilder, PathItem},
+            Content, Ref, ResponseBuilder,
+        };
+
+        let (_, mut spec) = OpenApiRouter::<()>::new()
+            .routes(routes!(get_album))
+            .split_for_parts();
+        assert!(check_refs("main", &spec).is_ok());
+
+        let response = ResponseBuilder::new()
+            .description("Album")
+            .content(
+                "application/json",
+                Content::new(Some(Ref::from_schema_name("Album"))),
+            )
+            .build();
+        spec.paths.paths.insert(
+            "/api/albums".to_string(),
+            PathItem::new(
+                HttpMethod::Get,
+                OperationBuilder::new().response("200", response).build(),
+            ),
+        );
+
+        let err = check_refs("main", &spec).unwrap_err();
+        assert!(err.to_string().contains("#/components/schemas/Album"));
+    }
+}
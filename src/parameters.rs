@@ -0,0 +1,99 @@
+use std::collections::BTreeMap;
+use std::sync::{Mutex, OnceLock};
+
+use utoipa::{
+    openapi::{path::Parameter, OpenApi},
+    Modify,
+};
+
+use crate::members;
+
+static SHARED_PARAMETERS: OnceLock<Mutex<BTreeMap<String, Parameter>>> = OnceLock::new();
+
+fn get_shared_parameters() -> &'static Mutex<BTreeMap<String, Parameter>> {
+    SHARED_PARAMETERS.get_or_init(|| Mutex::new(BTreeMap::new()))
+}
+
+/// Register a reusable parameter, documented under `components.parameters`
+/// and referenced as `#/components/parameters/{name}`
+/// ```rust
+/// use by_loco_openapi::prelude::*;
+/// use utoipa::openapi::path::{ParameterBuilder, ParameterIn};
+///
+/// register_shared_parameter(
+///     "TenantId",
+///     ParameterBuilder::new()
+///         .name("X-Tenant-Id")
+///         .parameter_in(ParameterIn::Header)
+///         .build(),
+/// );
+/// ```
+pub fn register_shared_parameter(name: &str, parameter: Parameter) {
+    if let Ok(mut parameters) = get_shared_parameters().lock() {
+        parameters.insert(name.to_string(), parameter);
+    }
+}
+
+/// Clears the parameters registered with [`register_shared_parameter`], mostly used for testing
+pub fn clear_shared_parameters() {
+    if let Ok(mut parameters) = get_shared_parameters().lock() {
+        parameters.clear();
+    }
+}
+
+/// Adds the registered parameters to `components.parameters`
+pub struct SharedParameters;
+
+impl Modify for SharedParameters {
+    fn modify(&self, openapi: &mut OpenApi) {
+        let Ok(parameters) = get_shared_parameters().lock() else {
+            return;
+        };
+        if parameters.is_empty() {
+            return;
+        }
+
+        let components = openapi.components.get_or_insert_with(Default::default);
+        let Some(components_parameters) =
+            members::object_mut(&mut components.extensions, "parameters")
+        else {
+            return;
+        };
+        for (name, parameter) in parameters.iter() {
+            if let Ok(value) = serde_json::to_value(parameter) {
+                components_parameters.insert(name.clone(), value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use utoipa::openapi::path::{ParameterBuilder, ParameterIn};
+
+    use super::*;
+
+    #[test]
+    #[serial_test::serial]
+    fn test_shared_parameter_in_components() {
+        register_shared_parameter(
+            "TenantId",
+            ParameterBuilder::new()
+                .name("X-Tenant-Id")
+                .parameter_in(ParameterIn::Header)
+                .build(),
+        );
+
+        let mut spec = OpenApi::default();
+        SharedParameters.modify(&mut spec);
+
+        let json = serde_json::to_value(&spec).unwrap();
+        assert_eq!(
+            json["components"]["parameters"]["TenantId"]["name"],
+            "X-Tenant-Id"
+        );
+        assert_eq!(json["components"]["parameters"]["TenantId"]["in"], "header");
+
+        clear_shared_parameters();
+    }
+}
@@ -0,0 +1,222 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use axum::{http::header, routing::get, Router as AxumRouter};
+use serde_json::{json, Value};
+use tower_http::compression::CompressionLayer;
+use utoipa::openapi::{path::Operation, OpenApi, RefOr};
+
+use crate::openapi::operations;
+use crate::utils::{CachedSpec, SpecRendering};
+
+const COLLECTION_SCHEMA: &str =
+    "https://schema.getpostman.com/json/collection/v2.1.0/collection.json";
+const BASE_URL_VARIABLE: &str = "baseUrl";
+
+/// Convert the spec into a Postman v2.1 collection
+///
+/// Every operation is a request in the folder of its first tag, untagged
+/// operations are at the top level. The URLs start with the `{{baseUrl}}`
+/// variable of the collection, set to the first server of the spec, and
+/// path parameters are Postman `:name` variables. JSON request bodies use
+/// the example of the spec, when there is one
+#[must_use]
+pub fn postman_collection(spec: &OpenApi) -> Value {
+    let mut folders: BTreeMap<&str, Vec<Value>> = BTreeMap::new();
+    let mut items = Vec::new();
+    for (method, path, operation) in operations(&spec.paths) {
+        let request = postman_request(method, path, operation);
+        match operation.tags.as_ref().and_then(|tags| tags.first()) {
+            Some(tag) => folders.entry(tag.as_str()).or_default().push(request),
+            None => items.push(request),
+        }
+    }
+    let folders = folders
+        .into_iter()
+        .map(|(tag, requests)| json!({ "name": tag, "item": requests }));
+
+    let base_url = spec
+        .servers
+        .as_ref()
+        .and_then(|servers| servers.first())
+        .map_or("", |server| server.url.as_str());
+
+    let mut collection = json!({
+        "info": {
+            "name": spec.info.title,
+            "schema": COLLECTION_SCHEMA,
+        },
+        "item": folders.chain(items).collect::<Vec<_>>(),
+        "variable": [{ "key": BASE_URL_VARIABLE, "value": base_url.trim_end_matches('/') }],
+    });
+    if let Some(description) = &spec.info.description {
+        collection["info"]["description"] = description.as_str().into();
+    }
+    collection
+}
+
+fn postman_request(method: &str, path: &str, operation: &Operation) -> Value {
+    let segments = path
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| {
+            segment
+                .strip_prefix('{')
+                .and_then(|name| name.strip_suffix('}'))
+                .map_or_else(|| segment.to_string(), |name| format!(":{name}"))
+        })
+        .collect::<Vec<_>>();
+
+    let mut request = json!({
+        "method": method,
+        "header": [],
+        "url": {
+            "raw": format!("{{{{{BASE_URL_VARIABLE}}}}}/{}", segments.join("/")),
+            "host": [format!("{{{{{BASE_URL_VARIABLE}}}}}")],
+            "path": segments,
+        },
+    });
+    if let Some(description) = &operation.description {
+        request["description"] = description.as_str().into();
+    }
+    if let Some(example) = json_body_example(operation) {
+        request["header"] = json!([{ "key": "Content-Type", "value": "application/json" }]);
+        request["body"] = json!({
+            "mode": "raw",
+            "raw": serde_json::to_string_pretty(&example).unwrap_or_default(),
+            "options": { "raw": { "language": "json" } },
+        });
+    }
+
+    let name = operation
+        .summary
+        .clone()
+        .or_else(|| operation.operation_id.clone())
+        .unwrap_or_else(|| format!("{method} {path}"));
+    json!({ "name": name, "request": request })
+}
+
+/// The example of the JSON request body, or its first named example
+fn json_body_example(operation: &Operation) -> Option<Value> {
+    let content = operation
+        .request_body
+        .as_ref()?
+        .content
+        .get("application/json")?;
+    content.example.clone().or_else(|| {
+        content.examples.values().find_map(|example| match example {
+            RefOr::T(example) => example.value.clone(),
+            RefOr::Ref(_) => None,
+        })
+    })
+}
+
+/// Adds an endpoint serving the spec as a Postman v2.1 collection, to import
+/// it into Postman
+///
+/// The collection is converted on the first request, and again after the
+/// spec is replaced
+pub fn add_postman_endpoint<T>(
+    app: AxumRouter<T>,
+    spec: Arc<CachedSpec>,
+    url: &str,
+) -> AxumRouter<T>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    let body = SpecRendering::new(spec, |spec| postman_collection(spec.spec()).to_string());
+    app.route(
+        url,
+        get(move || {
+            let body = body.get();
+            async move { ([(header::CONTENT_TYPE, "application/json")], body) }
+        })
+        .layer(CompressionLayer::new()),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use utoipa::{OpenApi as _, ToSchema};
+    use utoipa_axum::{router::OpenApiRouter, routes};
+
+    use super::*;
+
+    #[allow(dead_code)]
+    #[derive(ToSchema, serde::Serialize)]
+    struct NewAlbum {
+        title: String,
+    }
+
+    #[utoipa::path(
+        post,
+        path = "/api/albums",
+        tag = "album",
+        summary = "Create album",
+        request_body(content = NewAlbum, example = json!({"title": "Blue Train"})),
+        responses((status = 200, description = "Album")),
+    )]
+    async fn create_album() {}
+
+    #[utoipa::path(
+        get,
+        path = "/api/albums/{id}",
+        tag = "album",
+        params(("id" = u32, Path, description = "Album id")),
+        responses((status = 200, description = "Album")),
+    )]
+    async fn get_album() {}
+
+    #[utoipa::path(get, path = "/_health", responses((status = 200, description = "Healthy")))]
+    async fn health() {}
+
+    #[derive(utoipa::OpenApi)]
+    #[openapi(info(title = "Albums API"), servers((url = "https://api.example.com/")))]
+    struct ApiDoc;
+
+    #[test]
+    fn test_postman_collection() {
+        let (_, spec) = OpenApiRouter::<()>::with_openapi(ApiDoc::openapi())
+            .routes(routes!(create_album))
+            .routes(routes!(get_album))
+            .routes(routes!(health))
+            .split_for_parts();
+
+        let collection = postman_collection(&spec);
+
+        assert_eq!(collection["info"]["name"], "Albums API");
+        assert_eq!(collection["info"]["schema"], COLLECTION_SCHEMA);
+        assert_eq!(
+            collection["variable"],
+            json!([{ "key": "baseUrl", "value": "https://api.example.com" }])
+        );
+
+        let items = collection["item"].as_array().unwrap();
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0]["name"], "album");
+        let requests = items[0]["item"].as_array().unwrap();
+        assert_eq!(requests.len(), 2);
+
+        let create = &requests[0];
+        assert_eq!(create["name"], "Create album");
+        assert_eq!(create["request"]["method"], "POST");
+        assert_eq!(create["request"]["url"]["raw"], "{{baseUrl}}/api/albums");
+        assert_eq!(create["request"]["body"]["mode"], "raw");
+        assert_eq!(
+            serde_json::from_str::<Value>(create["request"]["body"]["raw"].as_str().unwrap())
+                .unwrap(),
+            json!({"title": "Blue Train"})
+        );
+
+        let get = &requests[1];
+        assert_eq!(get["request"]["url"]["raw"], "{{baseUrl}}/api/albums/:id");
+        assert_eq!(
+            get["request"]["url"]["path"],
+            json!(["api", "albums", ":id"])
+        );
+        assert!(get["request"].get("body").is_none());
+
+        assert_eq!(items[1]["name"], "health");
+        assert_eq!(items[1]["request"]["url"]["host"], json!(["{{baseUrl}}"]));
+    }
+}
@@ -1,5 +1,39 @@
-pub use super::auth::{set_jwt_location, SecurityAddon};
-pub use super::openapi::openapi;
+//! Everything needed to document routes and configure the initializer
+//! ```rust
+//! use by_loco_openapi::prelude::*;
+//!
+//! # fn main() -> loco_rs::Result<()> {
+//! let config: OpenAPIConfig = serde_json::from_value(serde_json::json!({ "well_known": true }))?;
+//! set_openapi_config(Some(config))?;
+//!
+//! let config = get_openapi_config();
+//! let views: Vec<&OpenAPIType> = config
+//!     .map(OpenAPIConfig::views)
+//!     .unwrap_or_default();
+//! assert!(views.is_empty());
+//! # Ok(())
+//! # }
+//! ```
+
+pub use super::auth::{
+    add_api_key_scheme, add_jwt_location, add_jwt_scope, set_bearer_format, set_jwt_location,
+    ApiKeyLocation, JWTLocation, JwtSecurityAddon, SecurityAddon,
+};
+pub use super::callbacks::add_callback;
+pub use super::config::{get_openapi_config, set_openapi_config, OpenAPIConfig, OpenAPIType};
+pub use super::examples::set_schema_example;
+pub use super::links::add_response_link;
+pub use super::openapi::{
+    add_webhook, openapi, openapi_body_required, openapi_deprecated, openapi_grouped,
+    openapi_hidden, openapi_named, openapi_scoped, openapi_secured, openapi_sunset, openapi_tagged,
+    openapi_with_example, openapi_with_extensions, set_operation_id_strategy, OperationIdStrategy,
+    RouteCollector,
+};
+pub use super::parameters::register_shared_parameter;
+pub use super::responses::{
+    set_default_error_response, set_global_response_headers, set_tag_response_headers,
+};
+pub use super::schemas::{deprecate_schema_field, register_schema};
 pub use utoipa;
 pub use utoipa::{path, schema, OpenApi, ToSchema};
 pub use utoipa_axum::{router::OpenApiRouter, routes};
@@ -0,0 +1,188 @@
+use std::collections::{HashMap, VecDeque};
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::{header, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    Router as AxumRouter,
+};
+
+/// Most clients tracked at once, the client with the oldest window is
+/// evicted to make room for a new one
+const MAX_CLIENTS: usize = 1024;
+
+/// Per-IP fixed window rate limiter for the `OpenAPI` visualizer and spec
+/// routes
+///
+/// Clients are identified by the `ConnectInfo` of the connection, requests
+/// without it share a single window. At most 1024 clients are tracked, so
+/// memory stays bounded when many addresses scrape the docs
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    limit: u32,
+    window: Duration,
+    max_clients: usize,
+    clients: Arc<Mutex<Clients>>,
+}
+
+#[derive(Debug, Default)]
+struct Clients {
+    windows: HashMap<Option<IpAddr>, Window>,
+    /// The clients by the start of their window, oldest first
+    order: VecDeque<(Option<IpAddr>, Instant)>,
+}
+
+#[derive(Debug)]
+struct Window {
+    start: Instant,
+    count: u32,
+}
+
+impl RateLimiter {
+    #[must_use]
+    pub fn per_minute(requests: u32) -> Self {
+        Self {
+            limit: requests,
+            window: Duration::from_secs(60),
+            max_clients: MAX_CLIENTS,
+            clients: Arc::default(),
+        }
+    }
+
+    /// Count a request of the client, returns the time until the window
+    /// resets when the limit is exceeded
+    fn check(&self, client: Option<IpAddr>) -> Result<(), Duration> {
+        let Ok(mut clients) = self.clients.lock() else {
+            return Ok(());
+        };
+        let Clients { windows, order } = &mut *clients;
+        let now = Instant::now();
+        // Windows expire oldest first, a client starts a new window after
+        // its previous one was dropped
+        while let Some((oldest, _)) = order
+            .front()
+            .filter(|(_, start)| now.duration_since(*start) >= self.window)
+        {
+            windows.remove(oldest);
+            order.pop_front();
+        }
+
+        if !windows.contains_key(&client) {
+            if windows.len() >= self.max_clients {
+                if let Some((oldest, _)) = order.pop_front() {
+                    windows.remove(&oldest);
+                }
+            }
+            windows.insert(
+                client,
+                Window {
+                    start: now,
+                    count: 0,
+                },
+            );
+            order.push_back((client, now));
+        }
+        let Some(window) = windows.get_mut(&client) else {
+            return Ok(());
+        };
+        let elapsed = now.duration_since(window.start);
+        if window.count >= self.limit {
+            return Err(self.window.saturating_sub(elapsed));
+        }
+        window.count += 1;
+        Ok(())
+    }
+
+    /// Rate limit all routes of the router
+    pub fn layer<S>(self, router: AxumRouter<S>) -> AxumRouter<S>
+    where
+        S: Clone + Send + Sync + 'static,
+    {
+        router.layer(middleware::from_fn_with_state(self, limit))
+    }
+}
+
+async fn limit(State(limiter): State<RateLimiter>, request: Request, next: Next) -> Response {
+    let client = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip());
+
+    match limiter.check(client) {
+        Ok(()) => next.run(request).await,
+        Err(retry_after) => (
+            StatusCode::TOO_MANY_REQUESTS,
+            [(
+                header::RETRY_AFTER,
+                retry_after.as_secs().max(1).to_string(),
+            )],
+        )
+            .into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{body::Body, routing::get};
+    use tower::ServiceExt;
+
+    use super::*;
+
+    fn request_from(ip: [u8; 4]) -> Request {
+        let mut request = Request::get("/docs").body(Body::empty()).unwrap();
+        request
+            .extensions_mut()
+            .insert(ConnectInfo(SocketAddr::from((ip, 1234))));
+        request
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_exceeded() {
+        let app: AxumRouter = RateLimiter::per_minute(3)
+            .layer(AxumRouter::new().route("/docs", get(|| async { "docs" })));
+
+        for _ in 0..3 {
+            let res = app
+                .clone()
+                .oneshot(request_from([10, 0, 0, 1]))
+                .await
+                .unwrap();
+            assert_eq!(res.status(), StatusCode::OK);
+        }
+        let res = app
+            .clone()
+            .oneshot(request_from([10, 0, 0, 1]))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert!(res.headers().contains_key(header::RETRY_AFTER));
+
+        // Other clients have their own window
+        let res = app.oneshot(request_from([10, 0, 0, 2])).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+
+    #[test]
+    fn test_clients_capped() {
+        let limiter = RateLimiter {
+            max_clients: 2,
+            ..RateLimiter::per_minute(1)
+        };
+        let client = |last: u8| Some(IpAddr::from([10, 0, 0, last]));
+
+        for last in 1..=3 {
+            assert!(limiter.check(client(last)).is_ok());
+        }
+        let clients = limiter.clients.lock().unwrap();
+        assert_eq!(clients.windows.len(), 2);
+        assert_eq!(clients.order.len(), 2);
+        // The oldest client was evicted for the newest one
+        assert!(!clients.windows.contains_key(&client(1)));
+        drop(clients);
+        assert!(limiter.check(client(3)).is_err());
+    }
+}
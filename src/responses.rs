@@ -0,0 +1,279 @@
+use std::collections::BTreeMap;
+use std::sync::{Mutex, OnceLock};
+
+use utoipa::{
+    openapi::{header::Header, ContentBuilder, OpenApi, Ref, RefOr, Response, ResponseBuilder},
+    Modify,
+};
+
+use crate::openapi::for_each_operation;
+
+static DEFAULT_RESPONSES: OnceLock<Mutex<BTreeMap<String, String>>> = OnceLock::new();
+
+static RESPONSE_HEADERS: OnceLock<Mutex<Vec<ResponseHeader>>> = OnceLock::new();
+
+fn get_default_responses() -> &'static Mutex<BTreeMap<String, String>> {
+    DEFAULT_RESPONSES.get_or_init(|| Mutex::new(BTreeMap::new()))
+}
+
+/// A header documented on the responses of all operations, or of the
+/// operations with a tag
+struct ResponseHeader {
+    tag: Option<String>,
+    name: String,
+    header: Header,
+}
+
+fn get_response_headers() -> &'static Mutex<Vec<ResponseHeader>> {
+    RESPONSE_HEADERS.get_or_init(|| Mutex::new(Vec::new()))
+}
+
+/// Document a default error response on every operation, so a consistent
+/// error envelope doesn't have to be listed in each `#[utoipa::path]`
+///
+/// `status_pattern` is a status code or range like `4XX`, `schema_ref` the
+/// name of a component schema or a full `$ref`. Operations documenting the
+/// status themselves keep their own response
+/// ```rust
+/// use by_loco_openapi::prelude::*;
+///
+/// set_default_error_response("4XX", "ErrorResponse");
+/// set_default_error_response("5XX", "ErrorResponse");
+/// ```
+pub fn set_default_error_response(status_pattern: &str, schema_ref: &str) {
+    if let Ok(mut responses) = get_default_responses().lock() {
+        responses.insert(status_pattern.to_string(), schema_ref.to_string());
+    }
+}
+
+/// Clears the responses set with [`set_default_error_response`], mostly used for testing
+pub fn clear_default_error_responses() {
+    if let Ok(mut responses) = get_default_responses().lock() {
+        responses.clear();
+    }
+}
+
+/// Document headers on every response of every operation, e.g. the
+/// `X-Request-Id` of a middleware, instead of listing them in each
+/// `#[utoipa::path]`
+///
+/// Responses documenting a header themselves keep their own
+/// ```rust
+/// use by_loco_openapi::prelude::*;
+/// use utoipa::openapi::{header::HeaderBuilder, schema::{ObjectBuilder, Type}};
+///
+/// set_global_response_headers([(
+///     "X-Request-Id",
+///     HeaderBuilder::new()
+///         .schema(ObjectBuilder::new().schema_type(Type::String))
+///         .description(Some("Id of the request, for the logs"))
+///         .build(),
+/// )]);
+/// ```
+pub fn set_global_response_headers<I, N>(headers: I)
+where
+    I: IntoIterator<Item = (N, Header)>,
+    N: Into<String>,
+{
+    add_response_headers(None, headers);
+}
+
+/// Document headers on every response of the operations with the tag, see
+/// [`set_global_response_headers`]
+pub fn set_tag_response_headers<I, N>(tag: &str, headers: I)
+where
+    I: IntoIterator<Item = (N, Header)>,
+    N: Into<String>,
+{
+    add_response_headers(Some(tag.to_string()), headers);
+}
+
+/// Clears the headers set with [`set_global_response_headers`] and
+/// [`set_tag_response_headers`], mostly used for testing
+pub fn clear_response_headers() {
+    if let Ok(mut headers) = get_response_headers().lock() {
+        headers.clear();
+    }
+}
+
+fn add_response_headers<I, N>(tag: Option<String>, headers: I)
+where
+    I: IntoIterator<Item = (N, Header)>,
+    N: Into<String>,
+{
+    if let Ok(mut response_headers) = get_response_headers().lock() {
+        response_headers.extend(headers.into_iter().map(|(name, header)| ResponseHeader {
+            tag: tag.clone(),
+            name: name.into(),
+            header,
+        }));
+    }
+}
+
+fn error_response(schema_ref: &str) -> Response {
+    let schema = if schema_ref.starts_with('#') {
+        Ref::new(schema_ref)
+    } else {
+        Ref::from_schema_name(schema_ref)
+    };
+    ResponseBuilder::new()
+        .description("Error")
+        .content(
+            "application/json",
+            ContentBuilder::new().schema(Some(schema)).build(),
+        )
+        .build()
+}
+
+/// Adds the default error responses to every operation
+pub struct DefaultErrorResponses;
+
+impl Modify for DefaultErrorResponses {
+    fn modify(&self, openapi: &mut OpenApi) {
+        let Ok(responses) = get_default_responses().lock() else {
+            return;
+        };
+        if responses.is_empty() {
+            return;
+        }
+
+        for_each_operation(&mut openapi.paths, |_, operation| {
+            for (status, schema_ref) in responses.iter() {
+                operation
+                    .responses
+                    .responses
+                    .entry(status.clone())
+                    .or_insert_with(|| RefOr::T(error_response(schema_ref)));
+            }
+        });
+    }
+}
+
+/// Adds the global and tag response headers to the responses of every
+/// operation
+pub struct GlobalResponseHeaders;
+
+impl Modify for GlobalResponseHeaders {
+    fn modify(&self, openapi: &mut OpenApi) {
+        let Ok(headers) = get_response_headers().lock() else {
+            return;
+        };
+        if headers.is_empty() {
+            return;
+        }
+
+        for_each_operation(&mut openapi.paths, |_, operation| {
+            let tags = operation.tags.clone().unwrap_or_default();
+            let headers = headers
+                .iter()
+                .filter(|header| header.tag.as_ref().is_none_or(|tag| tags.contains(tag)))
+                .collect::<Vec<_>>();
+            for response in operation.responses.responses.values_mut() {
+                // Referenced responses are documented once in the components
+                let RefOr::T(response) = response else {
+                    continue;
+                };
+                for header in &headers {
+                    response
+                        .headers
+                        .entry(header.name.clone())
+                        .or_insert_with(|| header.header.clone());
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use utoipa_axum::{router::OpenApiRouter, routes};
+
+    use super::*;
+
+    #[utoipa::path(
+        get,
+        path = "/api/album",
+        responses(
+            (status = 200, description = "Album"),
+            (status = "5XX", description = "Album service unavailable"),
+        ),
+    )]
+    async fn get_album() {}
+
+    #[utoipa::path(
+        get,
+        path = "/api/artist",
+        tag = "artist",
+        responses(
+            (
+                status = 200,
+                description = "Artist",
+                headers(("X-Request-Id" = String, description = "Traced")),
+            ),
+        ),
+    )]
+    async fn get_artist() {}
+
+    #[test]
+    #[serial_test::serial]
+    fn test_global_response_headers() {
+        let header = |description: &str| {
+            utoipa::openapi::header::HeaderBuilder::new()
+                .description(Some(description))
+                .build()
+        };
+        set_global_response_headers([("X-Request-Id", header("Id of the request"))]);
+        set_tag_response_headers(
+            "artist",
+            [("X-RateLimit-Remaining", header("Requests left"))],
+        );
+
+        let (_, mut spec) = OpenApiRouter::<()>::new()
+            .routes(routes!(get_album))
+            .routes(routes!(get_artist))
+            .split_for_parts();
+        GlobalResponseHeaders.modify(&mut spec);
+
+        let json = serde_json::to_value(&spec).unwrap();
+        let album_headers = &json["paths"]["/api/album"]["get"]["responses"]["200"]["headers"];
+        assert_eq!(
+            album_headers["X-Request-Id"]["description"],
+            "Id of the request"
+        );
+        assert!(album_headers.get("X-RateLimit-Remaining").is_none());
+
+        let artist_headers = &json["paths"]["/api/artist"]["get"]["responses"]["200"]["headers"];
+        assert_eq!(
+            artist_headers["X-RateLimit-Remaining"]["description"],
+            "Requests left"
+        );
+        // Headers documented by the response are kept
+        assert_eq!(artist_headers["X-Request-Id"]["description"], "Traced");
+
+        clear_response_headers();
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_default_error_response() {
+        set_default_error_response("4XX", "ErrorResponse");
+        set_default_error_response("5XX", "ErrorResponse");
+
+        let (_, mut spec) = OpenApiRouter::<()>::new()
+            .routes(routes!(get_album))
+            .split_for_parts();
+        DefaultErrorResponses.modify(&mut spec);
+
+        let json = serde_json::to_value(&spec).unwrap();
+        let responses = &json["paths"]["/api/album"]["get"]["responses"];
+        assert_eq!(
+            responses["4XX"]["content"]["application/json"]["schema"]["$ref"],
+            "#/components/schemas/ErrorResponse"
+        );
+        // Responses documented by the operation are kept
+        assert_eq!(responses["5XX"]["description"], "Album service unavailable");
+        assert_eq!(responses["200"]["description"], "Album");
+
+        clear_default_error_responses();
+    }
+}
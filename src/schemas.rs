@@ -0,0 +1,198 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::{Mutex, OnceLock};
+
+use utoipa::{
+    openapi::{schema::Schema, Deprecated, OpenApi, RefOr},
+    Modify, ToSchema,
+};
+
+static REGISTERED_SCHEMAS: OnceLock<Mutex<BTreeMap<String, RefOr<Schema>>>> = OnceLock::new();
+static DEPRECATED_FIELDS: OnceLock<Mutex<BTreeSet<(String, String)>>> = OnceLock::new();
+
+fn get_registered_schemas() -> &'static Mutex<BTreeMap<String, RefOr<Schema>>> {
+    REGISTERED_SCHEMAS.get_or_init(|| Mutex::new(BTreeMap::new()))
+}
+
+fn get_deprecated_fields() -> &'static Mutex<BTreeSet<(String, String)>> {
+    DEPRECATED_FIELDS.get_or_init(|| Mutex::new(BTreeSet::new()))
+}
+
+/// Register a shared model as a component schema, so it's in the spec even
+/// when no route references it, e.g. when it's only used by webhooks or
+/// links. The schemas it references are registered as well
+/// ```rust
+/// use by_loco_openapi::prelude::*;
+///
+/// #[derive(serde::Serialize, ToSchema)]
+/// struct AlbumEvent {
+///     title: String,
+/// }
+///
+/// register_schema::<AlbumEvent>();
+/// ```
+pub fn register_schema<T: ToSchema>() {
+    let mut schemas = vec![(T::name().into_owned(), T::schema())];
+    T::schemas(&mut schemas);
+    if let Ok(mut registered) = get_registered_schemas().lock() {
+        registered.extend(schemas);
+    }
+}
+
+/// Clears the schemas registered with [`register_schema`], mostly used for testing
+pub fn clear_registered_schemas() {
+    if let Ok(mut registered) = get_registered_schemas().lock() {
+        registered.clear();
+    }
+}
+
+/// Adds the registered schemas to the components, schemas collected from
+/// the routes with the same name are kept
+pub struct RegisteredSchemas;
+
+impl Modify for RegisteredSchemas {
+    fn modify(&self, openapi: &mut OpenApi) {
+        let Ok(registered) = get_registered_schemas().lock() else {
+            return;
+        };
+        if registered.is_empty() {
+            return;
+        }
+
+        let components = openapi.components.get_or_insert_with(Default::default);
+        for (name, schema) in registered.iter() {
+            components
+                .schemas
+                .entry(name.clone())
+                .or_insert_with(|| schema.clone());
+        }
+    }
+}
+
+/// Mark a property of a component schema as deprecated, e.g. a field of a
+/// model from another crate that can't take `#[schema(deprecated)]`
+///
+/// Only inline object and array properties can be marked, a property
+/// referencing another schema is left as is with a warning
+/// ```rust
+/// use by_loco_openapi::prelude::*;
+///
+/// deprecate_schema_field("Album", "rating");
+/// ```
+pub fn deprecate_schema_field(schema: &str, field: &str) {
+    if let Ok(mut fields) = get_deprecated_fields().lock() {
+        fields.insert((schema.to_string(), field.to_string()));
+    }
+}
+
+/// Clears the fields set with [`deprecate_schema_field`], mostly used for testing
+pub fn clear_deprecated_fields() {
+    if let Ok(mut fields) = get_deprecated_fields().lock() {
+        fields.clear();
+    }
+}
+
+/// Marks the properties set with [`deprecate_schema_field`] deprecated
+pub struct DeprecatedFields;
+
+impl Modify for DeprecatedFields {
+    fn modify(&self, openapi: &mut OpenApi) {
+        let Ok(fields) = get_deprecated_fields().lock() else {
+            return;
+        };
+        let Some(components) = openapi.components.as_mut() else {
+            return;
+        };
+
+        for (schema, field) in fields.iter() {
+            let property = match components.schemas.get_mut(schema) {
+                Some(RefOr::T(Schema::Object(object))) => object.properties.get_mut(field),
+                _ => None,
+            };
+            match property {
+                Some(RefOr::T(Schema::Object(object))) => {
+                    object.deprecated = Some(Deprecated::True);
+                }
+                Some(RefOr::T(Schema::Array(array))) => array.deprecated = Some(Deprecated::True),
+                Some(_) => tracing::warn!(
+                    schema,
+                    field,
+                    "property can't be marked deprecated, it's not an inline object or array schema"
+                ),
+                // The schema isn't in every spec, e.g. of route groups
+                None => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Serialize;
+
+    use super::*;
+
+    #[allow(dead_code)]
+    #[derive(Serialize, ToSchema)]
+    struct Artist {
+        name: String,
+    }
+
+    #[allow(dead_code)]
+    #[derive(Serialize, ToSchema)]
+    struct AlbumEvent {
+        title: String,
+        artist: Artist,
+    }
+
+    #[allow(dead_code)]
+    #[derive(Serialize, ToSchema)]
+    struct Album {
+        title: String,
+        #[schema(deprecated)]
+        year: u16,
+        rating: u8,
+    }
+
+    #[utoipa::path(
+        get,
+        path = "/api/album",
+        responses((status = 200, description = "Album", body = Album)),
+    )]
+    async fn get_album() {}
+
+    #[test]
+    #[serial_test::serial]
+    fn test_deprecated_fields() {
+        deprecate_schema_field("Album", "rating");
+
+        let (_, mut spec) = utoipa_axum::router::OpenApiRouter::<()>::new()
+            .merge(
+                utoipa_axum::router::OpenApiRouter::new().routes(utoipa_axum::routes!(get_album)),
+            )
+            .split_for_parts();
+        DeprecatedFields.modify(&mut spec);
+
+        let json = serde_json::to_value(&spec).unwrap();
+        let properties = &json["components"]["schemas"]["Album"]["properties"];
+        assert_eq!(properties["year"]["deprecated"], true);
+        assert_eq!(properties["rating"]["deprecated"], true);
+        assert!(properties["title"].get("deprecated").is_none());
+
+        clear_deprecated_fields();
+    }
+
+    #[test]
+    #[serial_test::serial]
+    fn test_registered_schema_in_components() {
+        register_schema::<AlbumEvent>();
+
+        let mut spec = OpenApi::default();
+        RegisteredSchemas.modify(&mut spec);
+
+        let schemas = &spec.components.unwrap().schemas;
+        assert!(schemas.contains_key("AlbumEvent"));
+        assert!(schemas.contains_key("Artist"));
+
+        clear_registered_schemas();
+    }
+}
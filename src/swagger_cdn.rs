@@ -0,0 +1,118 @@
+use axum::{response::Html, routing::get, Router as AxumRouter};
+use serde_json::{Map, Value};
+
+/// Default base URL of the `swagger-ui-dist` assets
+pub const DEFAULT_CDN_URL: &str = "https://unpkg.com/swagger-ui-dist@5";
+
+/// HTML page loading the minified Swagger UI bundle from a CDN
+///
+/// `$cdnUrl` is replaced with the base URL of the assets and `$options` with
+/// the Swagger UI options
+const DEFAULT_HTML: &str = r##"<!doctype html>
+<html lang="en">
+  <head>
+    <meta charset="utf-8" />
+    <meta name="viewport" content="width=device-width, initial-scale=1" />
+    <title>Swagger UI</title>
+    <link rel="stylesheet" href="$cdnUrl/swagger-ui.css" />
+  </head>
+  <body>
+    <div id="swagger-ui"></div>
+    <script src="$cdnUrl/swagger-ui-bundle.js" crossorigin></script>
+    <script>
+      window.onload = () => {
+        window.ui = SwaggerUIBundle($options);
+      };
+    </script>
+  </body>
+</html>
+"##;
+
+/// Swagger UI served from CDN-hosted assets instead of the assets embedded
+/// by `utoipa-swagger-ui`
+#[derive(Debug, Clone)]
+pub struct SwaggerCdn {
+    /// URL for where to host the Swagger UI page, example: /swagger
+    url: String,
+    /// URL Swagger UI loads the JSON spec from
+    spec_url: String,
+    /// Base URL of the `swagger-ui-dist` assets
+    cdn_url: String,
+    /// Additional Swagger UI options
+    ui_config: Map<String, Value>,
+}
+
+impl SwaggerCdn {
+    #[must_use]
+    pub fn with_url(
+        url: impl Into<String>,
+        spec_url: impl Into<String>,
+        cdn_url: impl Into<String>,
+    ) -> Self {
+        Self {
+            url: url.into(),
+            spec_url: spec_url.into(),
+            cdn_url: cdn_url.into(),
+            ui_config: Map::new(),
+        }
+    }
+
+    /// Pass the options of the object to Swagger UI, e.g. `docExpansion`
+    #[must_use]
+    pub fn ui_config(mut self, ui_config: &Value) -> Self {
+        if let Value::Object(ui_config) = ui_config {
+            self.ui_config = ui_config.clone();
+        }
+        self
+    }
+
+    /// Render the Swagger UI HTML page
+    #[must_use]
+    pub fn to_html(&self) -> String {
+        let mut options = self.ui_config.clone();
+        options.insert("url".to_string(), self.spec_url.clone().into());
+        options.insert("dom_id".to_string(), "#swagger-ui".into());
+        DEFAULT_HTML
+            .replace("$cdnUrl", self.cdn_url.trim_end_matches('/'))
+            .replace("$options", &Value::Object(options).to_string())
+    }
+}
+
+impl<S> From<SwaggerCdn> for AxumRouter<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    fn from(swagger: SwaggerCdn) -> Self {
+        let html = swagger.to_html();
+        Self::new().route(&swagger.url, get(move || async move { Html(html) }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_html_references_cdn() {
+        let html = SwaggerCdn::with_url(
+            "/swagger",
+            "/swagger/openapi.json",
+            "https://cdn.example.com/swagger-ui/",
+        )
+        .to_html();
+        assert!(html.contains(r#"src="https://cdn.example.com/swagger-ui/swagger-ui-bundle.js""#));
+        assert!(html.contains(r#"href="https://cdn.example.com/swagger-ui/swagger-ui.css""#));
+        assert!(html.contains(r#""url":"/swagger/openapi.json""#));
+        assert!(!html.contains("$cdnUrl"));
+    }
+
+    #[test]
+    fn test_html_passes_ui_config() {
+        let html = SwaggerCdn::with_url("/swagger", "/swagger/openapi.json", DEFAULT_CDN_URL)
+            .ui_config(&serde_json::json!({"docExpansion": "none", "tryItOutEnabled": true}))
+            .to_html();
+        assert!(html.contains(r#""docExpansion":"none""#));
+        assert!(html.contains(r#""tryItOutEnabled":true"#));
+        assert!(html.contains(r#""url":"/swagger/openapi.json""#));
+    }
+}
@@ -0,0 +1,35 @@
+use axum::Router as AxumRouter;
+use loco_rs::{app::Hooks, boot::create_context, environment::Environment, Result};
+use utoipa::openapi::OpenApi;
+
+use crate::{config::replace_openapi_config, openapi::clear_routes, utils::get_openapi_spec};
+
+/// Build the `OpenAPI` spec of an app without starting a server, to assert on
+/// the paths and schemas directly
+///
+/// Runs the same collection as a boot: the routes of the app are built, so
+/// the `openapi()` wrapped routes are collected, and the `openapi`
+/// initializer runs its `after_routes`. Every call returns the spec of its own
+/// boot
+///
+/// # Errors
+///
+/// Will return `Err` if the app context can't be created or the initializer
+/// fails
+pub async fn build_spec_for_hooks<H: Hooks>() -> Result<OpenApi> {
+    let environment = Environment::Test;
+    let config = H::load_config(&environment).await?;
+    let ctx = create_context::<H>(&environment, config).await?;
+
+    // The config is set once per process, replace the config of an earlier
+    // boot
+    replace_openapi_config(crate::openapi_config(&ctx)?)?;
+    clear_routes();
+    let _ = H::routes(&ctx);
+    for initializer in H::initializers(&ctx).await? {
+        if initializer.name() == "openapi" {
+            let _ = initializer.after_routes(AxumRouter::new(), &ctx).await?;
+        }
+    }
+    Ok(get_openapi_spec().as_ref().clone())
+}
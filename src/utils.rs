@@ -1,21 +1,480 @@
-use std::sync::OnceLock;
+use std::collections::{BTreeMap, BTreeSet};
+#[cfg(feature = "markdown")]
+use std::fmt::Write;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc, Mutex, RwLock, Weak,
+};
+use std::time::{SystemTime, UNIX_EPOCH};
 
-use axum::{response::Response, routing::get, Router as AxumRouter};
-use utoipa::openapi::OpenApi;
+use axum::{
+    body::{Body, Bytes, HttpBody},
+    extract::{Query, Request},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
+    routing::{get, MethodRouter},
+    Router as AxumRouter,
+};
+use tower_http::compression::CompressionLayer;
+use utoipa::openapi::{
+    path::{Operation, PathItem},
+    OpenApi, Server,
+};
+#[cfg(feature = "markdown")]
+use utoipa::openapi::{Content, RefOr, Required};
+use utoipa_axum::router::OpenApiRouter;
 
-use loco_rs::{controller::format, Result};
+use loco_rs::{Error, Result};
 
-static OPENAPI_SPEC: OnceLock<OpenApi> = OnceLock::new();
+use crate::config::get_openapi_config;
+use crate::members;
+use crate::openapi::{get_operation_id_strategy, COMPONENT_SCHEMA_PREFIX};
 
-pub fn set_openapi_spec(api: OpenApi) -> &'static OpenApi {
-    OPENAPI_SPEC.get_or_init(|| api)
+// The specs are replaced on every boot, the main and lean specs also when
+// they're rebuilt after boot, see `extend_openapi_spec`. The endpoints load
+// the current spec per request, a replaced spec is dropped once the requests
+// serving it are done
+static OPENAPI_SPEC: RwLock<Option<Arc<CachedSpec>>> = RwLock::new(None);
+// Serializes the rebuilds of the main spec, so none is lost
+static OPENAPI_SPEC_REBUILD: Mutex<()> = Mutex::new(());
+static OPENAPI_GROUP_SPECS: RwLock<BTreeMap<String, Arc<CachedSpec>>> =
+    RwLock::new(BTreeMap::new());
+static OPENAPI_LEAN_SPEC: RwLock<Option<Arc<CachedSpec>>> = RwLock::new(None);
+
+/// Well-known URL of the JSON spec, see [`crate::config::OpenAPIConfig::well_known`]
+pub const WELL_KNOWN_SPEC_URL: &str = "/.well-known/openapi.json";
+
+/// URL of the JSON spec without descriptions, see
+/// [`crate::config::OpenAPIConfig::strip_descriptions`]
+pub const LEAN_SPEC_URL: &str = "/openapi.min.json";
+
+/// An `OpenAPI` spec together with its JSON and YAML representations
+///
+/// The spec is serialized once when it's stored and the endpoints serve the
+/// cached bytes
+#[derive(Clone)]
+pub struct CachedSpec {
+    spec: Arc<OpenApi>,
+    json: Bytes,
+    yaml: Bytes,
+    json_etag: String,
+    yaml_etag: String,
+    json_pretty: bool,
+}
+
+// `OpenApi` only implements `Debug` with utoipa's `debug` feature
+impl std::fmt::Debug for CachedSpec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CachedSpec")
+            .field("json_etag", &self.json_etag)
+            .field("yaml_etag", &self.yaml_etag)
+            .field("json_pretty", &self.json_pretty)
+            .finish_non_exhaustive()
+    }
+}
+
+impl CachedSpec {
+    /// Serialize the spec to compact JSON and YAML
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the spec can't be serialized
+    pub fn new(spec: OpenApi) -> Result<Self> {
+        Self::with_json_pretty(spec, false)
+    }
+
+    /// Serialize the spec to JSON and YAML, with the JSON pretty-printed
+    /// when `json_pretty` is set
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the spec can't be serialized
+    pub fn with_json_pretty(spec: OpenApi, json_pretty: bool) -> Result<Self> {
+        let json = if json_pretty {
+            serde_json::to_vec_pretty(&spec)?
+        } else {
+            serde_json::to_vec(&spec)?
+        };
+        let json = Bytes::from(json);
+        let yaml = Bytes::from(
+            spec.to_yaml()
+                .map_err(|err| Error::string(&err.to_string()))?,
+        );
+        Ok(Self {
+            json_etag: etag(&json),
+            yaml_etag: etag(&yaml),
+            json_pretty,
+            spec: Arc::new(spec),
+            json,
+            yaml,
+        })
+    }
+
+    /// Serialize a JSON document of a spec as it is to compact JSON and YAML,
+    /// e.g. a document edited as JSON, instead of the spec parsed from it
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` if the document isn't a valid spec or can't be
+    /// serialized
+    pub fn from_document(document: serde_json::Value) -> Result<Self> {
+        let spec = members::from_value(&document)?;
+        let json = Bytes::from(serde_json::to_vec(&document)?);
+        let yaml = Bytes::from(
+            serde_yaml::to_string(&document).map_err(|err| Error::string(&err.to_string()))?,
+        );
+        Ok(Self {
+            json_etag: etag(&json),
+            yaml_etag: etag(&yaml),
+            json_pretty: false,
+            spec: Arc::new(spec),
+            json,
+            yaml,
+        })
+    }
+
+    #[must_use]
+    pub fn spec(&self) -> &OpenApi {
+        &self.spec
+    }
+
+    #[must_use]
+    pub const fn json(&self) -> &Bytes {
+        &self.json
+    }
+
+    #[must_use]
+    pub const fn yaml(&self) -> &Bytes {
+        &self.yaml
+    }
+
+    /// Check the YAML representation parses back into the same document,
+    /// stricter parsers choke on some of utoipa's serialization edge cases
+    ///
+    /// # Errors
+    ///
+    /// Will return `Err` describing the mismatch
+    pub fn check_yaml_round_trip(&self) -> std::result::Result<(), String> {
+        let parsed = serde_yaml::from_slice::<serde_json::Value>(&self.yaml)
+            .map_err(|err| format!("YAML spec doesn't parse: {err}"))?;
+        let expected = serde_json::from_slice::<serde_json::Value>(&self.json)
+            .map_err(|err| format!("JSON spec doesn't parse: {err}"))?;
+        if parsed == expected {
+            Ok(())
+        } else {
+            Err("YAML spec doesn't parse back into the same document".to_string())
+        }
+    }
+
+    /// Weak `ETag` of the JSON representation
+    #[must_use]
+    pub fn json_etag(&self) -> &str {
+        &self.json_etag
+    }
+
+    /// Weak `ETag` of the YAML representation
+    #[must_use]
+    pub fn yaml_etag(&self) -> &str {
+        &self.yaml_etag
+    }
+}
+
+/// Weak `ETag` derived from a hash of the serialized bytes, weak since the
+/// compressed responses of the endpoints share the `ETag` of the uncompressed
+/// spec
+fn etag(bytes: &[u8]) -> String {
+    let mut hasher = DefaultHasher::new();
+    bytes.hash(&mut hasher);
+    format!("W/\"{:016x}\"", hasher.finish())
+}
+
+/// Whether an `If-None-Match` header value matches the `ETag`, with the weak
+/// comparison of RFC 9110
+fn etag_matches(if_none_match: &str, etag: &str) -> bool {
+    let etag = etag.trim_start_matches("W/");
+    if_none_match
+        .split(',')
+        .map(str::trim)
+        .any(|tag| tag == "*" || tag.trim_start_matches("W/") == etag)
+}
+
+/// Store the `OpenAPI` spec, serializing it once for the spec endpoints
+///
+/// The spec of a previous boot is replaced, the endpoints serve the stored
+/// spec from their next request
+///
+/// # Errors
+///
+/// Will return `Err` if the spec can't be serialized
+pub fn set_openapi_spec(api: OpenApi) -> Result<Arc<CachedSpec>> {
+    let cached = Arc::new(CachedSpec::with_json_pretty(api, json_pretty())?);
+    warn_on_yaml_round_trip("main", &cached);
+    *OPENAPI_SPEC
+        .write()
+        .map_err(|_| Error::string("OpenAPI spec lock is poisoned"))? = Some(Arc::clone(&cached));
+    Ok(cached)
+}
+
+/// Merge the routes of a plugin loaded after boot into the main spec, so its
+/// endpoints are documented without restarting the app
+///
+/// The routes are documented as they are, with the configured
+/// `operationId` strategy, the modifiers of the initializer aren't applied
+/// to them.
+///
+/// Concurrency: rebuilds run one at a time, each merging into the spec of
+/// the previous one. Requests are served from the current spec until the
+/// rebuilt one is serialized, then it's swapped in at once. All endpoints of
+/// the main spec serve the rebuilt spec, the pages embedding it (Redoc,
+/// Scalar) and the Markdown and Postman renderings are rendered again on
+/// their next request
+/// ```rust,no_run
+/// use by_loco_openapi::prelude::*;
+///
+/// #[utoipa::path(get, path = "/api/plugins/reports", responses((status = 200, description = "Reports")))]
+/// async fn reports() {}
+///
+/// # fn load_plugin() -> loco_rs::Result<()> {
+/// by_loco_openapi::utils::extend_openapi_spec(OpenApiRouter::<()>::new().routes(routes!(reports)))?;
+/// # Ok(())
+/// # }
+/// ```
+///
+/// # Errors
+///
+/// Will return `Err` if the spec wasn't collected yet or can't be serialized
+pub fn extend_openapi_spec<S>(routes: OpenApiRouter<S>) -> Result<Arc<CachedSpec>>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    let _rebuild = OPENAPI_SPEC_REBUILD
+        .lock()
+        .map_err(|_| Error::string("OpenAPI spec rebuild lock is poisoned"))?;
+    let Some(current) = try_get_cached_openapi_spec() else {
+        return Err(Error::string(
+            "the OpenAPI spec isn't collected yet, extend it after the `openapi` initializer ran",
+        ));
+    };
+
+    let (_, mut routes_spec) = routes.split_for_parts();
+    get_operation_id_strategy().apply(&mut routes_spec);
+    let mut spec = current.spec().clone();
+    spec.merge(routes_spec);
+    let cached = Arc::new(CachedSpec::with_json_pretty(spec, json_pretty())?);
+    warn_on_yaml_round_trip("main", &cached);
+    if try_get_cached_openapi_lean_spec().is_some() {
+        set_openapi_lean_spec(strip_descriptions(cached.spec())?)?;
+    }
+
+    *OPENAPI_SPEC
+        .write()
+        .map_err(|_| Error::string("OpenAPI spec lock is poisoned"))? = Some(Arc::clone(&cached));
+    Ok(cached)
+}
+
+/// Store the spec document without descriptions, see
+/// [`strip_descriptions`], replacing the current one. Its JSON is never
+/// pretty-printed
+///
+/// # Errors
+///
+/// Will return `Err` if the document isn't a valid spec or can't be
+/// serialized
+pub fn set_openapi_lean_spec(document: serde_json::Value) -> Result<Arc<CachedSpec>> {
+    let cached = Arc::new(CachedSpec::from_document(document)?);
+    *OPENAPI_LEAN_SPEC
+        .write()
+        .map_err(|_| Error::string("OpenAPI lean spec lock is poisoned"))? =
+        Some(Arc::clone(&cached));
+    Ok(cached)
+}
+
+/// Get the cached spec without descriptions, if it was stored
+#[must_use]
+pub fn try_get_cached_openapi_lean_spec() -> Option<Arc<CachedSpec>> {
+    OPENAPI_LEAN_SPEC.read().ok().and_then(|spec| spec.clone())
+}
+
+/// The JSON document of the spec without the `description` and `summary`
+/// texts, for code generators that ignore the docs
+///
+/// Only string values are removed, so schema properties named `description`
+/// or `summary` are kept, and `example` and `examples` payloads are left
+/// untouched. The `description` of a response is required, so it's kept
+///
+/// # Errors
+///
+/// Will return `Err` if the spec can't be converted to JSON
+pub fn strip_descriptions(spec: &OpenApi) -> Result<serde_json::Value> {
+    fn strip(value: &mut serde_json::Value, response: bool) {
+        match value {
+            serde_json::Value::Object(map) => {
+                map.retain(|key, value| match key.as_str() {
+                    "description" => response || !value.is_string(),
+                    "summary" => !value.is_string(),
+                    _ => true,
+                });
+                for (key, value) in map.iter_mut() {
+                    match (key.as_str(), value) {
+                        ("example" | "examples", _) => {}
+                        ("responses", serde_json::Value::Object(responses)) => responses
+                            .values_mut()
+                            .for_each(|response| strip(response, true)),
+                        (_, value) => strip(value, false),
+                    }
+                }
+            }
+            serde_json::Value::Array(values) => {
+                values.iter_mut().for_each(|value| strip(value, false));
+            }
+            _ => {}
+        }
+    }
+
+    let mut json = serde_json::to_value(spec)?;
+    strip(&mut json, false);
+    Ok(json)
+}
+
+/// Whether the configured JSON spec is pretty-printed
+fn json_pretty() -> bool {
+    get_openapi_config().is_some_and(|config| config.json_pretty)
+}
+
+const JSON_CONTENT_TYPE: &str = "application/json";
+
+/// How the spec endpoints are served
+#[derive(Debug, Clone)]
+struct SpecOptions {
+    /// Point the first server of the JSON spec at the origin of the request
+    dynamic_servers: bool,
+    /// Lowercased hosts the forwarded origin of the request may name
+    allowed_hosts: Arc<[String]>,
+    /// Media type of the JSON spec
+    json_content_type: Arc<str>,
+    /// Serve the JSON spec
+    serve_json: bool,
+    /// Serve the YAML spec
+    serve_yaml: bool,
+}
+
+impl SpecOptions {
+    /// The options set in the `OpenAPI` config
+    fn from_config() -> Self {
+        let config = get_openapi_config();
+        Self {
+            dynamic_servers: config.is_some_and(|config| config.dynamic_servers),
+            allowed_hosts: config
+                .map(|config| lowercase_hosts(&config.allowed_hosts))
+                .unwrap_or_default(),
+            json_content_type: config
+                .and_then(|config| config.json_content_type.as_deref())
+                .unwrap_or(JSON_CONTENT_TYPE)
+                .into(),
+            serve_json: config.is_none_or(|config| config.serve_json),
+            serve_yaml: config.is_none_or(|config| config.serve_yaml),
+        }
+    }
+}
+
+impl Default for SpecOptions {
+    fn default() -> Self {
+        Self {
+            dynamic_servers: false,
+            allowed_hosts: Arc::default(),
+            json_content_type: JSON_CONTENT_TYPE.into(),
+            serve_json: true,
+            serve_yaml: true,
+        }
+    }
+}
+
+fn warn_on_yaml_round_trip(name: &str, spec: &CachedSpec) {
+    if let Err(err) = spec.check_yaml_round_trip() {
+        tracing::warn!(spec = name, "{err}");
+    }
+}
+
+/// The current `OpenAPI` spec
+///
+/// The spec is replaced on every boot and by [`extend_openapi_spec`], the
+/// returned spec stays the one current at the time of the call. Use
+/// [`get_cached_openapi_spec`] for its serialized JSON and YAML
+///
+/// # Panics
+///
+/// Will panic if `OpenAPI` spec fails to build
+#[must_use]
+pub fn get_openapi_spec() -> Arc<OpenApi> {
+    Arc::clone(&get_cached_openapi_spec().spec)
 }
 
+/// The current `OpenAPI` spec together with its JSON and YAML
+/// representations
+///
 /// # Panics
 ///
 /// Will panic if `OpenAPI` spec fails to build
-pub fn get_openapi_spec() -> &'static OpenApi {
-    OPENAPI_SPEC.get().unwrap()
+#[must_use]
+pub fn get_cached_openapi_spec() -> Arc<CachedSpec> {
+    try_get_cached_openapi_spec().unwrap()
+}
+
+/// Get the cached `OpenAPI` spec, if it was collected
+#[must_use]
+pub fn try_get_cached_openapi_spec() -> Option<Arc<CachedSpec>> {
+    OPENAPI_SPEC.read().ok().and_then(|spec| spec.clone())
+}
+
+/// Whether the `OpenAPI` spec was collected and documents at least one path
+///
+/// Meant for readiness probes, an empty spec usually means a misconfigured
+/// setup rather than an API without endpoints
+#[must_use]
+pub fn openapi_is_ready() -> bool {
+    try_get_cached_openapi_spec().is_some_and(|cached| !cached.spec().paths.paths.is_empty())
+}
+
+/// Store the `OpenAPI` specs of the named route groups, replacing the specs
+/// of the previous boot
+///
+/// # Errors
+///
+/// Will return `Err` if a spec can't be serialized
+pub fn set_openapi_group_specs(
+    specs: BTreeMap<String, OpenApi>,
+) -> Result<BTreeMap<String, Arc<CachedSpec>>> {
+    let cached = specs
+        .into_iter()
+        .map(|(group, spec)| {
+            CachedSpec::with_json_pretty(spec, json_pretty()).map(|spec| (group, Arc::new(spec)))
+        })
+        .collect::<Result<BTreeMap<_, _>>>()?;
+    for (group, spec) in &cached {
+        warn_on_yaml_round_trip(group, spec);
+    }
+    OPENAPI_GROUP_SPECS
+        .write()
+        .map_err(|_| Error::string("OpenAPI group specs lock is poisoned"))?
+        .clone_from(&cached);
+    Ok(cached)
+}
+
+/// Get the `OpenAPI` spec of a named route group, if it was collected
+#[must_use]
+pub fn get_openapi_group_spec(group: &str) -> Option<OpenApi> {
+    get_cached_openapi_group_spec(group).map(|spec| spec.spec().clone())
+}
+
+/// Get the cached `OpenAPI` spec of a named route group, if it was collected
+#[must_use]
+pub fn get_cached_openapi_group_spec(group: &str) -> Option<Arc<CachedSpec>> {
+    OPENAPI_GROUP_SPECS
+        .read()
+        .ok()
+        .and_then(|specs| specs.get(group).map(Arc::clone))
 }
 
 /// Axum handler that returns the `OpenAPI` spec as JSON
@@ -24,7 +483,22 @@ pub fn get_openapi_spec() -> &'static OpenApi {
 /// Currently this function doesn't return any error. this is for feature
 /// functionality
 pub async fn openapi_spec_json() -> Result<Response> {
-    format::json(get_openapi_spec())
+    openapi_spec_json_with_headers(HeaderMap::new()).await
+}
+
+/// Axum handler that returns the `OpenAPI` spec as JSON, or `304 Not
+/// Modified` when the `If-None-Match` header matches its `ETag`
+///
+/// # Errors
+/// Currently this function doesn't return any error. this is for feature
+/// functionality
+pub async fn openapi_spec_json_with_headers(headers: HeaderMap) -> Result<Response> {
+    Ok(spec_json(
+        &get_cached_openapi_spec(),
+        &headers,
+        &SpecQuery::default(),
+        &SpecOptions::from_config(),
+    ))
 }
 
 /// Axum handler that returns the `OpenAPI` spec as YAML
@@ -33,23 +507,1936 @@ pub async fn openapi_spec_json() -> Result<Response> {
 /// Currently this function doesn't return any error. this is for feature
 /// functionality
 pub async fn openapi_spec_yaml() -> Result<Response> {
-    format::yaml(&get_openapi_spec().to_yaml()?)
+    openapi_spec_yaml_with_headers(HeaderMap::new()).await
+}
+
+/// Axum handler that returns the `OpenAPI` spec as YAML, or `304 Not
+/// Modified` when the `If-None-Match` header matches its `ETag`
+///
+/// # Errors
+/// Currently this function doesn't return any error. this is for feature
+/// functionality
+pub async fn openapi_spec_yaml_with_headers(headers: HeaderMap) -> Result<Response> {
+    Ok(spec_yaml(&get_cached_openapi_spec(), &headers))
+}
+
+/// Axum handler that returns the `OpenAPI` spec as YAML when the `Accept`
+/// header prefers it, and as JSON otherwise
+///
+/// # Errors
+/// Currently this function doesn't return any error. this is for feature
+/// functionality
+pub async fn openapi_spec(headers: HeaderMap) -> Result<Response> {
+    Ok(spec_negotiated(
+        &get_cached_openapi_spec(),
+        &headers,
+        &SpecQuery::default(),
+        &SpecOptions::from_config(),
+    ))
+}
+
+/// Query parameters of the JSON spec endpoints
+#[derive(Debug, Default, Clone, serde::Deserialize)]
+struct SpecQuery {
+    /// Only document the operations with this tag, see [`filter_by_tag`]
+    tag: Option<String>,
+}
+
+/// A copy of the spec with only the operations tagged `tag`, and the
+/// component schemas still referenced, for browsing a large spec one tag at
+/// a time
+#[must_use]
+pub fn filter_by_tag(spec: &OpenApi, tag: &str) -> OpenApi {
+    let mut filtered = spec.clone();
+    for item in filtered.paths.paths.values_mut() {
+        for operation in path_operations(item) {
+            let tagged = operation
+                .as_ref()
+                .and_then(|operation| operation.tags.as_ref())
+                .is_some_and(|tags| tags.iter().any(|operation_tag| operation_tag == tag));
+            if !tagged {
+                *operation = None;
+            }
+        }
+    }
+    filtered.paths.paths.retain(|_, item| {
+        path_operations(item)
+            .iter()
+            .any(|operation| operation.is_some())
+    });
+    if let Some(tags) = filtered.tags.as_mut() {
+        tags.retain(|spec_tag| spec_tag.name == tag);
+    }
+
+    let Some(mut schemas) = filtered
+        .components
+        .as_mut()
+        .map(|components| std::mem::take(&mut components.schemas))
+    else {
+        return filtered;
+    };
+    // Schemas referenced outside of the component schemas, then the schemas
+    // they reference
+    let json = serde_json::to_value(&filtered).unwrap_or_default();
+    let mut refs = Vec::new();
+    crate::openapi::collect_refs(&json, &mut refs);
+    let mut pending = refs
+        .into_iter()
+        .filter_map(|reference| reference.strip_prefix(COMPONENT_SCHEMA_PREFIX))
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+    let mut referenced = BTreeSet::new();
+    while let Some(name) = pending.pop() {
+        let Some(schema) = schemas.get(&name).filter(|_| !referenced.contains(&name)) else {
+            continue;
+        };
+        let json = serde_json::to_value(schema).unwrap_or_default();
+        let mut refs = Vec::new();
+        crate::openapi::collect_refs(&json, &mut refs);
+        pending.extend(
+            refs.into_iter()
+                .filter_map(|reference| reference.strip_prefix(COMPONENT_SCHEMA_PREFIX))
+                .map(ToString::to_string),
+        );
+        referenced.insert(name);
+    }
+    schemas.retain(|name, _| referenced.contains(name));
+    if let Some(components) = filtered.components.as_mut() {
+        components.schemas = schemas;
+    }
+    filtered
+}
+
+fn path_operations(item: &mut PathItem) -> [&mut Option<Operation>; 8] {
+    [
+        &mut item.get,
+        &mut item.put,
+        &mut item.post,
+        &mut item.delete,
+        &mut item.options,
+        &mut item.head,
+        &mut item.patch,
+        &mut item.trace,
+    ]
+}
+
+fn spec_negotiated(
+    spec: &CachedSpec,
+    headers: &HeaderMap,
+    query: &SpecQuery,
+    options: &SpecOptions,
+) -> Response {
+    let yaml = options.serve_yaml && (accepts_yaml(headers) || !options.serve_json);
+    let mut response = if yaml {
+        spec_yaml(spec, headers)
+    } else {
+        spec_json(spec, headers, query, options)
+    };
+    response
+        .headers_mut()
+        .insert(header::VARY, HeaderValue::from_static("accept"));
+    response
+}
+
+/// Whether the JSON or YAML media type of the `Accept` header with the
+/// highest `q` weight is YAML, the first one listed when they're weighted
+/// alike
+fn accepts_yaml(headers: &HeaderMap) -> bool {
+    headers
+        .get_all(header::ACCEPT)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(|media_range| {
+            let mut params = media_range.split(';').map(str::trim);
+            let media_type = params.next()?;
+            let yaml = if ["application/yaml", "application/x-yaml", "text/yaml"]
+                .iter()
+                .any(|yaml| media_type.eq_ignore_ascii_case(yaml))
+            {
+                true
+            } else if media_type.eq_ignore_ascii_case("application/json") {
+                false
+            } else {
+                return None;
+            };
+            let weight = params
+                .filter_map(|param| param.split_once('='))
+                .find(|(name, _)| name.trim().eq_ignore_ascii_case("q"))
+                .map_or(Some(1.0), |(_, weight)| weight.trim().parse::<f32>().ok())?;
+            (weight > 0.0).then_some((weight, yaml))
+        })
+        .fold(
+            None,
+            |best: Option<(f32, bool)>, (weight, yaml)| match best {
+                Some((best_weight, _)) if best_weight >= weight => best,
+                _ => Some((weight, yaml)),
+            },
+        )
+        .is_some_and(|(_, yaml)| yaml)
+}
+
+fn spec_json(
+    spec: &CachedSpec,
+    headers: &HeaderMap,
+    query: &SpecQuery,
+    options: &SpecOptions,
+) -> Response {
+    let origin = options
+        .dynamic_servers
+        .then(|| forwarded_origin(headers, &options.allowed_hosts))
+        .flatten();
+    if origin.is_some() || query.tag.is_some() {
+        return spec_json_per_request(
+            spec,
+            query.tag.as_deref(),
+            origin.as_deref(),
+            &options.json_content_type,
+        );
+    }
+    spec_response(
+        &options.json_content_type,
+        spec.json(),
+        spec.json_etag(),
+        headers,
+    )
+}
+
+/// Origin of the request as seen by the client, from the `X-Forwarded-Host`
+/// and `X-Forwarded-Proto` headers set by a proxy
+///
+/// The headers can be sent by any client, so the origin is only trusted when
+/// its host is one of the `allowed_hosts`, and its scheme `http` or `https`
+fn forwarded_origin(headers: &HeaderMap, allowed_hosts: &[String]) -> Option<String> {
+    let header_value = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.split(',').next())
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+    };
+    let forwarded_host = header_value("x-forwarded-host");
+    let forwarded_proto = header_value("x-forwarded-proto");
+    if forwarded_host.is_none() && forwarded_proto.is_none() {
+        return None;
+    }
+    let host = forwarded_host.or_else(|| header_value("host"))?;
+    if !host_allowed(allowed_hosts, host) {
+        return None;
+    }
+    let scheme = match forwarded_proto {
+        None => "http",
+        Some(proto) if proto.eq_ignore_ascii_case("http") => "http",
+        Some(proto) if proto.eq_ignore_ascii_case("https") => "https",
+        Some(_) => return None,
+    };
+    Some(format!("{scheme}://{host}"))
+}
+
+/// Path of a server URL, the URL itself when it's relative
+fn server_path(url: &str) -> &str {
+    url.split_once("://").map_or(url, |(_, rest)| {
+        rest.find('/').map_or("", |index| &rest[index..])
+    })
+}
+
+/// Respond with the JSON spec filtered to the operations of `tag`, and its
+/// first server pointed at `origin`
+///
+/// The spec is serialized per request, so it isn't cached by `ETag`
+fn spec_json_per_request(
+    spec: &CachedSpec,
+    tag: Option<&str>,
+    origin: Option<&str>,
+    content_type: &str,
+) -> Response {
+    let mut spec_for_request = match tag {
+        Some(tag) => filter_by_tag(spec.spec(), tag),
+        None => spec.spec().clone(),
+    };
+    if let Some(origin) = origin {
+        let servers = spec_for_request.servers.get_or_insert_with(Vec::new);
+        match servers.first_mut() {
+            Some(server) => server.url = format!("{origin}{}", server_path(&server.url)),
+            None => servers.push(Server::new(origin)),
+        }
+    }
+
+    let json = if spec.json_pretty {
+        serde_json::to_vec_pretty(&spec_for_request)
+    } else {
+        serde_json::to_vec(&spec_for_request)
+    };
+    match json {
+        Ok(json) => {
+            let mut response = ([(header::CONTENT_TYPE, content_type)], json).into_response();
+            if origin.is_some() {
+                response.headers_mut().insert(
+                    header::VARY,
+                    HeaderValue::from_static("X-Forwarded-Host, X-Forwarded-Proto"),
+                );
+            }
+            response
+        }
+        Err(err) => {
+            tracing::error!("failed to serialize the OpenAPI spec: {err}");
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+fn spec_yaml(spec: &CachedSpec, headers: &HeaderMap) -> Response {
+    spec_response("application/yaml", spec.yaml(), spec.yaml_etag(), headers)
+}
+
+/// Respond with the serialized spec, or `304 Not Modified` when the client
+/// already has it
+fn spec_response(content_type: &str, body: &Bytes, etag: &str, headers: &HeaderMap) -> Response {
+    let not_modified = headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| etag_matches(value, etag));
+    if not_modified {
+        return (StatusCode::NOT_MODIFIED, [(header::ETAG, etag)]).into_response();
+    }
+
+    (
+        [(header::CONTENT_TYPE, content_type)],
+        [(header::ETAG, etag)],
+        body.clone(),
+    )
+        .into_response()
+}
+
+/// Set the title and favicon of the HTML pages of the router, e.g. to tell
+/// the docs of several services apart in the browser tabs
+///
+/// The `<title>` of the page is replaced, or added to the `<head>` when there
+/// is none, and the favicon is linked at the end of the `<head>`. Pages
+/// without a `<head>`, compressed responses and other media types are served
+/// unchanged
+pub fn add_page_branding<T>(
+    app: AxumRouter<T>,
+    page_title: Option<&str>,
+    favicon_url: Option<&str>,
+) -> AxumRouter<T>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    let branding = Arc::new((
+        page_title.map(ToString::to_string),
+        favicon_url.map(ToString::to_string),
+    ));
+    app.layer(middleware::map_response(move |response: Response| {
+        let branding = Arc::clone(&branding);
+        async move {
+            let (page_title, favicon_url) = &*branding;
+            brand_page(response, page_title.as_deref(), favicon_url.as_deref()).await
+        }
+    }))
+}
+
+async fn brand_page(
+    response: Response,
+    page_title: Option<&str>,
+    favicon_url: Option<&str>,
+) -> Response {
+    let is_html = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("text/html"));
+    if !is_html || response.headers().contains_key(header::CONTENT_ENCODING) {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let Ok(bytes) = axum::body::to_bytes(body, usize::MAX).await else {
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    };
+    let Some(html) = std::str::from_utf8(&bytes)
+        .ok()
+        .and_then(|html| brand_html(html, page_title, favicon_url))
+    else {
+        tracing::debug!("docs page has no <head>, serving it without the title and favicon");
+        return Response::from_parts(parts, Body::from(bytes));
+    };
+    parts.headers.remove(header::CONTENT_LENGTH);
+    Response::from_parts(parts, Body::from(html))
+}
+
+/// The page with its title and favicon set, `None` when it has no `<head>`
+fn brand_html(html: &str, page_title: Option<&str>, favicon_url: Option<&str>) -> Option<String> {
+    let mut html = html.to_string();
+    if let Some(page_title) = page_title {
+        let title = format!("<title>{}</title>", escape_html(page_title));
+        match (html.find("<title>"), html.find("</title>")) {
+            (Some(start), Some(end)) if start < end => {
+                html.replace_range(start..end + "</title>".len(), &title);
+            }
+            _ => html.insert_str(html.find("</head>")?, &title),
+        }
+    }
+    if let Some(favicon_url) = favicon_url {
+        let link = format!(r#"<link rel="icon" href="{}">"#, escape_html(favicon_url));
+        html.insert_str(html.find("</head>")?, &link);
+    }
+    Some(html)
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Name of the counter of the docs and spec endpoint hits, see
+/// [`add_metrics`]
+#[cfg(feature = "metrics")]
+pub const REQUESTS_METRIC: &str = "loco_openapi_requests_total";
+
+/// Count the hits of the routes of the router with the `metrics` crate, as
+/// the [`REQUESTS_METRIC`] counter labeled by the `endpoint` route and the
+/// response `status`
+///
+/// Only the routes already added are counted, requests that match no route
+/// aren't. A router without routes is returned as it is
+#[cfg(feature = "metrics")]
+pub fn add_metrics<T>(app: AxumRouter<T>) -> AxumRouter<T>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    // axum panics on a route layer without routes
+    if !app.has_routes() {
+        return app;
+    }
+    app.route_layer(middleware::from_fn(count_request))
+}
+
+#[cfg(feature = "metrics")]
+async fn count_request(request: Request, next: Next) -> Response {
+    let endpoint = request
+        .extensions()
+        .get::<axum::extract::MatchedPath>()
+        .map_or_else(
+            || request.uri().path().to_string(),
+            |path| path.as_str().to_string(),
+        );
+    let response = next.run(request).await;
+    metrics::counter!(
+        REQUESTS_METRIC,
+        "endpoint" => endpoint,
+        "status" => response.status().as_u16().to_string(),
+    )
+    .increment(1);
+    response
+}
+
+/// Serve the routes of the router only on the `hosts`, e.g. an internal
+/// domain, other hosts get a `404 Not Found`. No restriction when `hosts` is
+/// empty
+///
+/// The host is taken from the `Host` header, or the authority of HTTP/2
+/// requests, and compared case-insensitively. A host listed with a port, e.g.
+/// `docs.example.com:8443`, only matches requests to that port, one without a
+/// port matches all ports
+pub fn add_allowed_hosts<T>(app: AxumRouter<T>, hosts: &[String]) -> AxumRouter<T>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    if hosts.is_empty() {
+        return app;
+    }
+    let hosts = lowercase_hosts(hosts);
+    app.layer(middleware::from_fn(move |request: Request, next: Next| {
+        let hosts = Arc::clone(&hosts);
+        async move {
+            let allowed = request_host(&request).is_some_and(|host| host_allowed(&hosts, host));
+            if allowed {
+                next.run(request).await
+            } else {
+                StatusCode::NOT_FOUND.into_response()
+            }
+        }
+    }))
+}
+
+fn lowercase_hosts(hosts: &[String]) -> Arc<[String]> {
+    hosts.iter().map(|host| host.to_lowercase()).collect()
+}
+
+/// Whether `authority` is one of the lowercased `hosts`, a host listed with a
+/// port only matches that port, one without a port matches all ports
+fn host_allowed(hosts: &[String], authority: &str) -> bool {
+    let authority = authority.to_lowercase();
+    let host = without_port(&authority);
+    hosts.iter().any(|allowed| {
+        if without_port(allowed) == allowed {
+            allowed == host
+        } else {
+            *allowed == authority
+        }
+    })
+}
+
+/// Host of the request
+fn request_host(request: &Request) -> Option<&str> {
+    request
+        .headers()
+        .get(header::HOST)
+        .and_then(|value| value.to_str().ok())
+        .or_else(|| {
+            request
+                .uri()
+                .authority()
+                .map(|authority| authority.as_str())
+        })
+}
+
+/// Host of an authority without the port
+fn without_port(authority: &str) -> &str {
+    // Bracketed IPv6 hosts contain colons of their own
+    match authority.rfind(':') {
+        Some(index) if !authority[index..].contains(']') => &authority[..index],
+        _ => authority,
+    }
+}
+
+const REQUEST_ID_HEADER: &str = "x-request-id";
+const MAX_REQUEST_ID_LEN: usize = 255;
+
+/// Tag the responses of all routes of the router with an `X-Request-Id`, so
+/// docs requests can be correlated with the logs of the edge
+///
+/// The id of the request is echoed when it's a valid id, as with Loco's
+/// `request_id` middleware, otherwise one is generated. A response that
+/// already has an id keeps it
+pub fn add_request_id<T>(app: AxumRouter<T>) -> AxumRouter<T>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    app.layer(middleware::from_fn(request_id))
+}
+
+async fn request_id(request: Request, next: Next) -> Response {
+    let id = request
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|id| is_valid_request_id(id))
+        .map_or_else(generate_request_id, ToString::to_string);
+
+    let mut response = next.run(request).await;
+    if let Ok(value) = HeaderValue::from_str(&id) {
+        response
+            .headers_mut()
+            .entry(REQUEST_ID_HEADER)
+            .or_insert(value);
+    }
+    response
+}
+
+fn is_valid_request_id(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= MAX_REQUEST_ID_LEN
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// A request id unique to the process, from the time and a counter
+fn generate_request_id() -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let mut hasher = DefaultHasher::new();
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .hash(&mut hasher);
+    COUNTER.fetch_add(1, Ordering::Relaxed).hash(&mut hasher);
+    std::process::id().hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+/// The URL with the trailing slash toggled, `None` for the root which has no
+/// form without the slash
+pub(crate) fn alternate_trailing_slash(url: &str) -> Option<String> {
+    let alternate_url = url
+        .strip_suffix('/')
+        .map_or_else(|| format!("{url}/"), ToString::to_string);
+    (!alternate_url.is_empty()).then_some(alternate_url)
+}
+
+/// Route `url` and its form with the trailing slash toggled to the handler
+fn route_with_trailing_slash<T>(
+    app: AxumRouter<T>,
+    url: &str,
+    method_router: MethodRouter<T>,
+) -> AxumRouter<T>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    let app = match alternate_trailing_slash(url) {
+        Some(alternate_url) => app.route(&alternate_url, method_router.clone()),
+        None => app,
+    };
+    app.route(url, method_router)
+}
+
+/// Serve the spec response for `GET`, compressed, and its headers without a
+/// body for `HEAD`, with the `Content-Length` of the uncompressed spec
+fn spec_method_router<T, F>(respond: F) -> MethodRouter<T>
+where
+    T: Clone + Send + Sync + 'static,
+    F: Fn(&HeaderMap, &SpecQuery) -> Response + Clone + Send + Sync + 'static,
+{
+    let head_respond = respond.clone();
+    get(
+        move |headers: HeaderMap, Query(query): Query<SpecQuery>| async move {
+            respond(&headers, &query)
+        },
+    )
+    .layer(CompressionLayer::new())
+    .head(
+        move |headers: HeaderMap, Query(query): Query<SpecQuery>| async move {
+            without_body(head_respond(&headers, &query))
+        },
+    )
+}
+
+fn without_body(response: Response) -> Response {
+    let (mut parts, body) = response.into_parts();
+    if let Some(len) = body.size_hint().exact() {
+        parts.headers.insert(header::CONTENT_LENGTH, len.into());
+    }
+    Response::from_parts(parts, Body::empty())
 }
 
 /// Adds the `OpenAPI` endpoints the app router
 pub fn add_openapi_endpoints<T>(
+    app: AxumRouter<T>,
+    json_url: &Option<String>,
+    yaml_url: &Option<String>,
+) -> AxumRouter<T>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    let endpoints = add_spec_endpoints(
+        AxumRouter::new(),
+        get_cached_openapi_spec(),
+        json_url,
+        yaml_url,
+    );
+    #[cfg(feature = "metrics")]
+    let endpoints = add_metrics(endpoints);
+    app.merge(endpoints)
+}
+
+/// Adds endpoints serving the given `OpenAPI` spec to the app router
+///
+/// Responses are compressed when the client accepts gzip or deflate, and the
+/// URLs are served with and without a trailing slash
+pub fn add_spec_endpoints<T>(
+    app: AxumRouter<T>,
+    spec: Arc<CachedSpec>,
+    json_url: &Option<String>,
+    yaml_url: &Option<String>,
+) -> AxumRouter<T>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    route_spec_endpoints(app, spec, json_url, yaml_url, SpecOptions::from_config())
+}
+
+/// The spec served by an endpoint, loaded per request. The endpoints of a
+/// stored spec follow its replacements, see [`extend_openapi_spec`]
+#[derive(Clone)]
+pub(crate) enum SpecSource {
+    Main,
+    Lean,
+    Group(String),
+    Fixed(Arc<CachedSpec>),
+}
+
+impl SpecSource {
+    pub(crate) fn of(spec: Arc<CachedSpec>) -> Self {
+        let is_spec = |stored: Arc<CachedSpec>| Arc::ptr_eq(&stored, &spec);
+        if try_get_cached_openapi_spec().is_some_and(is_spec) {
+            return Self::Main;
+        }
+        if try_get_cached_openapi_lean_spec().is_some_and(is_spec) {
+            return Self::Lean;
+        }
+        let group = OPENAPI_GROUP_SPECS.read().ok().and_then(|specs| {
+            specs
+                .iter()
+                .find(|(_, stored)| Arc::ptr_eq(stored, &spec))
+                .map(|(group, _)| group.clone())
+        });
+        group.map_or(Self::Fixed(spec), Self::Group)
+    }
+
+    /// The current spec, the main spec when a group isn't collected anymore
+    pub(crate) fn get(&self) -> Arc<CachedSpec> {
+        match self {
+            Self::Main => get_cached_openapi_spec(),
+            Self::Lean => {
+                try_get_cached_openapi_lean_spec().unwrap_or_else(get_cached_openapi_spec)
+            }
+            Self::Group(group) => {
+                get_cached_openapi_group_spec(group).unwrap_or_else(get_cached_openapi_spec)
+            }
+            Self::Fixed(spec) => Arc::clone(spec),
+        }
+    }
+}
+
+type Render<R> = Arc<dyn Fn(&CachedSpec) -> R + Send + Sync>;
+type Rendered<R> = Arc<Mutex<Option<(Weak<CachedSpec>, R)>>>;
+
+/// A rendering of a spec, e.g. a docs page embedding it, rendered again on
+/// the first request after the spec is replaced
+#[derive(Clone)]
+pub(crate) struct SpecRendering<R> {
+    source: SpecSource,
+    render: Render<R>,
+    rendered: Rendered<R>,
+}
+
+impl<R: Clone> SpecRendering<R> {
+    pub(crate) fn new<F>(spec: Arc<CachedSpec>, render: F) -> Self
+    where
+        F: Fn(&CachedSpec) -> R + Send + Sync + 'static,
+    {
+        Self {
+            source: SpecSource::of(spec),
+            render: Arc::new(render),
+            rendered: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// The rendering of the current spec
+    pub(crate) fn get(&self) -> R {
+        let spec = self.source.get();
+        let Ok(mut rendered) = self.rendered.lock() else {
+            return (self.render)(&spec);
+        };
+        // The weak reference keeps the allocation of the rendered spec, so
+        // another spec can't take its address
+        match &*rendered {
+            Some((rendered_spec, rendering)) if std::ptr::eq(rendered_spec.as_ptr(), &*spec) => {
+                rendering.clone()
+            }
+            _ => {
+                let rendering = (self.render)(&spec);
+                *rendered = Some((Arc::downgrade(&spec), rendering.clone()));
+                rendering
+            }
+        }
+    }
+}
+
+fn route_spec_endpoints<T>(
     mut app: AxumRouter<T>,
+    spec: Arc<CachedSpec>,
     json_url: &Option<String>,
     yaml_url: &Option<String>,
+    options: SpecOptions,
 ) -> AxumRouter<T>
 where
     T: Clone + Send + Sync + 'static,
 {
-    if let Some(json_url) = json_url {
-        app = app.route(json_url, get(openapi_spec_json));
+    let spec = SpecSource::of(spec);
+    if let Some(json_url) = json_url.as_ref().filter(|_| options.serve_json) {
+        app = route_with_trailing_slash(
+            app,
+            json_url,
+            spec_method_router({
+                let spec = spec.clone();
+                let options = options.clone();
+                move |headers, query| spec_json(&spec.get(), headers, query, &options)
+            }),
+        );
     }
-    if let Some(yaml_url) = yaml_url {
-        app = app.route(yaml_url, get(openapi_spec_yaml));
+    if let Some(yaml_url) = yaml_url.as_ref().filter(|_| options.serve_yaml) {
+        app = route_with_trailing_slash(
+            app,
+            yaml_url,
+            spec_method_router(move |headers, _| spec_yaml(&spec.get(), headers)),
+        );
     }
     app
 }
+
+/// Adds an endpoint serving the given `OpenAPI` spec as JSON or YAML,
+/// depending on the `Accept` header of the request
+pub fn add_negotiated_spec_endpoint<T>(
+    app: AxumRouter<T>,
+    spec: Arc<CachedSpec>,
+    url: &str,
+) -> AxumRouter<T>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    let options = SpecOptions::from_config();
+    if !options.serve_json && !options.serve_yaml {
+        return app;
+    }
+    let spec = SpecSource::of(spec);
+    route_with_trailing_slash(
+        app,
+        url,
+        spec_method_router(move |headers, query| {
+            spec_negotiated(&spec.get(), headers, query, &options)
+        }),
+    )
+}
+
+/// Environment variable with the build timestamp, e.g. set by CI
+pub const BUILD_TIMESTAMP_ENV: &str = "BUILD_TIMESTAMP";
+/// Environment variable with the git commit of the build
+pub const GIT_SHA_ENV: &str = "GIT_SHA";
+
+/// Which build of the app serves the spec, documented as the `x-build`
+/// extension of the spec
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct BuildInfo {
+    /// The `BUILD_TIMESTAMP` environment variable, or the time the spec was
+    /// collected as an RFC 3339 UTC timestamp
+    pub timestamp: String,
+    /// The `GIT_SHA` environment variable, if set
+    pub commit: Option<String>,
+}
+
+impl BuildInfo {
+    /// Read the build info from the environment of the app
+    #[must_use]
+    pub fn from_env() -> Self {
+        let non_empty = |name| std::env::var(name).ok().filter(|value| !value.is_empty());
+        Self {
+            timestamp: non_empty(BUILD_TIMESTAMP_ENV).unwrap_or_else(|| {
+                let secs = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|elapsed| elapsed.as_secs())
+                    .unwrap_or_default();
+                rfc3339(secs)
+            }),
+            commit: non_empty(GIT_SHA_ENV),
+        }
+    }
+
+    /// Add the build info to the spec as the `x-build` extension
+    pub fn apply(&self, spec: &mut OpenApi) {
+        if let Ok(value) = serde_json::to_value(self) {
+            spec.extensions
+                .get_or_insert_with(Default::default)
+                .insert("x-build".to_string(), value);
+        }
+    }
+
+    /// The `x-build` extension of the spec, if any
+    #[must_use]
+    pub fn of(spec: &OpenApi) -> Option<Self> {
+        let value = spec.extensions.as_ref()?.get("x-build")?;
+        serde_json::from_value(value.clone()).ok()
+    }
+}
+
+/// Format seconds since the Unix epoch as an RFC 3339 UTC timestamp
+fn rfc3339(secs: u64) -> String {
+    let days = secs / 86_400;
+    let secs_of_day = secs % 86_400;
+    // Civil date from days since the epoch, from Howard Hinnant's
+    // `civil_from_days`
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let day_of_era = z - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        secs_of_day / 3_600,
+        secs_of_day % 3_600 / 60,
+        secs_of_day % 60
+    )
+}
+
+/// Adds an endpoint returning the `x-build` extension and `info.version` of
+/// the given `OpenAPI` spec as JSON, to check which spec is live after a
+/// deploy
+pub fn add_version_endpoint<T>(
+    app: AxumRouter<T>,
+    spec: Arc<CachedSpec>,
+    url: &str,
+) -> AxumRouter<T>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    let body = SpecRendering::new(spec, |spec| {
+        let build = BuildInfo::of(spec.spec());
+        serde_json::json!({
+            "version": spec.spec().info.version,
+            "timestamp": build.as_ref().map(|build| &build.timestamp),
+            "commit": build.as_ref().and_then(|build| build.commit.as_ref()),
+        })
+        .to_string()
+    });
+    route_with_trailing_slash(
+        app,
+        url,
+        get(move || {
+            let body = body.get();
+            async move { ([(header::CONTENT_TYPE, "application/json")], body) }
+        }),
+    )
+}
+
+/// Adds an endpoint serving a Markdown rendering of the given `OpenAPI` spec
+///
+/// The spec is rendered on the first request, and again after it's replaced
+#[cfg(feature = "markdown")]
+pub fn add_markdown_endpoint<T>(
+    app: AxumRouter<T>,
+    spec: Arc<CachedSpec>,
+    url: &str,
+) -> AxumRouter<T>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    let markdown = SpecRendering::new(spec, |spec| openapi_to_markdown(spec.spec()));
+    route_with_trailing_slash(
+        app,
+        url,
+        get(move || {
+            let markdown = markdown.get();
+            async move {
+                (
+                    [(header::CONTENT_TYPE, "text/markdown; charset=utf-8")],
+                    markdown,
+                )
+            }
+        })
+        .layer(CompressionLayer::new()),
+    )
+}
+
+/// Render the `OpenAPI` spec as Markdown, with the operations grouped by
+/// their first tag followed by the component schemas
+#[cfg(feature = "markdown")]
+#[must_use]
+pub fn openapi_to_markdown(spec: &OpenApi) -> String {
+    let mut by_tag: BTreeMap<&str, Vec<_>> = BTreeMap::new();
+    for (method, path, operation) in crate::openapi::operations(&spec.paths) {
+        let tag = operation
+            .tags
+            .as_ref()
+            .and_then(|tags| tags.first())
+            .map_or("default", String::as_str);
+        by_tag
+            .entry(tag)
+            .or_default()
+            .push((method, path, operation));
+    }
+
+    let mut markdown = String::new();
+    let _ = writeln!(markdown, "# {} {}\n", spec.info.title, spec.info.version);
+    if let Some(description) = &spec.info.description {
+        let _ = writeln!(markdown, "{description}\n");
+    }
+
+    for (tag, operations) in by_tag {
+        let _ = writeln!(markdown, "## {tag}\n");
+        for (method, path, operation) in operations {
+            write_markdown_operation(&mut markdown, method, path, operation);
+        }
+    }
+
+    if let Some(components) = spec.components.as_ref().filter(|c| !c.schemas.is_empty()) {
+        let _ = writeln!(markdown, "## Schemas\n");
+        for (name, schema) in &components.schemas {
+            let json = serde_json::to_string_pretty(schema).unwrap_or_default();
+            let _ = writeln!(markdown, "### {name}\n\n```json\n{json}\n```\n");
+        }
+    }
+
+    markdown
+}
+
+#[cfg(feature = "markdown")]
+fn write_markdown_operation(
+    markdown: &mut String,
+    method: &str,
+    path: &str,
+    operation: &Operation,
+) {
+    let _ = writeln!(markdown, "### `{method} {path}`\n");
+    for text in [&operation.summary, &operation.description]
+        .into_iter()
+        .flatten()
+    {
+        let _ = writeln!(markdown, "{text}\n");
+    }
+
+    if let Some(parameters) = operation.parameters.as_ref().filter(|p| !p.is_empty()) {
+        let _ = writeln!(markdown, "**Parameters**\n");
+        let _ = writeln!(markdown, "| Name | In | Required | Description |");
+        let _ = writeln!(markdown, "| --- | --- | --- | --- |");
+        for parameter in parameters {
+            let location = serde_json::to_value(&parameter.parameter_in)
+                .ok()
+                .and_then(|value| value.as_str().map(ToString::to_string))
+                .unwrap_or_default();
+            let required = if parameter.required == Required::True {
+                "yes"
+            } else {
+                "no"
+            };
+            let _ = writeln!(
+                markdown,
+                "| `{}` | {location} | {required} | {} |",
+                parameter.name,
+                parameter.description.as_deref().unwrap_or_default()
+            );
+        }
+        markdown.push('\n');
+    }
+
+    if let Some(request_body) = &operation.request_body {
+        let _ = writeln!(markdown, "**Request body**\n");
+        for (content_type, content) in &request_body.content {
+            let _ = writeln!(
+                markdown,
+                "- `{content_type}`: {}",
+                markdown_schema_name(content)
+            );
+        }
+        markdown.push('\n');
+    }
+
+    let _ = writeln!(markdown, "**Responses**\n");
+    for (status, response) in &operation.responses.responses {
+        match response {
+            RefOr::T(response) => {
+                let _ = writeln!(markdown, "- `{status}`: {}", response.description);
+                for (content_type, content) in &response.content {
+                    let _ = writeln!(
+                        markdown,
+                        "  - `{content_type}`: {}",
+                        markdown_schema_name(content)
+                    );
+                }
+            }
+            RefOr::Ref(reference) => {
+                let _ = writeln!(markdown, "- `{status}`: `{}`", reference.ref_location);
+            }
+        }
+    }
+    markdown.push('\n');
+}
+
+/// Name of the referenced schema of the content
+#[cfg(feature = "markdown")]
+fn markdown_schema_name(content: &Content) -> String {
+    match &content.schema {
+        Some(RefOr::Ref(reference)) => reference
+            .ref_location
+            .rsplit('/')
+            .next()
+            .unwrap_or_default()
+            .to_string(),
+        Some(RefOr::T(_)) => "inline schema".to_string(),
+        None => "no schema".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use axum::{http::Request, response::Html};
+    use flate2::read::GzDecoder;
+    use rstest::rstest;
+    use tower::ServiceExt;
+    use utoipa::openapi::{Info, OpenApiBuilder};
+
+    use super::*;
+
+    fn test_spec() -> Arc<CachedSpec> {
+        Arc::new(
+            CachedSpec::new(
+                OpenApiBuilder::new()
+                    .info(Info::new("Loco Demo Test", "1.0.0"))
+                    .build(),
+            )
+            .unwrap(),
+        )
+    }
+
+    #[rstest]
+    #[case("<html><head><title>Redoc</title></head><body></body></html>")]
+    #[case("<html><head></head><body></body></html>")]
+    #[tokio::test]
+    async fn test_page_branding(#[case] page: &'static str) {
+        let app: AxumRouter = add_page_branding(
+            AxumRouter::new().route("/redoc", get(move || async move { Html(page) })),
+            Some("Albums <API>"),
+            Some("/static/favicon.png"),
+        );
+
+        let res = app
+            .oneshot(Request::get("/redoc").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let body = axum::body::to_bytes(res.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let html = std::str::from_utf8(&body).unwrap();
+
+        assert!(html.contains(
+            r#"<title>Albums &lt;API&gt;</title><link rel="icon" href="/static/favicon.png"></head>"#
+        ));
+        assert_eq!(html.matches("<title>").count(), 1);
+    }
+
+    #[cfg(feature = "metrics")]
+    #[test]
+    fn test_metrics() {
+        use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+        let app: AxumRouter = add_metrics(add_spec_endpoints(
+            AxumRouter::new(),
+            test_spec(),
+            &Some("/openapi.json".to_string()),
+            &None,
+        ));
+
+        metrics::with_local_recorder(&recorder, || {
+            tokio::runtime::Builder::new_current_thread()
+                .build()
+                .unwrap()
+                .block_on(async {
+                    for _ in 0..2 {
+                        app.clone()
+                            .oneshot(Request::get("/openapi.json").body(Body::empty()).unwrap())
+                            .await
+                            .unwrap();
+                    }
+                });
+        });
+
+        let hits = snapshotter
+            .snapshot()
+            .into_vec()
+            .into_iter()
+            .find_map(|(key, _, _, value)| {
+                let key = key.key();
+                let labels = key
+                    .labels()
+                    .map(|label| (label.key().to_string(), label.value().to_string()))
+                    .collect::<Vec<_>>();
+                (key.name() == REQUESTS_METRIC
+                    && labels.contains(&("endpoint".to_string(), "/openapi.json".to_string()))
+                    && labels.contains(&("status".to_string(), "200".to_string())))
+                .then_some(value)
+            });
+        assert_eq!(hits, Some(DebugValue::Counter(2)));
+
+        let app: AxumRouter = add_metrics(AxumRouter::new());
+        assert!(!app.has_routes());
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn test_group_spec_endpoints_serve_replaced_specs() {
+        let group_specs = |title: &str| {
+            BTreeMap::from([(
+                "admin".to_string(),
+                OpenApiBuilder::new()
+                    .info(Info::new(title, "1.0.0"))
+                    .build(),
+            )])
+        };
+        let specs = set_openapi_group_specs(group_specs("First boot")).unwrap();
+        let app = json_spec_router(Arc::clone(&specs["admin"]));
+
+        set_openapi_group_specs(group_specs("Second boot")).unwrap();
+
+        let res = app
+            .oneshot(Request::get("/openapi.json").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let body = axum::body::to_bytes(res.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["info"]["title"], "Second boot");
+        assert_eq!(
+            get_openapi_group_spec("admin").map(|spec| spec.info.title),
+            Some("Second boot".to_string())
+        );
+        set_openapi_group_specs(BTreeMap::new()).unwrap();
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn test_extend_openapi_spec() {
+        #[utoipa::path(get, path = "/api/plugins/reports", responses((status = 200, description = "Reports")))]
+        async fn reports() {}
+
+        set_openapi_spec(OpenApi::default()).unwrap();
+        let app: AxumRouter =
+            add_openapi_endpoints(AxumRouter::new(), &Some("/openapi.json".to_string()), &None);
+
+        extend_openapi_spec(OpenApiRouter::<()>::new().routes(utoipa_axum::routes!(reports)))
+            .unwrap();
+
+        let res = app
+            .oneshot(Request::get("/openapi.json").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let body = axum::body::to_bytes(res.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert!(json["paths"]["/api/plugins/reports"]["get"].is_object());
+        assert!(get_openapi_spec()
+            .paths
+            .paths
+            .contains_key("/api/plugins/reports"));
+    }
+
+    #[rstest]
+    #[case(Some("internal.example.com"), StatusCode::OK)]
+    #[case(Some("Internal.Example.com:5150"), StatusCode::OK)]
+    #[case(Some("public.example.com"), StatusCode::NOT_FOUND)]
+    #[case(None, StatusCode::NOT_FOUND)]
+    #[case(Some("docs.example.com:8443"), StatusCode::OK)]
+    #[case(Some("Docs.Example.com:8443"), StatusCode::OK)]
+    #[case(Some("docs.example.com:443"), StatusCode::NOT_FOUND)]
+    #[case(Some("docs.example.com"), StatusCode::NOT_FOUND)]
+    #[case(Some("[::1]:5150"), StatusCode::OK)]
+    #[tokio::test]
+    async fn test_allowed_hosts(#[case] host: Option<&str>, #[case] expected: StatusCode) {
+        let app: AxumRouter = add_allowed_hosts(
+            AxumRouter::new().route("/redoc", get(|| async { "docs" })),
+            &[
+                "internal.example.com".to_string(),
+                "docs.example.com:8443".to_string(),
+                "[::1]".to_string(),
+            ],
+        );
+
+        let mut request = Request::get("/redoc");
+        if let Some(host) = host {
+            request = request.header(header::HOST, host);
+        }
+        let res = app
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(res.status(), expected);
+    }
+
+    #[tokio::test]
+    async fn test_allowed_hosts_unrestricted() {
+        let app: AxumRouter = add_allowed_hosts(
+            AxumRouter::new().route("/redoc", get(|| async { "docs" })),
+            &[],
+        );
+
+        let res = app
+            .oneshot(
+                Request::get("/redoc")
+                    .header(header::HOST, "public.example.com")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+
+    #[test]
+    fn test_page_branding_without_head() {
+        assert_eq!(
+            brand_html("<redoc></redoc>", Some("Albums API"), None),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn test_lean_spec_endpoint() {
+        let spec = members::from_value(&serde_json::json!({
+            "openapi": "3.1.0",
+            "info": { "title": "Loco Demo Test", "version": "1.0.0", "description": "Albums" },
+            "paths": {
+                "/api/album": {
+                    "get": {
+                        "summary": "Get an album",
+                        "description": "Returns the album",
+                        "responses": { "200": { "description": "Album" } }
+                    }
+                }
+            },
+            "components": {
+                "schemas": {
+                    "Album": {
+                        "type": "object",
+                        "description": "An album",
+                        "properties": { "description": { "type": "string" } },
+                        "examples": [{ "description": "Live recording" }]
+                    }
+                }
+            },
+            "webhooks": {
+                "album.created": {
+                    "post": {
+                        "description": "Sent when an album is created",
+                        "responses": { "200": { "description": "Received" } }
+                    }
+                }
+            }
+        }))
+        .unwrap();
+        let lean = Arc::new(CachedSpec::from_document(strip_descriptions(&spec).unwrap()).unwrap());
+
+        let app: AxumRouter = add_spec_endpoints(
+            AxumRouter::new(),
+            lean,
+            &Some(LEAN_SPEC_URL.to_string()),
+            &None,
+        );
+        let res = app
+            .oneshot(Request::get(LEAN_SPEC_URL).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(res.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert!(json["info"].get("description").is_none());
+        assert!(json["paths"]["/api/album"]["get"].get("summary").is_none());
+        assert!(json["components"]["schemas"]["Album"]
+            .get("description")
+            .is_none());
+        assert_eq!(
+            json["components"]["schemas"]["Album"]["properties"]["description"]["type"],
+            "string"
+        );
+        assert_eq!(
+            json["components"]["schemas"]["Album"]["examples"],
+            serde_json::json!([{ "description": "Live recording" }])
+        );
+        assert_eq!(
+            json["paths"]["/api/album"]["get"]["responses"]["200"]["description"],
+            "Album"
+        );
+        assert!(json["webhooks"]["album.created"]["post"]["responses"]["200"].is_object());
+        assert!(json["webhooks"]["album.created"]["post"]
+            .get("description")
+            .is_none());
+        assert!(!body.contains("Returns the album"));
+    }
+
+    #[test]
+    fn test_rfc3339() {
+        assert_eq!(rfc3339(0), "1970-01-01T00:00:00Z");
+        assert_eq!(rfc3339(951_782_400), "2000-02-29T00:00:00Z");
+        assert_eq!(rfc3339(1_790_000_000), "2026-09-21T14:13:20Z");
+    }
+
+    #[tokio::test]
+    async fn test_version_endpoint() {
+        let mut spec = OpenApiBuilder::new()
+            .info(Info::new("Loco Demo Test", "1.2.0"))
+            .build();
+        BuildInfo {
+            timestamp: "2026-10-16T08:00:00Z".to_string(),
+            commit: Some("4f2c1e9".to_string()),
+        }
+        .apply(&mut spec);
+        let spec = CachedSpec::new(spec).unwrap();
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(spec.json()).unwrap()["x-build"]["commit"],
+            "4f2c1e9"
+        );
+
+        let app: AxumRouter =
+            add_version_endpoint(AxumRouter::new(), Arc::new(spec), "/openapi/version");
+        let res = app
+            .oneshot(
+                Request::get("/openapi/version")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(res.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap(),
+            serde_json::json!({
+                "version": "1.2.0",
+                "timestamp": "2026-10-16T08:00:00Z",
+                "commit": "4f2c1e9",
+            })
+        );
+    }
+
+    #[test]
+    fn test_cached_spec_matches_spec() {
+        let spec = test_spec();
+
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(spec.json()).unwrap(),
+            serde_json::to_value(spec.spec()).unwrap()
+        );
+        assert_eq!(spec.yaml(), spec.spec().to_yaml().unwrap().as_bytes());
+    }
+
+    #[rstest]
+    #[case(true)]
+    #[case(false)]
+    fn test_json_pretty(#[case] json_pretty: bool) {
+        let spec = CachedSpec::with_json_pretty(
+            OpenApiBuilder::new()
+                .info(Info::new("Loco Demo Test", "1.0.0"))
+                .build(),
+            json_pretty,
+        )
+        .unwrap();
+        let json = std::str::from_utf8(spec.json()).unwrap();
+
+        assert_eq!(json.contains("\n  \""), json_pretty);
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(json).unwrap(),
+            serde_json::to_value(spec.spec()).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_yaml_without_anchors() {
+        // The anchors of a spec written by hand are expanded when it's read,
+        // the served YAML never has any
+        let spec = serde_yaml::from_str::<OpenApi>(
+            r"
+openapi: 3.1.0
+info: &info
+  title: album
+  version: 1.0.0
+paths: {}
+components:
+  schemas:
+    Album: &album
+      type: object
+    Photo: *album
+",
+        )
+        .unwrap();
+        let spec = CachedSpec::with_json_pretty(spec, false).unwrap();
+        let yaml = std::str::from_utf8(spec.yaml()).unwrap();
+
+        assert!(!yaml
+            .lines()
+            .map(str::trim_start)
+            .any(|line| line.contains(": &")
+                || line.contains(": *")
+                || line.starts_with("- &")
+                || line.starts_with("- *")));
+        assert_eq!(spec.check_yaml_round_trip(), Ok(()));
+        assert_eq!(spec.yaml_etag(), etag(spec.yaml()));
+    }
+
+    #[test]
+    fn test_yaml_round_trip() {
+        let spec = test_spec();
+
+        assert_eq!(spec.check_yaml_round_trip(), Ok(()));
+        let parsed: OpenApi = serde_yaml::from_slice(spec.yaml()).unwrap();
+        assert_eq!(
+            serde_json::to_value(parsed).unwrap(),
+            serde_json::to_value(spec.spec()).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_compressed_json_spec_decodes_to_spec() {
+        let spec = test_spec();
+        let app: AxumRouter = add_spec_endpoints(
+            AxumRouter::new(),
+            Arc::clone(&spec),
+            &Some("/openapi.json".to_string()),
+            &None,
+        );
+
+        let res = app
+            .oneshot(
+                Request::get("/openapi.json")
+                    .header(header::ACCEPT_ENCODING, "gzip")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(res.headers().get(header::CONTENT_ENCODING).unwrap(), "gzip");
+
+        let body = axum::body::to_bytes(res.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let mut json = String::new();
+        GzDecoder::new(&body[..]).read_to_string(&mut json).unwrap();
+
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&json).unwrap(),
+            serde_json::to_value(spec.spec()).unwrap()
+        );
+    }
+
+    fn json_spec_router(spec: Arc<CachedSpec>) -> AxumRouter {
+        add_spec_endpoints(
+            AxumRouter::new(),
+            spec,
+            &Some("/openapi.json".to_string()),
+            &None,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_json_spec_etag() {
+        let spec = test_spec();
+
+        let res = json_spec_router(Arc::clone(&spec))
+            .oneshot(Request::get("/openapi.json").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let etag = res.headers().get(header::ETAG).unwrap().clone();
+        assert_eq!(etag, spec.json_etag());
+        // The compressed responses share the tag, so it can't be strong
+        assert!(spec.json_etag().starts_with("W/"));
+
+        let res = json_spec_router(Arc::clone(&spec))
+            .oneshot(
+                Request::get("/openapi.json")
+                    .header(header::IF_NONE_MATCH, etag.clone())
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::NOT_MODIFIED);
+        let body = axum::body::to_bytes(res.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert!(body.is_empty());
+
+        let res = json_spec_router(Arc::clone(&spec))
+            .oneshot(
+                Request::get("/openapi.json")
+                    .header(header::ACCEPT_ENCODING, "gzip")
+                    .header(header::IF_NONE_MATCH, etag)
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::NOT_MODIFIED);
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn test_spec_handlers_with_headers() {
+        let spec = set_openapi_spec((*test_spec()).spec().clone()).unwrap();
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::IF_NONE_MATCH,
+            HeaderValue::from_str(spec.json_etag()).unwrap(),
+        );
+
+        // The handlers without headers always send the spec
+        let res = openapi_spec_json().await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let res = openapi_spec_json_with_headers(headers).await.unwrap();
+        assert_eq!(res.status(), StatusCode::NOT_MODIFIED);
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::IF_NONE_MATCH,
+            HeaderValue::from_str(spec.yaml_etag()).unwrap(),
+        );
+        let res = openapi_spec_yaml().await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let res = openapi_spec_yaml_with_headers(headers).await.unwrap();
+        assert_eq!(res.status(), StatusCode::NOT_MODIFIED);
+    }
+
+    #[tokio::test]
+    async fn test_json_spec_filtered_by_tag() {
+        let spec: OpenApi = serde_json::from_value(serde_json::json!({
+            "openapi": "3.1.0",
+            "info": { "title": "Loco Demo Test", "version": "1.0.0" },
+            "tags": [{ "name": "album" }, { "name": "artist" }],
+            "paths": {
+                "/api/album": {
+                    "get": {
+                        "tags": ["album"],
+                        "responses": { "200": { "description": "Album", "content": {
+                            "application/json": { "schema": { "$ref": "#/components/schemas/Album" } }
+                        } } }
+                    },
+                    "delete": {
+                        "tags": ["admin"],
+                        "responses": { "204": { "description": "Deleted" } }
+                    }
+                },
+                "/api/artist": {
+                    "get": {
+                        "tags": ["artist"],
+                        "responses": { "200": { "description": "Artist", "content": {
+                            "application/json": { "schema": { "$ref": "#/components/schemas/Artist" } }
+                        } } }
+                    }
+                }
+            },
+            "components": {
+                "schemas": {
+                    "Album": {
+                        "type": "object",
+                        "properties": { "tracks": { "$ref": "#/components/schemas/Track" } }
+                    },
+                    "Track": { "type": "object" },
+                    "Artist": { "type": "object" }
+                }
+            }
+        }))
+        .unwrap();
+        let spec = Arc::new(CachedSpec::new(spec).unwrap());
+
+        let res = json_spec_router(Arc::clone(&spec))
+            .oneshot(
+                Request::get("/openapi.json?tag=album")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(res.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+        let paths = json["paths"].as_object().unwrap();
+        assert_eq!(paths.keys().collect::<Vec<_>>(), vec!["/api/album"]);
+        assert!(paths["/api/album"].get("delete").is_none());
+        let schemas = json["components"]["schemas"].as_object().unwrap();
+        assert_eq!(schemas.keys().collect::<Vec<_>>(), vec!["Album", "Track"]);
+        assert_eq!(json["tags"], serde_json::json!([{ "name": "album" }]));
+
+        let res = json_spec_router(Arc::clone(&spec))
+            .oneshot(Request::get("/openapi.json").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let body = axum::body::to_bytes(res.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(&body, spec.json());
+    }
+
+    #[tokio::test]
+    async fn test_json_spec_head() {
+        let spec = test_spec();
+
+        let res = json_spec_router(Arc::clone(&spec))
+            .oneshot(Request::head("/openapi.json").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers()[header::CONTENT_TYPE], JSON_CONTENT_TYPE);
+        assert_eq!(res.headers()[header::ETAG], spec.json_etag());
+        assert_eq!(
+            res.headers()[header::CONTENT_LENGTH],
+            spec.json().len().to_string()
+        );
+        let body = axum::body::to_bytes(res.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert!(body.is_empty());
+    }
+
+    #[rstest]
+    #[case("/openapi.json", "/openapi.json")]
+    #[case("/openapi.json", "/openapi.json/")]
+    #[case("/openapi.json/", "/openapi.json")]
+    #[case("/openapi.json/", "/openapi.json/")]
+    #[tokio::test]
+    async fn test_json_spec_trailing_slash(#[case] json_url: &str, #[case] request_url: &str) {
+        let app: AxumRouter = add_spec_endpoints(
+            AxumRouter::new(),
+            test_spec(),
+            &Some(json_url.to_string()),
+            &None,
+        );
+
+        let res = app
+            .oneshot(Request::get(request_url).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+
+    #[rstest]
+    #[case(&[], None, None)]
+    #[case(&[("x-forwarded-host", "api.example.com"), ("x-forwarded-proto", "https")], None, Some("https://api.example.com"))]
+    #[case(&[("x-forwarded-host", "api.example.com, proxy.internal")], Some("/api/v2"), Some("http://api.example.com/api/v2"))]
+    #[case(&[("x-forwarded-proto", "https")], Some("http://localhost:5150/api"), Some("https://localhost:5150/api"))]
+    #[case(&[("x-forwarded-host", "evil.example.com")], Some("/api"), Some("/api"))]
+    #[case(&[("x-forwarded-host", "api.example.com"), ("x-forwarded-proto", "javascript")], Some("/api"), Some("/api"))]
+    #[tokio::test]
+    async fn test_dynamic_servers(
+        #[case] forwarded: &[(&str, &str)],
+        #[case] server_url: Option<&str>,
+        #[case] expected: Option<&str>,
+    ) {
+        let spec = Arc::new(
+            CachedSpec::new(
+                OpenApiBuilder::new()
+                    .servers(server_url.map(|url| vec![Server::new(url)]))
+                    .build(),
+            )
+            .unwrap(),
+        );
+        let app: AxumRouter = route_spec_endpoints(
+            AxumRouter::new(),
+            spec,
+            &Some("/openapi.json".to_string()),
+            &None,
+            SpecOptions {
+                dynamic_servers: true,
+                allowed_hosts: lowercase_hosts(&[
+                    "api.example.com".to_string(),
+                    "localhost".to_string(),
+                ]),
+                ..Default::default()
+            },
+        );
+
+        let mut request = Request::get("/openapi.json").header(header::HOST, "localhost:5150");
+        for (name, value) in forwarded {
+            request = request.header(*name, *value);
+        }
+        let res = app
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let body = axum::body::to_bytes(res.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+        assert_eq!(json["servers"][0]["url"].as_str(), expected);
+    }
+
+    #[test]
+    fn test_forwarded_origin_needs_allowed_hosts() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "x-forwarded-host",
+            HeaderValue::from_static("api.example.com"),
+        );
+
+        assert_eq!(forwarded_origin(&headers, &[]), None);
+        assert_eq!(
+            forwarded_origin(&headers, &lowercase_hosts(&["API.example.com".to_string()])),
+            Some("http://api.example.com".to_string())
+        );
+    }
+
+    #[rstest]
+    #[case(SpecOptions::default(), "application/json")]
+    #[case(
+        SpecOptions { json_content_type: "application/openapi+json".into(), ..Default::default() },
+        "application/openapi+json"
+    )]
+    #[tokio::test]
+    async fn test_json_content_type(#[case] options: SpecOptions, #[case] content_type: &str) {
+        let app: AxumRouter = route_spec_endpoints(
+            AxumRouter::new(),
+            test_spec(),
+            &Some("/openapi.json".to_string()),
+            &None,
+            options,
+        );
+
+        let res = app
+            .oneshot(Request::get("/openapi.json").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers()[header::CONTENT_TYPE], content_type);
+    }
+
+    #[rstest]
+    #[case(true, true, StatusCode::OK, StatusCode::OK)]
+    #[case(false, true, StatusCode::NOT_FOUND, StatusCode::OK)]
+    #[case(true, false, StatusCode::OK, StatusCode::NOT_FOUND)]
+    #[tokio::test]
+    async fn test_serve_formats(
+        #[case] serve_json: bool,
+        #[case] serve_yaml: bool,
+        #[case] json_status: StatusCode,
+        #[case] yaml_status: StatusCode,
+    ) {
+        let app: AxumRouter = route_spec_endpoints(
+            AxumRouter::new(),
+            test_spec(),
+            &Some("/openapi.json".to_string()),
+            &Some("/openapi.yaml".to_string()),
+            SpecOptions {
+                serve_json,
+                serve_yaml,
+                ..Default::default()
+            },
+        );
+
+        for (url, status) in [
+            ("/openapi.json", json_status),
+            ("/openapi.yaml", yaml_status),
+        ] {
+            let res = app
+                .clone()
+                .oneshot(Request::get(url).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+            assert_eq!(res.status(), status, "{url}");
+        }
+    }
+
+    #[rstest]
+    #[case(None, "application/json")]
+    #[case(Some("application/json"), "application/json")]
+    #[case(Some("application/yaml"), "application/yaml")]
+    #[case(Some("text/html, application/x-yaml;q=0.9, */*"), "application/yaml")]
+    #[case(Some("*/*"), "application/json")]
+    #[case(Some("application/json;q=0.1, application/yaml"), "application/yaml")]
+    #[case(
+        Some("application/yaml;q=0, application/json;q=0.5"),
+        "application/json"
+    )]
+    #[case(Some("Application/YAML"), "application/yaml")]
+    #[tokio::test]
+    async fn test_negotiated_spec_endpoint(
+        #[case] accept: Option<&str>,
+        #[case] content_type: &str,
+    ) {
+        let spec = test_spec();
+        let app: AxumRouter =
+            add_negotiated_spec_endpoint(AxumRouter::new(), Arc::clone(&spec), "/openapi");
+
+        let mut request = Request::get("/openapi");
+        if let Some(accept) = accept {
+            request = request.header(header::ACCEPT, accept);
+        }
+        let res = app
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.headers()[header::CONTENT_TYPE], content_type);
+        assert_eq!(res.headers()[header::VARY], "accept");
+        let body = axum::body::to_bytes(res.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let expected = if content_type == "application/yaml" {
+            spec.yaml()
+        } else {
+            spec.json()
+        };
+        assert_eq!(&body, expected);
+    }
+
+    #[rstest]
+    #[case(None)]
+    #[case(Some("edge-1234"))]
+    #[tokio::test]
+    async fn test_request_id(#[case] request_id: Option<&str>) {
+        let app: AxumRouter = add_request_id(json_spec_router(test_spec()));
+
+        let mut request = Request::get("/openapi.json");
+        if let Some(request_id) = request_id {
+            request = request.header(REQUEST_ID_HEADER, request_id);
+        }
+        let res = app
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(res.status(), StatusCode::OK);
+        let id = res.headers()[REQUEST_ID_HEADER].to_str().unwrap();
+        match request_id {
+            Some(request_id) => assert_eq!(id, request_id),
+            None => assert!(is_valid_request_id(id)),
+        }
+    }
+
+    #[test]
+    fn test_alternate_trailing_slash() {
+        assert_eq!(
+            alternate_trailing_slash("/docs"),
+            Some("/docs/".to_string())
+        );
+        assert_eq!(
+            alternate_trailing_slash("/docs/"),
+            Some("/docs".to_string())
+        );
+        assert_eq!(alternate_trailing_slash("/"), None);
+    }
+
+    #[cfg(feature = "markdown")]
+    #[tokio::test]
+    async fn test_markdown_endpoint() {
+        let app: AxumRouter = add_markdown_endpoint(AxumRouter::new(), test_spec(), "/api.md");
+
+        let res = app
+            .oneshot(Request::get("/api.md").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert!(res.headers()[header::CONTENT_TYPE]
+            .to_str()
+            .unwrap()
+            .starts_with("text/markdown"));
+        let body = axum::body::to_bytes(res.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert!(body.starts_with(b"# Loco Demo Test 1.0.0"));
+    }
+
+    #[test]
+    fn test_etag_matches() {
+        assert!(etag_matches("\"abc\"", "\"abc\""));
+        assert!(etag_matches("W/\"abc\"", "\"abc\""));
+        assert!(etag_matches("\"abc\"", "W/\"abc\""));
+        assert!(etag_matches("W/\"abc\"", "W/\"abc\""));
+        assert!(etag_matches("\"xyz\", \"abc\"", "\"abc\""));
+        assert!(etag_matches("*", "\"abc\""));
+        assert!(!etag_matches("\"xyz\"", "\"abc\""));
+    }
+}
@@ -0,0 +1,114 @@
+use loco_rs::{Error, Result};
+use utoipa::openapi::OpenApi;
+
+use crate::openapi::{for_each_operation, unresolved_refs};
+
+/// Structural problems in the `OpenAPI` spec
+///
+/// Checks that every `$ref` resolves within the spec and that every operation
+/// documents at least one response
+#[must_use]
+pub fn validate_spec(spec: &OpenApi) -> Vec<String> {
+    let mut errors = Vec::new();
+
+    let mut paths = spec.paths.clone();
+    for_each_operation(&mut paths, |path, operation| {
+        if operation.responses.responses.is_empty() {
+            let operation_id = operation.operation_id.as_deref().unwrap_or_default();
+            errors.push(format!(
+                "operation `{operation_id}` of `{path}` has no responses"
+            ));
+        }
+    });
+
+    match serde_json::to_value(spec) {
+        Ok(json) => {
+            for reference in unresolved_refs(&json) {
+                errors.push(format!("`$ref` `{reference}` does not resolve"));
+            }
+        }
+        Err(err) => errors.push(format!("spec can't be serialized: {err}")),
+    }
+
+    errors
+}
+
+/// Validate the spec, logging the problems or failing when `strict` is set
+///
+/// # Errors
+///
+/// Will return `Err` in strict mode when the spec is invalid
+pub fn check_spec(name: &str, spec: &OpenApi, strict: bool) -> Result<()> {
+    let errors = validate_spec(spec);
+    if errors.is_empty() {
+        return Ok(());
+    }
+    if strict {
+        return Err(Error::string(&format!(
+            "invalid OpenAPI spec `{name}`: {}",
+            errors.join(", ")
+        )));
+    }
+    for error in &errors {
+        tracing::error!(spec = name, "invalid OpenAPI spec: {error}");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use utoipa::openapi::{
+        path::{HttpMethod, OperationBuilder, PathItem, PathsBuilder},
+        Ref,
+    };
+    use utoipa_axum::{router::OpenApiRouter, routes};
+
+    use super::*;
+
+    #[utoipa::path(get, path = "/api/album", responses((status = 200, description = "Album")))]
+    async fn get_album() {}
+
+    #[utoipa::path(get, path = "/api/broken")]
+    async fn broken() {}
+
+    #[test]
+    fn test_valid_spec() {
+        let (_, spec) = OpenApiRouter::<()>::new()
+            .routes(routes!(get_album))
+            .split_for_parts();
+
+        assert!(validate_spec(&spec).is_empty());
+        assert!(check_spec("main", &spec, true).is_ok());
+    }
+
+    #[test]
+    fn test_operation_without_responses() {
+        let (_, spec) = OpenApiRouter::<()>::new()
+            .routes(routes!(broken))
+            .split_for_parts();
+
+        assert_eq!(
+            validate_spec(&spec),
+            vec!["operation `broken` of `/api/broken` has no responses".to_string()]
+        );
+        assert!(check_spec("main", &spec, true).is_err());
+        assert!(check_spec("main", &spec, false).is_ok());
+    }
+
+    #[test]
+    fn test_dangling_ref() {
+        let operation = OperationBuilder::new()
+            .response("200", Ref::from_response_name("Missing"))
+            .build();
+        let mut spec = OpenApi::default();
+        spec.paths = PathsBuilder::new()
+            .path("/api/missing", PathItem::new(HttpMethod::Get, operation))
+            .build();
+
+        assert_eq!(
+            validate_spec(&spec),
+            vec!["`$ref` `#/components/responses/Missing` does not resolve".to_string()]
+        );
+        assert!(check_spec("main", &spec, true).is_err());
+    }
+}
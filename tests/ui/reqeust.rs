@@ -1,7 +1,7 @@
 use async_trait::async_trait;
-use loco_openapi::openapi::clear_routes;
-use loco_openapi::prelude::routes;
-use loco_openapi::{
+use by_loco_openapi::openapi::clear_routes;
+use by_loco_openapi::prelude::routes;
+use by_loco_openapi::{
     auth::{set_jwt_location, SecurityAddon},
     prelude::openapi, // Make sure openapi macro is imported
 };
@@ -13,11 +13,13 @@ use loco_rs::{
     environment::Environment,
     prelude::*,
     task::Tasks,
+    tests_cfg::db::Migrator,
 };
 use rstest::rstest;
 use serde::Serialize; // Added import for Album
 use serde_json::{json, Value};
 use std::collections::BTreeMap;
+use std::path::Path;
 use utoipa::{OpenApi, ToSchema}; // Added ToSchema
                                  // Define a minimal TestApp
 use insta::{assert_json_snapshot, assert_snapshot, assert_yaml_snapshot, with_settings};
@@ -68,6 +70,9 @@ fn config_test() -> Config {
     let mut initializers = BTreeMap::new();
     let mut openapi_conf = serde_json::Map::new();
 
+    // The server URL of the spec
+    openapi_conf.insert("public_url".to_string(), json!("http://localhost:5150"));
+
     // Configure endpoints to match test requests
     openapi_conf.insert(
         "redoc".to_string(),
@@ -93,6 +98,14 @@ fn config_test() -> Config {
             "spec_yaml_url": "/swagger/openapi.yaml"
         }),
     );
+    openapi_conf.insert(
+        "elements".to_string(),
+        json!({
+            "url": "/docs"
+        }),
+    );
+
+    openapi_conf.insert("well_known".to_string(), json!(true));
 
     initializers.insert("openapi".to_string(), Value::Object(openapi_conf));
     config.initializers = Some(initializers);
@@ -120,7 +133,7 @@ impl Hooks for TestApp {
 
     async fn initializers(_ctx: &AppContext) -> Result<Vec<Box<dyn Initializer>>> {
         Ok(vec![Box::new(
-            loco_openapi::OpenapiInitializerWithSetup::new(
+            by_loco_openapi::OpenapiInitializerWithSetup::new(
                 |ctx| {
                     #[derive(OpenApi)]
                     #[openapi(
@@ -146,7 +159,7 @@ impl Hooks for TestApp {
         config: Config,
     ) -> Result<BootResult> {
         // Assuming Migrator is not needed as per previous iteration
-        create_app::<Self>(mode, environment, config).await
+        create_app::<Self, Migrator>(mode, environment, config).await
     }
 
     async fn connect_workers(_ctx: &AppContext, _queue: &Queue) -> Result<()> {
@@ -155,7 +168,13 @@ impl Hooks for TestApp {
 
     fn register_tasks(_tasks: &mut Tasks) {}
 
-    // Removed truncate and seed as they are not part of the Hooks trait
+    async fn truncate(_ctx: &AppContext) -> Result<()> {
+        Ok(())
+    }
+
+    async fn seed(_ctx: &AppContext, _base: &Path) -> Result<()> {
+        Ok(())
+    }
 }
 
 // Test for OpenAPI UI Endpoints
@@ -178,6 +197,8 @@ impl Hooks for TestApp {
     case("/swagger/openapi.json"),
     case("/swagger/openapi.yaml")
 )]
+#[cfg_attr(feature = "elements", case("/docs"), case("/docs/openapi.json"))]
+#[case("/.well-known/openapi.json")]
 #[case("")]
 #[tokio::test]
 #[serial_test::serial]
@@ -219,3 +240,83 @@ async fn test_openapi_ui_endpoints(#[case] endpoint: &str) {
     .await;
     clear_routes();
 }
+
+// Test the UI base paths work both with and without a trailing slash
+#[rstest]
+#[cfg_attr(feature = "redoc", case("/redoc/", "/redoc"))]
+#[cfg_attr(feature = "scalar", case("/scalar/", "/scalar"))]
+#[cfg_attr(feature = "swagger", case("/swagger", "/swagger/"))]
+#[cfg_attr(feature = "elements", case("/docs/", "/docs"))]
+#[case("", "")]
+#[tokio::test]
+#[serial_test::serial]
+async fn test_openapi_ui_trailing_slash_redirects(#[case] endpoint: &str, #[case] location: &str) {
+    loco_rs::testing::request::request::<TestApp, _, _>(|rq, _ctx| async move {
+        if endpoint.is_empty() {
+            return;
+        }
+        let res = rq.get(endpoint).await;
+
+        assert!(
+            res.status_code().is_redirection(),
+            "Expected {} to redirect, got {}",
+            endpoint,
+            res.status_code()
+        );
+        assert_eq!(
+            res.headers().get("location").unwrap().to_str().unwrap(),
+            location
+        );
+    })
+    .await;
+    clear_routes();
+}
+
+// Test the spec can be inspected without starting a server
+#[cfg(feature = "test-support")]
+#[tokio::test]
+#[serial_test::serial]
+async fn test_build_spec_for_hooks() {
+    // A spec left by an earlier boot must be replaced by this one
+    let mut stale_spec = utoipa::openapi::OpenApi::default();
+    stale_spec.info.title = "Stale spec".to_string();
+    by_loco_openapi::utils::set_openapi_spec(stale_spec).unwrap();
+
+    let spec = by_loco_openapi::testing::build_spec_for_hooks::<TestApp>()
+        .await
+        .unwrap();
+
+    assert_ne!(spec.info.title, "Stale spec");
+    assert!(spec.paths.paths.contains_key("/api/album/get_album"));
+    assert!(spec
+        .components
+        .as_ref()
+        .is_some_and(|components| components.schemas.contains_key("Album")));
+    clear_routes();
+}
+
+// Test the UI router can be mounted by the app, nested under another router
+#[cfg(feature = "redoc")]
+#[tokio::test]
+#[serial_test::serial]
+async fn test_build_ui_router_nested() {
+    use tower::ServiceExt;
+
+    loco_rs::testing::request::request::<TestApp, _, _>(|_rq, _ctx| async move {
+        let app =
+            axum::Router::new().nest("/internal", by_loco_openapi::build_ui_router().unwrap());
+
+        let res = app
+            .oneshot(
+                axum::http::Request::get("/internal/redoc/openapi.json")
+                    .body(axum::body::Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(res.status(), axum::http::StatusCode::OK);
+    })
+    .await;
+    clear_routes();
+}